@@ -0,0 +1,68 @@
+use std::fmt;
+use axum_web_starter::errors::{AppError, ErrorBody};
+use reqwest::{Response, StatusCode};
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server rejected the request with one of its own errors
+    Api(AppError),
+    /// An unsuccessful response without an `ErrorBody`, such as a malformed JSON rejection
+    /// or a proxy in between giving up
+    Status { status: StatusCode, body: String },
+    /// The request could not be sent, or the response could not be read or decoded
+    Http(reqwest::Error),
+    /// The client was built with an unusable base URL or auth header
+    Config(String),
+}
+
+impl ClientError {
+    pub(crate) async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return ClientError::Http(e),
+        };
+        serde_json::from_str::<ErrorBody>(&body)
+            .ok()
+            .and_then(|error| AppError::from_response(status, error))
+            .map(ClientError::Api)
+            .unwrap_or(ClientError::Status { status, body })
+    }
+
+    /// The status the server answered with, if it answered
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api(e) => Some(e.status()),
+            ClientError::Status { status, .. } => Some(*status),
+            ClientError::Http(e) => e.status(),
+            ClientError::Config(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Api(e) => write!(f, "{}", e),
+            ClientError::Status { status, body } => write!(f, "{}: {}", status, body),
+            ClientError::Http(e) => write!(f, "Request failed: {}", e),
+            ClientError::Config(message) => write!(f, "Invalid client configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Api(e) => Some(e),
+            ClientError::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Http(e)
+    }
+}
@@ -0,0 +1,229 @@
+//! Typed client for the customer and seller API of `axum_web_starter`, decoding into the
+//! server's own models and errors.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), axum_web_client::ClientError> {
+//! use axum_web_client::Client;
+//! use axum_web_starter::models::customer::CustomerPayload;
+//! use axum_web_starter::models::page::Page;
+//!
+//! let client = Client::builder("http://localhost:3000/v1").bearer_token("secret").build()?;
+//! let payload = CustomerPayload { name: "Ada".to_string(), email: "ada@example.com".to_string() };
+//! let customer = client.create_customer(&payload).await?;
+//! let first_page = client.list_customers(Page::new(20, 0)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod error;
+pub mod retry;
+pub mod routes;
+
+use axum_web_starter::models::customer::{Customer, CustomerPayload};
+use axum_web_starter::models::page::Page;
+use axum_web_starter::models::seller::{Seller, SellerPayload};
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use reqwest::{Method, RequestBuilder, Response, Url};
+use uuid::Uuid;
+
+pub use error::ClientError;
+pub use retry::RetryPolicy;
+use routes::Operation;
+
+pub struct ClientBuilder {
+    base_url: String,
+    http: Option<reqwest::Client>,
+    auth: Option<Result<HeaderValue, String>>,
+    retry: RetryPolicy,
+    idempotency_keys: bool,
+}
+
+impl ClientBuilder {
+    /// Send `Authorization: Bearer <token>` with every request
+    pub fn bearer_token(self, token: &str) -> Self {
+        self.auth_header(&format!("Bearer {}", token))
+    }
+
+    /// Send this `Authorization` header with every request
+    pub fn auth_header(mut self, value: &str) -> Self {
+        self.auth = Some(HeaderValue::from_str(value).map_err(|e| e.to_string()).map(|mut value| {
+            value.set_sensitive(true);
+            value
+        }));
+        self
+    }
+
+    /// The `reqwest` client to send with, e.g. one with timeouts or a client certificate
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Give every create a fresh `Idempotency-Key`, so it is retried like the other calls
+    /// without creating a record twice. On by default; the keys are stored in the database,
+    /// so turn it off against a server without one.
+    pub fn idempotency_keys(mut self, enabled: bool) -> Self {
+        self.idempotency_keys = enabled;
+        self
+    }
+
+    pub fn build(self) -> Result<Client, ClientError> {
+        let base_url = Url::parse(&self.base_url).map_err(|e| ClientError::Config(format!("{}: {}", self.base_url, e)))?;
+        if base_url.cannot_be_a_base() {
+            return Err(ClientError::Config(format!("{} cannot be a base URL", self.base_url)));
+        }
+        Ok(Client {
+            http: self.http.unwrap_or_default(),
+            base_url,
+            auth: self.auth.transpose().map_err(ClientError::Config)?,
+            retry: self.retry,
+            idempotency_keys: self.idempotency_keys,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    auth: Option<HeaderValue>,
+    retry: RetryPolicy,
+    idempotency_keys: bool,
+}
+
+impl Client {
+    /// A client with the default retry policy and no authentication. `base_url` may include
+    /// a version prefix such as `/v1`.
+    pub fn new(base_url: &str) -> Result<Client, ClientError> {
+        Self::builder(base_url).build()
+    }
+
+    pub fn builder(base_url: &str) -> ClientBuilder {
+        ClientBuilder {
+            base_url: base_url.to_string(),
+            http: None,
+            auth: None,
+            retry: RetryPolicy::default(),
+            idempotency_keys: true,
+        }
+    }
+
+    pub async fn create_customer(&self, payload: &CustomerPayload) -> Result<Customer, ClientError> {
+        let response = self.send(&routes::CREATE_CUSTOMER, None, |request| request.json(payload)).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Create the customer, or update the one with the same email
+    pub async fn upsert_customer(&self, payload: &CustomerPayload) -> Result<Customer, ClientError> {
+        let response = self.send(&routes::UPSERT_CUSTOMER, None, |request| request.json(payload)).await?;
+        Ok(response.json().await?)
+    }
+
+    /// One page of customers in ID order, or all of them with `Page::default()`
+    pub async fn list_customers(&self, page: Page) -> Result<Vec<Customer>, ClientError> {
+        let response = self.send(&routes::LIST_CUSTOMERS, None, |request| request.query(&page)).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_customer(&self, id: Uuid) -> Result<Customer, ClientError> {
+        let response = self.send(&routes::GET_CUSTOMER, Some(&id.to_string()), |request| request).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_customer_by_email(&self, email: &str) -> Result<Customer, ClientError> {
+        let response = self.send(&routes::GET_CUSTOMER_BY_EMAIL, Some(email), |request| request).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn update_customer(&self, id: Uuid, payload: &CustomerPayload) -> Result<Customer, ClientError> {
+        let response = self.send(&routes::UPDATE_CUSTOMER, Some(&id.to_string()), |request| request.json(payload)).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_customer(&self, id: Uuid) -> Result<(), ClientError> {
+        self.send(&routes::DELETE_CUSTOMER, Some(&id.to_string()), |request| request).await?;
+        Ok(())
+    }
+
+    pub async fn create_seller(&self, payload: &SellerPayload) -> Result<Seller, ClientError> {
+        let response = self.send(&routes::CREATE_SELLER, None, |request| request.json(payload)).await?;
+        Ok(response.json().await?)
+    }
+
+    /// One page of sellers in ID order, or all of them with `Page::default()`
+    pub async fn list_sellers(&self, page: Page) -> Result<Vec<Seller>, ClientError> {
+        let response = self.send(&routes::LIST_SELLERS, None, |request| request.query(&page)).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_seller(&self, id: Uuid) -> Result<Seller, ClientError> {
+        let response = self.send(&routes::GET_SELLER, Some(&id.to_string()), |request| request).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn update_seller(&self, id: Uuid, payload: &SellerPayload) -> Result<Seller, ClientError> {
+        let response = self.send(&routes::UPDATE_SELLER, Some(&id.to_string()), |request| request.json(payload)).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_seller(&self, id: Uuid) -> Result<(), ClientError> {
+        self.send(&routes::DELETE_SELLER, Some(&id.to_string()), |request| request).await?;
+        Ok(())
+    }
+
+    /// The URL of an operation, with its one path parameter filled in and escaped
+    fn url(&self, path: &str, param: Option<&str>) -> Url {
+        let mut url = self.base_url.clone();
+        // Checked by the builder
+        let mut segments = url.path_segments_mut().unwrap();
+        segments.pop_if_empty();
+        for segment in path.trim_start_matches('/').split('/') {
+            segments.push(if segment.starts_with('{') { param.unwrap_or_default() } else { segment });
+        }
+        drop(segments);
+        url
+    }
+
+    /// Send the request built by `build`, retrying it with backoff while that is safe: always
+    /// for GET, PUT and DELETE, and for POST only under an `Idempotency-Key`
+    async fn send(
+        &self,
+        operation: &Operation,
+        param: Option<&str>,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response, ClientError> {
+        let url = self.url(operation.path, param);
+        let idempotency_key = (operation.method == Method::POST && self.idempotency_keys)
+            .then(|| Uuid::new_v4().to_string());
+        let retryable = operation.method != Method::POST || idempotency_key.is_some();
+
+        let mut retry = 0;
+        loop {
+            let mut request = build(self.http.request(operation.method.clone(), url.clone()));
+            if let Some(auth) = &self.auth {
+                request = request.header(AUTHORIZATION, auth.clone());
+            }
+            if let Some(key) = &idempotency_key {
+                request = request.header("Idempotency-Key", key);
+            }
+            let may_retry = retryable && retry < self.retry.max_retries;
+
+            let backoff = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if may_retry && RetryPolicy::retries_status(response.status()) => {
+                    self.retry.backoff(retry, retry::retry_after(&response))
+                }
+                Ok(response) => return Err(ClientError::from_response(response).await),
+                Err(e) if may_retry && RetryPolicy::retries_error(&e) => self.retry.backoff(retry, None),
+                Err(e) => return Err(e.into()),
+            };
+            tokio::time::sleep(backoff).await;
+            retry += 1;
+        }
+    }
+}
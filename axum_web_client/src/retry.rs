@@ -0,0 +1,57 @@
+use std::time::Duration;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+
+/// How often and how patiently idempotent requests are retried after a connection failure,
+/// a timeout, or a 429, 502, 503 or 504 answer
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for every retry after it
+    pub initial_backoff: Duration,
+    /// Upper bound of a single wait, including one asked for with `Retry-After`
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request once
+    pub fn none() -> Self {
+        RetryPolicy { max_retries: 0, ..Self::default() }
+    }
+
+    /// The wait before retry number `retry` (starting at 0), at least what the server asked for
+    pub(crate) fn backoff(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self.initial_backoff.saturating_mul(2u32.saturating_pow(retry));
+        backoff.max(retry_after.unwrap_or_default()).min(self.max_backoff)
+    }
+
+    pub(crate) fn retries_status(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    pub(crate) fn retries_error(e: &reqwest::Error) -> bool {
+        e.is_connect() || e.is_timeout()
+    }
+}
+
+/// `Retry-After` in seconds; the HTTP date form is not used by the server and is ignored
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
@@ -0,0 +1,38 @@
+use reqwest::Method;
+
+/// An endpoint of the server, with its path as written in the OpenAPI document
+pub struct Operation {
+    pub method: Method,
+    pub path: &'static str,
+}
+
+pub const CREATE_CUSTOMER: Operation = Operation { method: Method::POST, path: "/customers" };
+pub const UPSERT_CUSTOMER: Operation = Operation { method: Method::PUT, path: "/customers" };
+pub const LIST_CUSTOMERS: Operation = Operation { method: Method::GET, path: "/customers" };
+pub const GET_CUSTOMER: Operation = Operation { method: Method::GET, path: "/customers/{id}" };
+pub const GET_CUSTOMER_BY_EMAIL: Operation = Operation { method: Method::GET, path: "/customers/by-email/{email}" };
+pub const UPDATE_CUSTOMER: Operation = Operation { method: Method::PUT, path: "/customers/{id}" };
+pub const DELETE_CUSTOMER: Operation = Operation { method: Method::DELETE, path: "/customers/{id}" };
+
+pub const CREATE_SELLER: Operation = Operation { method: Method::POST, path: "/sellers" };
+pub const LIST_SELLERS: Operation = Operation { method: Method::GET, path: "/sellers" };
+pub const GET_SELLER: Operation = Operation { method: Method::GET, path: "/sellers/{id}" };
+pub const UPDATE_SELLER: Operation = Operation { method: Method::PUT, path: "/sellers/{id}" };
+pub const DELETE_SELLER: Operation = Operation { method: Method::DELETE, path: "/sellers/{id}" };
+
+/// Every operation the client calls. The tests check each one against `ApiDoc`, so a route
+/// renamed on the server fails the build of the client instead of its callers.
+pub const OPERATIONS: &[Operation] = &[
+    CREATE_CUSTOMER,
+    UPSERT_CUSTOMER,
+    LIST_CUSTOMERS,
+    GET_CUSTOMER,
+    GET_CUSTOMER_BY_EMAIL,
+    UPDATE_CUSTOMER,
+    DELETE_CUSTOMER,
+    CREATE_SELLER,
+    LIST_SELLERS,
+    GET_SELLER,
+    UPDATE_SELLER,
+    DELETE_SELLER,
+];
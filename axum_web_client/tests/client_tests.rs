@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use axum_web_client::routes::OPERATIONS;
+use axum_web_client::{Client, ClientError, RetryPolicy};
+use axum_web_starter::api_doc::ApiDoc;
+use axum_web_starter::config::AppConfig;
+use axum_web_starter::errors::AppError;
+use axum_web_starter::models::customer::CustomerPayload;
+use axum_web_starter::models::page::Page;
+use axum_web_starter::models::seller::SellerPayload;
+use reqwest::Method;
+use tokio::net::TcpListener;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+/// Serve a router on a free port and return its base URL
+async fn serve(app: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// The application with customers, sellers and the cache in memory, under `/v1`
+async fn spawn_app() -> Client {
+    let vars = HashMap::from([
+        ("CACHE_BACKEND", "memory"),
+        ("STORAGE_BACKEND", "memory"),
+        ("REDIS_URL", "redis://127.0.0.1:1"),
+        ("DATABASE_URL", "postgresql://root@127.0.0.1:1/unused"),
+        ("GRPC_PORT", "0"),
+        ("JOB_WORKERS", "0"),
+    ]);
+    let config = AppConfig::from_vars(|name| vars.get(name).map(|value| value.to_string())).unwrap();
+    let base_url = serve(axum_web_starter::app(&config).await.unwrap()).await;
+    // Idempotency keys are stored in the database, which these tests run without
+    Client::builder(&format!("{}/v1", base_url)).idempotency_keys(false).build().unwrap()
+}
+
+fn customer_payload(name: &str) -> CustomerPayload {
+    CustomerPayload { name: name.to_string(), email: format!("{}.{}@example.com", name, Uuid::new_v4()) }
+}
+
+fn seller_payload(name: &str) -> SellerPayload {
+    SellerPayload { name: name.to_string(), company_name: format!("{} Ltd", name) }
+}
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy { max_retries: 3, initial_backoff: Duration::from_millis(1), max_backoff: Duration::from_millis(10) }
+}
+
+#[test]
+fn test_operations_are_documented() {
+    let doc = ApiDoc::openapi();
+    for operation in OPERATIONS {
+        let item = doc.paths.paths.get(operation.path)
+            .unwrap_or_else(|| panic!("{} is not in ApiDoc", operation.path));
+        let documented = [
+            (Method::GET, &item.get),
+            (Method::POST, &item.post),
+            (Method::PUT, &item.put),
+            (Method::DELETE, &item.delete),
+        ]
+        .into_iter()
+        .find_map(|(method, documented)| (method == operation.method).then_some(documented))
+        .unwrap_or_else(|| panic!("Unexpected method {}", operation.method));
+        assert!(documented.is_some(), "{} {} is not in ApiDoc", operation.method, operation.path);
+    }
+}
+
+#[tokio::test]
+async fn test_customer_round_trip() {
+    let client = spawn_app().await;
+
+    let customer = client.create_customer(&customer_payload("ada")).await.unwrap();
+    assert_eq!(client.get_customer(customer.id).await.unwrap().email, customer.email);
+    assert_eq!(client.get_customer_by_email(&customer.email).await.unwrap().id, customer.id);
+
+    let updated = client.update_customer(customer.id, &customer_payload("grace")).await.unwrap();
+    assert_eq!(updated.name, "grace");
+
+    client.delete_customer(customer.id).await.unwrap();
+    let error = client.get_customer(customer.id).await.unwrap_err();
+    assert!(matches!(error, ClientError::Api(AppError::NotFound(_))), "{}", error);
+}
+
+#[tokio::test]
+async fn test_duplicate_email_decodes_conflict() {
+    let client = spawn_app().await;
+    let payload = customer_payload("ada");
+    let customer = client.create_customer(&payload).await.unwrap();
+
+    match client.create_customer(&payload).await {
+        Err(ClientError::Api(AppError::Conflict { existing_id, .. })) => assert_eq!(existing_id, Some(customer.id)),
+        other => panic!("Expected a conflict, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_list_customers_page() {
+    let client = spawn_app().await;
+    for name in ["ada", "grace", "edsger"] {
+        client.create_customer(&customer_payload(name)).await.unwrap();
+    }
+
+    let all = client.list_customers(Page::default()).await.unwrap();
+    assert_eq!(all.len(), 3);
+    let page = client.list_customers(Page::new(2, 1)).await.unwrap();
+    let ids: Vec<Uuid> = page.iter().map(|customer| customer.id).collect();
+    let mut expected: Vec<Uuid> = all.iter().map(|customer| customer.id).collect();
+    expected.sort();
+    assert_eq!(ids, expected[1..3]);
+
+    let error = client.list_customers(Page::new(0, 0)).await.unwrap_err();
+    assert!(matches!(error, ClientError::Api(AppError::BadRequest(_))), "{}", error);
+}
+
+#[tokio::test]
+async fn test_update_seller() {
+    let client = spawn_app().await;
+    let seller = client.create_seller(&seller_payload("ada")).await.unwrap();
+
+    let updated = client.update_seller(seller.id, &seller_payload("grace")).await.unwrap();
+    assert_eq!(updated.id, seller.id);
+    assert_eq!(client.get_seller(seller.id).await.unwrap().company_name, "grace Ltd");
+    assert_eq!(client.list_sellers(Page::new(10, 0)).await.unwrap().len(), 1);
+
+    client.delete_seller(seller.id).await.unwrap();
+    let error = client.update_seller(seller.id, &seller_payload("grace")).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn test_idempotent_calls_are_retried() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let app = Router::new().route("/sellers", get(move || {
+        let attempt = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if attempt < 2 {
+                Err((StatusCode::SERVICE_UNAVAILABLE, [("Retry-After", "0")]))
+            } else {
+                Ok(Json(Vec::<serde_json::Value>::new()))
+            }
+        }
+    }));
+    let client = Client::builder(&serve(app).await).retry(fast_retries()).build().unwrap();
+
+    assert!(client.list_sellers(Page::default()).await.unwrap().is_empty());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_creates_without_idempotency_key_are_not_retried() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let app = Router::new().route("/sellers", axum::routing::post(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async { StatusCode::SERVICE_UNAVAILABLE }
+    }));
+    let client = Client::builder(&serve(app).await)
+        .retry(fast_retries())
+        .idempotency_keys(false)
+        .build()
+        .unwrap();
+
+    let error = client.create_seller(&seller_payload("ada")).await.unwrap_err();
+    assert!(matches!(error, ClientError::Status { .. }), "{}", error);
+    assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_auth_header_is_sent() {
+    let app = Router::new().route("/customers", get(|headers: HeaderMap| async move {
+        match headers.get("authorization").and_then(|value| value.to_str().ok()) {
+            Some("Bearer secret") => Ok(Json(Vec::<serde_json::Value>::new())),
+            _ => Err(StatusCode::UNAUTHORIZED),
+        }
+    }));
+    let base_url = serve(app).await;
+
+    let client = Client::builder(&base_url).bearer_token("secret").build().unwrap();
+    assert!(client.list_customers(Page::default()).await.is_ok());
+    let anonymous = Client::new(&base_url).unwrap();
+    let error = anonymous.list_customers(Page::default()).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+}
@@ -0,0 +1,84 @@
+//! Encode and decode times of each cache format, with and without zstd, for a plain customer
+//! and for a customer cached with its addresses. Run with `cargo bench --bench cache_codec`.
+
+use axum_web_starter::cache_codec::{CacheCodec, CacheFormat};
+use axum_web_starter::models::address::{AddressKind, CustomerAddress};
+use axum_web_starter::models::customer::Customer;
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::hint::black_box;
+use uuid::Uuid;
+
+fn customer() -> Customer {
+    Customer { id: Uuid::new_v4(), name: "Ada Lovelace".to_string(), email: "ada@example.com".to_string() }
+}
+
+/// What `GET /customers/{id}?expand=addresses` caches
+fn customer_with_addresses(count: usize) -> (Customer, Vec<CustomerAddress>) {
+    let customer = customer();
+    let addresses = (0..count)
+        .map(|index| CustomerAddress {
+            id: Uuid::new_v4(),
+            customer_id: customer.id,
+            kind: if index % 2 == 0 { AddressKind::Shipping } else { AddressKind::Billing },
+            line1: format!("{} Analytical Engine Street", index + 1),
+            line2: Some("Floor 2".to_string()),
+            city: "London".to_string(),
+            region: None,
+            postal_code: "NW1 2DB".to_string(),
+            country_code: "GB".to_string(),
+            phone: Some("+442071234567".to_string()),
+            is_default: index < 2,
+            created_at: Utc::now(),
+        })
+        .collect();
+    (customer, addresses)
+}
+
+fn codecs() -> Vec<(String, CacheCodec)> {
+    CacheFormat::ALL
+        .into_iter()
+        .flat_map(|format| {
+            let name = format!("{:?}", format).to_lowercase();
+            [
+                (name.clone(), CacheCodec { format, compress_above: None, zstd_level: 3 }),
+                (format!("{}+zstd", name), CacheCodec { format, compress_above: Some(0), zstd_level: 3 }),
+            ]
+        })
+        .collect()
+}
+
+/// Encode and decode `value` with every codec, printing the size of each entry
+fn bench_value<T: Serialize + DeserializeOwned>(c: &mut Criterion, value_name: &str, value: &T) {
+    let codecs = codecs();
+    for (name, codec) in &codecs {
+        println!("{} as {}: {} bytes", value_name, name, codec.encode(value).unwrap().len());
+    }
+
+    let mut group = c.benchmark_group(format!("encode_{}", value_name));
+    for (name, codec) in &codecs {
+        group.bench_with_input(BenchmarkId::from_parameter(name), codec, |b, codec| {
+            b.iter(|| codec.encode(black_box(value)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("decode_{}", value_name));
+    for (name, codec) in &codecs {
+        let entry = codec.encode(value).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(name), &entry, |b, entry| {
+            b.iter(|| codec.decode::<T>(black_box(entry)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_codecs(c: &mut Criterion) {
+    bench_value(c, "customer", &customer());
+    bench_value(c, "customer_with_addresses", &customer_with_addresses(20));
+}
+
+criterion_group!(benches, bench_codecs);
+criterion_main!(benches);
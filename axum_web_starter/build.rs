@@ -0,0 +1,15 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Only the server speaks gRPC; a models-only build needs neither protoc nor the descriptors
+    #[cfg(feature = "server")]
+    {
+        use std::env;
+        use std::path::PathBuf;
+
+        // The descriptor set backs the gRPC reflection service
+        let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+        tonic_build::configure()
+            .file_descriptor_set_path(out_dir.join("sillycat_descriptor.bin"))
+            .compile_protos(&["proto/customer.proto", "proto/seller.proto"], &["proto"])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+-- Marketplace orders placed by a customer with a seller.
+-- All amounts are stored in integer minor units (e.g. cents) of `currency`.
+CREATE TABLE IF NOT EXISTS orders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    customer_id UUID NOT NULL REFERENCES customers (id) ON DELETE RESTRICT,
+    seller_id UUID NOT NULL REFERENCES sellers (id) ON DELETE RESTRICT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    currency CHAR(3) NOT NULL,
+    total_amount BIGINT NOT NULL CHECK (total_amount >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS orders_customer_id_idx ON orders (customer_id);
+CREATE INDEX IF NOT EXISTS orders_seller_id_idx ON orders (seller_id);
+
+CREATE TABLE IF NOT EXISTS order_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    sku TEXT NOT NULL,
+    description TEXT NOT NULL,
+    quantity INT4 NOT NULL CHECK (quantity > 0),
+    unit_price BIGINT NOT NULL CHECK (unit_price >= 0),
+    line_total BIGINT NOT NULL CHECK (line_total >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS order_items_order_id_idx ON order_items (order_id);
@@ -0,0 +1,17 @@
+-- Products listed by sellers. Prices are integer minor units of `currency`.
+CREATE TABLE IF NOT EXISTS products (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    seller_id UUID NOT NULL REFERENCES sellers (id) ON DELETE CASCADE,
+    sku TEXT NOT NULL,
+    title TEXT NOT NULL,
+    price BIGINT NOT NULL CHECK (price >= 0),
+    currency CHAR(3) NOT NULL,
+    stock_quantity INT4 NOT NULL DEFAULT 0 CHECK (stock_quantity >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (seller_id, sku)
+);
+
+CREATE INDEX IF NOT EXISTS products_title_idx ON products (title);
+
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS product_id UUID REFERENCES products (id) ON DELETE SET NULL;
@@ -0,0 +1,22 @@
+-- Shipping and billing addresses with contact phone numbers, many per customer.
+CREATE TABLE IF NOT EXISTS customer_addresses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    customer_id UUID NOT NULL REFERENCES customers (id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('shipping', 'billing')),
+    line1 TEXT NOT NULL,
+    line2 TEXT,
+    city TEXT NOT NULL,
+    region TEXT,
+    postal_code TEXT NOT NULL,
+    country_code CHAR(2) NOT NULL,
+    phone TEXT,
+    is_default BOOL NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS customer_addresses_customer_id_idx ON customer_addresses (customer_id);
+
+-- At most one default address of each kind per customer
+CREATE UNIQUE INDEX IF NOT EXISTS customer_addresses_one_default_idx
+    ON customer_addresses (customer_id, kind)
+    WHERE is_default;
@@ -0,0 +1,3 @@
+-- Emails identify customers regardless of case. Existing duplicates must be
+-- merged before this migration can be applied.
+CREATE UNIQUE INDEX IF NOT EXISTS customers_email_lower_idx ON customers (lower(email));
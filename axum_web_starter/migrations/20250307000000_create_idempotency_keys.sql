@@ -0,0 +1,16 @@
+-- Responses to POST requests carrying an Idempotency-Key, replayed when the client retries.
+-- In-flight rows expire quickly so a crashed request does not block its key for long.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    principal TEXT NOT NULL,
+    key TEXT NOT NULL,
+    request_hash BYTEA NOT NULL,
+    status TEXT NOT NULL DEFAULT 'in_flight' CHECK (status IN ('in_flight', 'completed')),
+    response_status INT,
+    response_headers JSONB,
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (principal, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_expires_at_idx ON idempotency_keys (expires_at);
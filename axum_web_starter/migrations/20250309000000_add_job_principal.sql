@@ -0,0 +1,3 @@
+-- Who submitted each job. Only they and admins may read it; jobs queued before this
+-- migration belong to nobody and are left to admins.
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS principal TEXT NOT NULL DEFAULT '';
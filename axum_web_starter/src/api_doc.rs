@@ -1,21 +1,85 @@
 use utoipa::OpenApi;
+use crate::cache::{CacheHealth, CircuitState};
+use crate::errors::ErrorBody;
+use crate::models::cache::{CacheDeletion, CacheEntity, CacheStats, WarmCacheRequest};
 use crate::models::customer::Customer;
 use crate::models::customer::CustomerPayload;
+use crate::models::customer::CustomerDetails;
+use crate::models::customer::{CustomerBatchOperation, CustomerBatchRequest};
+use crate::models::batch::{BatchItemResult, BatchMode, BatchResponse};
+use crate::models::transfer::{DataFormat, ImportReport, LineError};
+use crate::models::job::{Job, JobKind, JobPayload, JobStatus};
+use crate::models::search::{SearchRecord, SearchResponse, SearchResult};
+use crate::models::seller::{Seller, SellerPayload};
+use crate::models::address::{AddressKind, CustomerAddress, CustomerAddressPayload};
+use crate::models::order::{
+    Order, OrderAction, OrderItem, OrderItemPayload, OrderPayload, OrderStatus, OrderStatusChange,
+    OrderTransitionPayload,
+};
+use crate::models::product::{Product, ProductPayload};
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         crate::handlers::customer_handler::create_customer_api,
+        crate::handlers::customer_handler::upsert_customer_api,
         crate::handlers::customer_handler::list_customers_api,
+        crate::handlers::customer_handler::get_customer_by_email_api,
         crate::handlers::customer_handler::get_customer_api,
         crate::handlers::customer_handler::update_customer_api,
         crate::handlers::customer_handler::delete_customer_api,
+        crate::handlers::customer_handler::batch_customers_api,
+        crate::handlers::customer_handler::export_customers_api,
+        crate::handlers::customer_handler::import_customers_api,
+        crate::handlers::seller_handler::create_seller_api,
+        crate::handlers::seller_handler::list_sellers_api,
+        crate::handlers::seller_handler::get_seller_api,
+        crate::handlers::seller_handler::update_seller_api,
+        crate::handlers::seller_handler::delete_seller_api,
+        crate::handlers::address_handler::create_address_api,
+        crate::handlers::address_handler::list_addresses_api,
+        crate::handlers::address_handler::get_address_api,
+        crate::handlers::address_handler::update_address_api,
+        crate::handlers::address_handler::delete_address_api,
+        crate::handlers::order_handler::create_order_api,
+        crate::handlers::order_handler::list_orders_api,
+        crate::handlers::order_handler::get_order_api,
+        crate::handlers::order_handler::list_customer_orders_api,
+        crate::handlers::order_handler::list_seller_orders_api,
+        crate::handlers::order_handler::transition_order_api,
+        crate::handlers::order_handler::list_order_history_api,
+        crate::handlers::product_handler::create_product_api,
+        crate::handlers::product_handler::list_seller_products_api,
+        crate::handlers::product_handler::search_products_api,
+        crate::handlers::product_handler::get_product_api,
+        crate::handlers::product_handler::update_product_api,
+        crate::handlers::product_handler::delete_product_api,
+        crate::handlers::job_handler::create_job_api,
+        crate::handlers::job_handler::list_jobs_api,
+        crate::handlers::job_handler::get_job_api,
+        crate::handlers::job_handler::retry_job_api,
+        crate::handlers::search_handler::search_api,
+        crate::handlers::admin_handler::warm_cache,
+        crate::handlers::admin_handler::evict_cache_entry,
+        crate::handlers::admin_handler::delete_cache_prefix,
+        crate::handlers::admin_handler::cache_stats,
     ),
     components(
-        schemas(Customer, CustomerPayload)
+        schemas(ErrorBody, Customer, CustomerPayload, CustomerDetails, CustomerAddress, CustomerAddressPayload, AddressKind,
+            Order, OrderItem, OrderPayload, OrderItemPayload, OrderStatus,
+            OrderAction, OrderTransitionPayload, OrderStatusChange, Product, ProductPayload,
+            BatchMode, BatchItemResult, BatchResponse, CustomerBatchOperation, CustomerBatchRequest,
+            DataFormat, ImportReport, LineError, Job, JobKind, JobPayload, JobStatus,
+            Seller, SellerPayload, SearchRecord, SearchResult, SearchResponse,
+            CacheEntity, WarmCacheRequest, CacheDeletion, CacheStats, CacheHealth, CircuitState)
     ),
     tags(
-        (name = "Customers", description = "API for managing customers")
+        (name = "Customers", description = "API for managing customers"),
+        (name = "Sellers", description = "API for managing sellers"),
+        (name = "Orders", description = "API for managing marketplace orders"),
+        (name = "Products", description = "API for managing seller catalogs"),
+        (name = "Jobs", description = "API for background jobs"),
+        (name = "Search", description = "API for searching customers and sellers")
     )
 )]
 pub struct ApiDoc;
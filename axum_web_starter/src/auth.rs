@@ -0,0 +1,90 @@
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::sync::Arc;
+use crate::config::AppConfig;
+use crate::errors::AppError;
+
+/// Who a request is made on behalf of. Authentication layers insert it into the request
+/// extensions; requests nobody authenticated belong to the anonymous principal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal(pub String);
+
+impl Principal {
+    pub fn anonymous() -> Self {
+        Principal("anonymous".to_string())
+    }
+
+    pub fn from_parts(parts: &Parts) -> Self {
+        parts.extensions.get::<Principal>().cloned().unwrap_or_else(Principal::anonymous)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Principal::from_parts(parts))
+    }
+}
+
+/// Who may call the `/admin` routes: whoever sends `Authorization: Bearer <ADMIN_TOKEN>`, and the
+/// client certificate principals in `ADMIN_PRINCIPALS`
+#[derive(Clone)]
+pub struct AdminAuth {
+    /// Tokens are compared by digest so the comparison takes as long whatever they share
+    token_digest: Option<[u8; 32]>,
+    principals: Arc<Vec<String>>,
+}
+
+impl AdminAuth {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            token_digest: config.admin_token.as_deref().map(|token| Sha256::digest(token).into()),
+            principals: Arc::new(config.admin_principals.clone()),
+        }
+    }
+
+    /// Whether the request carrying `headers` on behalf of `principal` is made by an admin
+    pub fn is_admin(&self, headers: &HeaderMap, principal: &Principal) -> bool {
+        self.authorize(headers, principal).is_ok()
+    }
+
+    /// Reject a request that is not made by an admin: 401 without any credentials, 403 otherwise
+    pub fn authorize(&self, headers: &HeaderMap, principal: &Principal) -> Result<(), AppError> {
+        if self.token_digest.is_none() && self.principals.is_empty() {
+            return Err(AppError::Forbidden("The admin API is disabled".to_string()));
+        }
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if let (Some(token), Some(digest)) = (token, &self.token_digest) {
+            if Sha256::digest(token).as_slice() == digest {
+                return Ok(());
+            }
+        }
+        if self.principals.contains(&principal.0) {
+            return Ok(());
+        }
+        if token.is_none() && *principal == Principal::anonymous() {
+            Err(AppError::Unauthorized("Admin credentials are required".to_string()))
+        } else {
+            Err(AppError::Forbidden("Admin access is not granted".to_string()))
+        }
+    }
+}
+
+/// Middleware rejecting requests that are not made by an admin
+pub async fn require_admin(State(admin): State<AdminAuth>, request: Request, next: Next) -> Response {
+    let (parts, body) = request.into_parts();
+    if let Err(e) = admin.authorize(&parts.headers, &Principal::from_parts(&parts)) {
+        return e.into_response();
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
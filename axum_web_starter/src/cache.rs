@@ -0,0 +1,403 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use opendal::{Buffer, Error, ErrorKind, Operator};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use utoipa::ToSchema;
+use tokio::time::{timeout, Duration, Instant};
+use tracing::{error, info, warn};
+use crate::cache_codec::{CacheCodec, CodecError};
+use crate::config::AppConfig;
+
+/// How values are encoded, when the circuit breaker trips and how long each cache call may take
+#[derive(Debug, Clone, Copy)]
+pub struct CacheSettings {
+    pub codec: CacheCodec,
+    /// Consecutive failures or timeouts that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before one call is let through as a probe
+    pub open_for: Duration,
+    pub read_timeout: Duration,
+    pub write_timeout: Duration,
+    pub delete_timeout: Duration,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            codec: CacheCodec::default(),
+            failure_threshold: 5,
+            open_for: Duration::from_secs(30),
+            read_timeout: Duration::from_millis(100),
+            write_timeout: Duration::from_millis(100),
+            delete_timeout: Duration::from_millis(100),
+        }
+    }
+}
+
+impl CacheSettings {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            codec: CacheCodec {
+                format: config.cache_format,
+                compress_above: config.cache_compress_above,
+                zstd_level: config.cache_zstd_level,
+            },
+            failure_threshold: config.cache_failure_threshold,
+            open_for: config.cache_open_duration,
+            read_timeout: config.cache_read_timeout,
+            write_timeout: config.cache_write_timeout,
+            delete_timeout: config.cache_delete_timeout,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Cache calls go through
+    Closed,
+    /// Cache calls are skipped, and handlers load from the store as on a miss
+    Open,
+    /// One call is probing whether the cache is back
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// The value of the `cache_circuit_state` gauge
+    pub fn gauge(self) -> u8 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// A probe is in flight; another one may start at `until` should it never finish
+    HalfOpen { until: Instant },
+}
+
+/// Counters of the cache and its circuit breaker since startup
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CacheHealth {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Reads that found a current entry
+    pub hits: u64,
+    /// Reads that found nothing usable: no entry, a stale one, or no answer from the cache.
+    /// Reads skipped while the circuit was open only count as `skipped`.
+    pub misses: u64,
+    /// Times the circuit opened
+    pub trips: u64,
+    /// Calls skipped while the circuit was open
+    pub skipped: u64,
+    pub failures: u64,
+    pub timeouts: u64,
+    /// Keys whose write or delete never reached the cache, waiting to be deleted
+    pub pending_deletes: u64,
+}
+
+/// The OpenDAL operator behind a circuit breaker. After `failure_threshold` consecutive failures
+/// or timeouts the circuit opens and every call fails at once, which handlers already treat as
+/// a cache miss, so an unreachable Redis costs nothing instead of a timeout per request. Once
+/// `open_for` has passed, the next call probes the cache and closes the circuit if it succeeds.
+///
+/// A write or delete that fails or is skipped leaves whatever the cache held under its key, so
+/// the key is remembered, read as a miss, and deleted once the cache answers again.
+pub struct Cache {
+    operator: Operator,
+    settings: CacheSettings,
+    circuit: Mutex<Circuit>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    trips: AtomicU64,
+    skipped: AtomicU64,
+    failures: AtomicU64,
+    timeouts: AtomicU64,
+    pending_deletes: Mutex<HashSet<String>>,
+    replaying: AtomicBool,
+}
+
+impl Cache {
+    pub fn new(operator: Operator, settings: CacheSettings) -> Self {
+        Self {
+            operator,
+            settings,
+            circuit: Mutex::new(Circuit::Closed { failures: 0 }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            trips: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            pending_deletes: Mutex::default(),
+            replaying: AtomicBool::new(false),
+        }
+    }
+
+    /// The operator itself, bypassing the circuit breaker
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+
+    pub fn codec(&self) -> CacheCodec {
+        self.settings.codec
+    }
+
+    /// The value cached under `key`, or `None` on a miss. Stale or unreadable entries are misses
+    /// too, so the caller loads the value again and overwrites them.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        // Checked before the read, which may replay the delete only after reading the entry
+        let pending = self.is_pending_delete(key);
+        // Skipped reads count as skipped calls, not as misses
+        let entry = self.try_call("read", self.settings.read_timeout, self.operator.read(key)).await?;
+        let value = match entry {
+            Ok(_) if pending => {
+                info!("Ignoring cache entry {} left behind while the cache was unreachable", key);
+                None
+            }
+            Ok(entry) => match self.settings.codec.decode(&entry.to_vec()) {
+                Ok(value) => {
+                    info!("Cache hit with cache_key = {}", key);
+                    Some(value)
+                }
+                Err(CodecError::Stale) => {
+                    info!("Refreshing stale cache entry {}", key);
+                    None
+                }
+                Err(e) => {
+                    error!("Cache read error for {}: {}", key, e);
+                    None
+                }
+            },
+            Err(e) => {
+                info!("Cache miss with cache_key: {}, with {}", key, e);
+                None
+            }
+        };
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Cache `value` under `key`. Failures are only logged, as the cache can always be skipped.
+    pub async fn put<T: Serialize>(&self, key: &str, value: &T) {
+        match self.settings.codec.encode(value) {
+            Ok(entry) => {
+                if let Err(e) = self.write(key, entry).await {
+                    error!("Cache write error: {}", e);
+                }
+            }
+            Err(e) => error!("Cache write error for {}: {}", key, e),
+        }
+    }
+
+    /// The raw entry under `key`, header byte included
+    pub async fn read(&self, key: &str) -> opendal::Result<Buffer> {
+        self.call("read", self.settings.read_timeout, self.operator.read(key)).await
+    }
+
+    pub async fn write(&self, key: &str, value: impl Into<Buffer>) -> opendal::Result<()> {
+        let value = value.into();
+        let result = self
+            .call("write", self.settings.write_timeout, async { self.operator.write(key, value).await.map(|_| ()) })
+            .await;
+        self.track_pending_delete(key, result.is_ok());
+        result
+    }
+
+    pub async fn delete(&self, key: &str) -> opendal::Result<()> {
+        let result = self.call("delete", self.settings.delete_timeout, self.operator.delete(key)).await;
+        self.track_pending_delete(key, result.is_ok());
+        result
+    }
+
+    /// Whether the backend can list its keys, which `keys` and `delete_prefix` need. Redis
+    /// cannot through OpenDAL.
+    pub fn can_list(&self) -> bool {
+        self.operator.info().full_capability().list
+    }
+
+    /// Every key starting with `prefix`. Listing gets the read timeout, like any other read.
+    pub async fn keys(&self, prefix: &str) -> opendal::Result<Vec<String>> {
+        let list = async { self.operator.list_with(prefix).recursive(true).await };
+        let entries = self.call("list", self.settings.read_timeout, list).await?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .map(|entry| entry.path().to_string())
+            .collect())
+    }
+
+    /// Delete every key starting with `prefix` and return how many there were
+    pub async fn delete_prefix(&self, prefix: &str) -> opendal::Result<usize> {
+        let keys = self.keys(prefix).await?;
+        for key in &keys {
+            self.delete(key).await?;
+        }
+        Ok(keys.len())
+    }
+
+    pub fn health(&self) -> CacheHealth {
+        let circuit = self.circuit.lock().unwrap();
+        let (state, consecutive_failures) = match *circuit {
+            Circuit::Closed { failures } => (CircuitState::Closed, failures),
+            // Reported as half open once the next call would probe
+            Circuit::Open { until } if Instant::now() >= until => (CircuitState::HalfOpen, 0),
+            Circuit::Open { .. } => (CircuitState::Open, 0),
+            Circuit::HalfOpen { .. } => (CircuitState::HalfOpen, 0),
+        };
+        CacheHealth {
+            state,
+            consecutive_failures,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            trips: self.trips.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            pending_deletes: self.pending_deletes.lock().unwrap().len() as u64,
+        }
+    }
+
+    fn is_pending_delete(&self, key: &str) -> bool {
+        self.pending_deletes.lock().unwrap().contains(key)
+    }
+
+    /// Forget `key` once the cache holds what was last written to it, or remember to delete it
+    fn track_pending_delete(&self, key: &str, reached_cache: bool) {
+        let mut pending_deletes = self.pending_deletes.lock().unwrap();
+        if reached_cache {
+            pending_deletes.remove(key);
+        } else if pending_deletes.insert(key.to_string()) {
+            warn!("Cache entry {} may be stale, deleting it once the cache is back", key);
+        }
+    }
+
+    /// Delete the keys left behind while the cache was unreachable. Keys stay pending until
+    /// their delete succeeds, so reads keep missing them meanwhile; the first failure stops the
+    /// replay and counts against the circuit like any other call.
+    async fn replay_pending_deletes(&self) {
+        if self.pending_deletes.lock().unwrap().is_empty() || self.replaying.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let keys: Vec<String> = self.pending_deletes.lock().unwrap().iter().cloned().collect();
+        info!("Replaying {} cache deletes", keys.len());
+        for key in keys {
+            match timeout(self.settings.delete_timeout, self.operator.delete(&key)).await {
+                Ok(Ok(())) => {
+                    self.pending_deletes.lock().unwrap().remove(&key);
+                }
+                Ok(Err(e)) => {
+                    error!("Cache delete replay error for {}: {}", key, e);
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    self.failed();
+                    break;
+                }
+                Err(_) => {
+                    error!("Cache delete replay for {} timed out after {:?}", key, self.settings.delete_timeout);
+                    self.timeouts.fetch_add(1, Ordering::Relaxed);
+                    self.failed();
+                    break;
+                }
+            }
+        }
+        self.replaying.store(false, Ordering::Release);
+    }
+
+    async fn call<T>(
+        &self,
+        operation: &'static str,
+        limit: Duration,
+        call: impl Future<Output = opendal::Result<T>>,
+    ) -> opendal::Result<T> {
+        match self.try_call(operation, limit, call).await {
+            Some(result) => result,
+            None => Err(Error::new(ErrorKind::Unexpected, "cache circuit is open").set_temporary()),
+        }
+    }
+
+    /// Run `call` within `limit` and record the outcome in the circuit, or return `None` without
+    /// running it while the circuit is open
+    async fn try_call<T>(
+        &self,
+        operation: &'static str,
+        limit: Duration,
+        call: impl Future<Output = opendal::Result<T>>,
+    ) -> Option<opendal::Result<T>> {
+        if !self.permit() {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        let result = match timeout(limit, call).await {
+            Ok(Ok(value)) => {
+                self.succeeded();
+                self.replay_pending_deletes().await;
+                Ok(value)
+            }
+            // A missing key is an answer from a working cache
+            Ok(Err(e)) if e.kind() == ErrorKind::NotFound => {
+                self.succeeded();
+                self.replay_pending_deletes().await;
+                Err(e)
+            }
+            Ok(Err(e)) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                self.failed();
+                Err(e)
+            }
+            Err(_) => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                self.failed();
+                Err(Error::new(ErrorKind::Unexpected, format!("cache {} timed out after {:?}", operation, limit))
+                    .set_temporary())
+            }
+        };
+        Some(result)
+    }
+
+    /// Whether a call may go to the cache, turning an expired open circuit into a probe
+    fn permit(&self) -> bool {
+        let mut circuit = self.circuit.lock().unwrap();
+        match *circuit {
+            Circuit::Closed { .. } => true,
+            Circuit::Open { until } | Circuit::HalfOpen { until } if Instant::now() >= until => {
+                *circuit = Circuit::HalfOpen { until: Instant::now() + self.settings.open_for };
+                true
+            }
+            // Only one probe at a time
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => false,
+        }
+    }
+
+    fn succeeded(&self) {
+        let mut circuit = self.circuit.lock().unwrap();
+        if matches!(*circuit, Circuit::HalfOpen { .. }) {
+            info!("Cache is back, closing the circuit");
+        }
+        *circuit = Circuit::Closed { failures: 0 };
+    }
+
+    fn failed(&self) {
+        let mut circuit = self.circuit.lock().unwrap();
+        let failures = match *circuit {
+            Circuit::Closed { failures } => failures + 1,
+            Circuit::HalfOpen { .. } => self.settings.failure_threshold,
+            // A call let through before the circuit opened
+            Circuit::Open { .. } => return,
+        };
+        if failures >= self.settings.failure_threshold {
+            warn!("Cache failed {} times in a row, skipping it for {:?}", failures, self.settings.open_for);
+            self.trips.fetch_add(1, Ordering::Relaxed);
+            *circuit = Circuit::Open { until: Instant::now() + self.settings.open_for };
+        } else {
+            *circuit = Circuit::Closed { failures };
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Version of the entry layout, in the high nibble of the header byte. Entries with another one,
+/// such as the plain JSON text written before there was a header, are stale.
+const VERSION: u8 = 1;
+const COMPRESSED: u8 = 0b1000;
+const FORMAT_MASK: u8 = 0b0111;
+/// Largest value a compressed entry may expand to, so a corrupt entry cannot exhaust memory
+const MAX_DECOMPRESSED_BYTES: u64 = 16 * 1024 * 1024;
+
+/// How cached values are serialized, set with `CACHE_FORMAT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheFormat {
+    Json,
+    MessagePack,
+    /// The most compact, but cannot encode `#[serde(flatten)]` or skipped fields
+    Bincode,
+}
+
+impl CacheFormat {
+    pub const ALL: [CacheFormat; 3] = [CacheFormat::Json, CacheFormat::MessagePack, CacheFormat::Bincode];
+
+    fn id(self) -> u8 {
+        match self {
+            CacheFormat::Json => 1,
+            CacheFormat::MessagePack => 2,
+            CacheFormat::Bincode => 3,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        CacheFormat::ALL.into_iter().find(|format| format.id() == id)
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            CacheFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            // Named fields, so optional ones can be left out like in JSON
+            CacheFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            CacheFormat::Bincode => bincode::serialize(value).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            CacheFormat::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            CacheFormat::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+            CacheFormat::Bincode => bincode::deserialize(bytes).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl FromStr for CacheFormat {
+    type Err = UnknownCacheFormat;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(CacheFormat::Json),
+            "msgpack" => Ok(CacheFormat::MessagePack),
+            "bincode" => Ok(CacheFormat::Bincode),
+            other => Err(UnknownCacheFormat(other.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownCacheFormat(String);
+
+impl fmt::Display for UnknownCacheFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown cache format {}, expected json, msgpack or bincode", self.0)
+    }
+}
+
+impl std::error::Error for UnknownCacheFormat {}
+
+#[derive(Debug)]
+pub enum CodecError {
+    /// Written by another version of the app; refresh the entry
+    Stale,
+    Encode(String),
+    Decode(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Stale => write!(f, "stale cache entry"),
+            CodecError::Encode(message) => write!(f, "failed to encode cache entry: {}", message),
+            CodecError::Decode(message) => write!(f, "failed to decode cache entry: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// Turns values into cache entries and back. An entry is one header byte (the layout version,
+/// whether it is zstd compressed, and its format) followed by the serialized value. Entries are
+/// decoded in the format they were written in, so changing `CACHE_FORMAT` needs no flush.
+#[derive(Debug, Clone, Copy)]
+pub struct CacheCodec {
+    pub format: CacheFormat,
+    /// Values serialized to more bytes than this are compressed, none when unset
+    pub compress_above: Option<usize>,
+    pub zstd_level: i32,
+}
+
+impl Default for CacheCodec {
+    fn default() -> Self {
+        Self { format: CacheFormat::Json, compress_above: Some(1024), zstd_level: 3 }
+    }
+}
+
+impl CacheCodec {
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        let serialized = self.format.serialize(value).map_err(CodecError::Encode)?;
+        let mut header = (VERSION << 4) | self.format.id();
+        let body = match self.compress_above {
+            Some(threshold) if serialized.len() > threshold => {
+                header |= COMPRESSED;
+                zstd::encode_all(serialized.as_slice(), self.zstd_level).map_err(|e| CodecError::Encode(e.to_string()))?
+            }
+            _ => serialized,
+        };
+        let mut entry = Vec::with_capacity(body.len() + 1);
+        entry.push(header);
+        entry.extend_from_slice(&body);
+        Ok(entry)
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, entry: &[u8]) -> Result<T, CodecError> {
+        let (&header, body) = entry.split_first().ok_or(CodecError::Stale)?;
+        if header >> 4 != VERSION {
+            return Err(CodecError::Stale);
+        }
+        let format = CacheFormat::from_id(header & FORMAT_MASK).ok_or(CodecError::Stale)?;
+        if header & COMPRESSED == 0 {
+            return format.deserialize(body).map_err(CodecError::Decode);
+        }
+        let body = decompress(body).map_err(|e| CodecError::Decode(e.to_string()))?;
+        format.deserialize(&body).map_err(CodecError::Decode)
+    }
+}
+
+/// zstd decompression that stops past `MAX_DECOMPRESSED_BYTES` instead of growing without bound
+fn decompress(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    zstd::Decoder::new(body)?
+        .take(MAX_DECOMPRESSED_BYTES + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > MAX_DECOMPRESSED_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("entry expands to more than {} bytes", MAX_DECOMPRESSED_BYTES),
+        ));
+    }
+    Ok(decompressed)
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+use anyhow::{anyhow, bail, Context, Result};
+use crate::config::AppConfig;
+use crate::database;
+use crate::models::batch::BatchMode;
+use crate::models::transfer::DataFormat;
+use crate::repositories::customer_repository::PgCustomerRepository;
+use crate::repositories::seller_repository::PgSellerRepository;
+use crate::transfer;
+
+const IMPORT_USAGE: &str =
+    "Usage: axum_web_starter import <customers|sellers> <file> [--format csv|ndjson] [--best-effort]";
+
+/// Offline load through the same importer as `POST /customers/import` and `POST /sellers/import`
+pub async fn import(config: &AppConfig, args: &[String]) -> Result<()> {
+    let (entity, file) = match args {
+        [entity, file, ..] => (entity.as_str(), file.as_str()),
+        _ => bail!(IMPORT_USAGE),
+    };
+    let mut format = Path::new(file)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(DataFormat::from_extension);
+    let mut mode = BatchMode::Atomic;
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--best-effort" => mode = BatchMode::BestEffort,
+            "--format" => {
+                let value = options.next().ok_or_else(|| anyhow!(IMPORT_USAGE))?;
+                format = Some(DataFormat::from_extension(value).ok_or_else(|| anyhow!("Unknown format: {}", value))?);
+            }
+            other => bail!("Unknown option: {}\n{}", other, IMPORT_USAGE),
+        }
+    }
+    let format = format.ok_or_else(|| anyhow!("Cannot tell the format of {}, pass --format", file))?;
+
+    let data = tokio::fs::read(file).await.with_context(|| format!("Failed to read {}", file))?;
+    let pool = database::connect(config, &config.database_url).await?;
+    let report = match entity {
+        "customers" => transfer::import_customers(&PgCustomerRepository::new(pool), format, mode, &data).await?,
+        "sellers" => transfer::import_sellers(&PgSellerRepository::new(pool), format, mode, &data).await?,
+        other => bail!("Unknown entity: {}\n{}", other, IMPORT_USAGE),
+    };
+
+    for error in &report.errors {
+        eprintln!("line {}: {}", error.line, error.error);
+    }
+    println!("Imported {} {} from {}", report.imported, entity, file);
+    if report.status().is_success() {
+        Ok(())
+    } else {
+        bail!("{} lines rejected, nothing was imported", report.errors.len())
+    }
+}
+
+/// Create the optional search indexes, after `sqlx migrate run`
+pub async fn search_indexes(config: &AppConfig) -> Result<()> {
+    let pool = database::connect(config, &config.database_url).await?;
+    if database::create_search_indexes(&pool).await? {
+        println!("Trigram search indexes are in place");
+    } else {
+        println!("Trigram indexes could not be created, search falls back to substring matching");
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use tokio::time::Duration;
+use crate::cache_codec::CacheFormat;
+
+/// Settings read from the environment (and `.env`) at startup
+pub struct AppConfig {
+    pub database_url: String,
+    /// Replica serving `list_*` and `get_*` queries, none by default
+    pub database_read_url: Option<String>,
+    /// How long reads of a principal stay on the primary after it wrote
+    pub read_your_writes: Duration,
+    pub replica_health_check_interval: Duration,
+    /// Size of each database pool
+    pub db_max_connections: u32,
+    /// Connections each pool keeps open even when idle
+    pub db_min_connections: u32,
+    /// How long a query waits for a free connection before failing with a 503
+    pub db_acquire_timeout: Duration,
+    /// Idle connections above the minimum are closed after this, never when unset
+    pub db_idle_timeout: Option<Duration>,
+    /// Connections are replaced after this, never when unset
+    pub db_max_lifetime: Option<Duration>,
+    /// `statement_timeout` of every connection, none when unset
+    pub db_statement_timeout: Option<Duration>,
+    /// Failed attempts to reach the database at startup before giving up
+    pub db_connect_retries: u32,
+    /// Start without a database and connect on the first query
+    pub db_lazy_connect: bool,
+    /// `postgres`, or `memory` to keep customers, sellers and the cache in process
+    pub storage_backend: String,
+    /// Port of the HTTP (or HTTPS) server
+    pub port: u16,
+    pub redis_url: String,
+    /// `redis`, or `memory` for a cache local to the process
+    pub cache_backend: String,
+    /// `json`, `msgpack` or `bincode`
+    pub cache_format: CacheFormat,
+    /// Cached values larger than this are zstd compressed, none when unset
+    pub cache_compress_above: Option<usize>,
+    pub cache_zstd_level: i32,
+    /// Consecutive cache failures or timeouts after which the cache is skipped
+    pub cache_failure_threshold: u32,
+    /// How long the cache is skipped before a call probes it again
+    pub cache_open_duration: Duration,
+    pub cache_read_timeout: Duration,
+    pub cache_write_timeout: Duration,
+    pub cache_delete_timeout: Duration,
+    /// Number of background job workers, 0 disables them
+    pub job_workers: usize,
+    /// How long an idle worker waits before polling the queue again
+    pub job_poll_interval: Duration,
+    /// `development` enables developer tooling such as the GraphiQL playground
+    pub app_env: String,
+    /// Deepest field nesting a GraphQL query may use
+    pub graphql_max_depth: usize,
+    /// Highest cost a GraphQL query may add up to, with list fields weighted by their size
+    pub graphql_max_complexity: usize,
+    /// Port of a separate gRPC server, 0 serves gRPC on the HTTP port
+    pub grpc_port: u16,
+    /// Announced in the `Deprecation` header of the unprefixed routes, 2025-03-09 by default
+    pub legacy_api_deprecated_at: DateTime<Utc>,
+    /// Announced in the `Sunset` header of the unprefixed routes, e.g. `2026-01-01T00:00:00Z`
+    pub legacy_api_sunset: Option<DateTime<Utc>>,
+    /// Bearer token granting access to the `/admin` routes, which are closed when unset
+    pub admin_token: Option<String>,
+    /// Client certificate principals granted access to the `/admin` routes
+    pub admin_principals: Vec<String>,
+    /// Origins browsers may call the API from; empty disables CORS and `*` allows any origin
+    pub cors_allowed_origins: Vec<String>,
+    /// Responses smaller than this are sent uncompressed
+    pub compression_min_bytes: u16,
+    /// Largest request body accepted outside the import endpoints
+    pub max_body_bytes: usize,
+    pub request_timeout: Duration,
+    /// `max-age` of the Strict-Transport-Security header, 0 leaves it out
+    pub hsts_max_age: u64,
+    pub referrer_policy: String,
+    /// PEM certificate chain and private key; setting both serves HTTPS instead of HTTP
+    pub tls_cert_path: Option<PathBuf>,
+    pub tls_key_path: Option<PathBuf>,
+    /// PEM bundle of the CAs issuing client certificates; enables mutual TLS
+    pub tls_client_ca_path: Option<PathBuf>,
+    /// Let clients without a certificate connect anonymously when mutual TLS is enabled
+    pub tls_client_auth_optional: bool,
+    /// How often the TLS files are checked for changes
+    pub tls_reload_interval: Duration,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Read the settings from any source of variables, such as a map when embedding the app
+    /// or in tests, with the same names and defaults as the environment
+    pub fn from_vars(vars: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let vars = &vars;
+        Ok(Self {
+            database_url: required(vars, "DATABASE_URL")?,
+            database_read_url: maybe(vars, "DATABASE_READ_URL")?,
+            read_your_writes: Duration::from_secs(optional(vars, "READ_YOUR_WRITES_SECS", 5)?),
+            replica_health_check_interval: Duration::from_secs(optional(vars, "REPLICA_HEALTH_CHECK_SECS", 5)?),
+            db_max_connections: optional(vars, "DB_MAX_CONNECTIONS", 10)?,
+            db_min_connections: optional(vars, "DB_MIN_CONNECTIONS", 0)?,
+            db_acquire_timeout: Duration::from_secs(optional(vars, "DB_ACQUIRE_TIMEOUT_SECS", 30)?),
+            db_idle_timeout: seconds(optional(vars, "DB_IDLE_TIMEOUT_SECS", 600)?),
+            db_max_lifetime: seconds(optional(vars, "DB_MAX_LIFETIME_SECS", 1800)?),
+            db_statement_timeout: milliseconds(optional(vars, "DB_STATEMENT_TIMEOUT_MS", 0)?),
+            db_connect_retries: optional(vars, "DB_CONNECT_RETRIES", 5)?,
+            db_lazy_connect: optional(vars, "DB_LAZY_CONNECT", false)?,
+            storage_backend: optional(vars, "STORAGE_BACKEND", "postgres".to_string())?,
+            port: optional(vars, "PORT", 3000)?,
+            redis_url: required(vars, "REDIS_URL")?,
+            cache_backend: optional(vars, "CACHE_BACKEND", "redis".to_string())?,
+            cache_format: optional(vars, "CACHE_FORMAT", CacheFormat::Json)?,
+            cache_compress_above: Some(optional(vars, "CACHE_COMPRESS_ABOVE_BYTES", 1024)?).filter(|bytes| *bytes > 0),
+            cache_zstd_level: optional(vars, "CACHE_ZSTD_LEVEL", 3)?,
+            cache_failure_threshold: optional(vars, "CACHE_FAILURE_THRESHOLD", 5)?,
+            cache_open_duration: Duration::from_secs(optional(vars, "CACHE_OPEN_SECS", 30)?),
+            cache_read_timeout: Duration::from_millis(optional(vars, "CACHE_READ_TIMEOUT_MS", 100)?),
+            cache_write_timeout: Duration::from_millis(optional(vars, "CACHE_WRITE_TIMEOUT_MS", 100)?),
+            cache_delete_timeout: Duration::from_millis(optional(vars, "CACHE_DELETE_TIMEOUT_MS", 100)?),
+            job_workers: optional(vars, "JOB_WORKERS", 2)?,
+            job_poll_interval: Duration::from_millis(optional(vars, "JOB_POLL_INTERVAL_MS", 1000)?),
+            app_env: optional(vars, "APP_ENV", "production".to_string())?,
+            graphql_max_depth: optional(vars, "GRAPHQL_MAX_DEPTH", 8)?,
+            graphql_max_complexity: optional(vars, "GRAPHQL_MAX_COMPLEXITY", 1000)?,
+            grpc_port: optional(vars, "GRPC_PORT", 0)?,
+            legacy_api_deprecated_at: optional(
+                vars,
+                "LEGACY_API_DEPRECATED_AT",
+                Utc.with_ymd_and_hms(2025, 3, 9, 0, 0, 0).unwrap(),
+            )?,
+            legacy_api_sunset: maybe(vars, "LEGACY_API_SUNSET")?,
+            admin_token: maybe(vars, "ADMIN_TOKEN")?,
+            admin_principals: list(vars, "ADMIN_PRINCIPALS"),
+            cors_allowed_origins: list(vars, "CORS_ALLOWED_ORIGINS"),
+            compression_min_bytes: optional(vars, "COMPRESSION_MIN_BYTES", 1024)?,
+            max_body_bytes: optional(vars, "MAX_BODY_BYTES", 2 * 1024 * 1024)?,
+            request_timeout: Duration::from_secs(optional(vars, "REQUEST_TIMEOUT_SECS", 30)?),
+            hsts_max_age: optional(vars, "HSTS_MAX_AGE_SECS", 365 * 24 * 60 * 60)?,
+            referrer_policy: optional(vars, "REFERRER_POLICY", "no-referrer".to_string())?,
+            tls_cert_path: maybe(vars, "TLS_CERT_PATH")?,
+            tls_key_path: maybe(vars, "TLS_KEY_PATH")?,
+            tls_client_ca_path: maybe(vars, "TLS_CLIENT_CA_PATH")?,
+            tls_client_auth_optional: optional(vars, "TLS_CLIENT_AUTH_OPTIONAL", false)?,
+            tls_reload_interval: Duration::from_secs(optional(vars, "TLS_RELOAD_INTERVAL_SECS", 10)?),
+        })
+    }
+
+    pub fn is_development(&self) -> bool {
+        self.app_env == "development"
+    }
+}
+
+fn required(vars: &impl Fn(&str) -> Option<String>, name: &str) -> Result<String> {
+    vars(name).with_context(|| format!("{} must be set in the .env file", name))
+}
+
+fn optional<T>(vars: &impl Fn(&str) -> Option<String>, name: &str, default: T) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    Ok(maybe(vars, name)?.unwrap_or(default))
+}
+
+fn maybe<T>(vars: &impl Fn(&str) -> Option<String>, name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match vars(name) {
+        Some(value) if value.is_empty() => Ok(None),
+        Some(value) => value.parse().map(Some).with_context(|| format!("Invalid value for {}: {}", name, value)),
+        None => Ok(None),
+    }
+}
+
+/// A duration in seconds, with 0 meaning none
+fn seconds(value: u64) -> Option<Duration> {
+    (value > 0).then(|| Duration::from_secs(value))
+}
+
+fn milliseconds(value: u64) -> Option<Duration> {
+    (value > 0).then(|| Duration::from_millis(value))
+}
+
+/// Comma separated values, empty when unset
+fn list(vars: &impl Fn(&str) -> Option<String>, name: &str) -> Vec<String> {
+    vars(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
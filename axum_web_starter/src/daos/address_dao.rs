@@ -0,0 +1,177 @@
+use sqlx::{Acquire, PgExecutor, Postgres, Transaction};
+use uuid::Uuid;
+use crate::models::address::{AddressKind, CustomerAddress, CustomerAddressPayload};
+
+pub struct AddressDAO;
+
+impl AddressDAO {
+    /// Create a new address for a customer, demoting the previous default of the same kind
+    pub async fn create_address<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        customer_id: Uuid,
+        payload: &CustomerAddressPayload,
+    ) -> Result<CustomerAddress, sqlx::Error> {
+        let mut tx = conn.begin().await?;
+        if payload.is_default {
+            Self::clear_default(&mut tx, customer_id, payload.kind).await?;
+        }
+        let address = sqlx::query_as!(
+            CustomerAddress,
+            r#"
+            INSERT INTO customer_addresses
+                (customer_id, kind, line1, line2, city, region, postal_code, country_code, phone, is_default)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id::UUID, customer_id, kind as "kind: AddressKind", line1, line2, city, region,
+                      postal_code, country_code, phone, is_default, created_at
+            "#,
+            customer_id,
+            payload.kind as AddressKind,
+            payload.line1,
+            payload.line2,
+            payload.city,
+            payload.region,
+            payload.postal_code,
+            payload.country_code,
+            payload.phone,
+            payload.is_default
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(address)
+    }
+
+    /// Retrieve all addresses of a customer, defaults first
+    pub async fn list_addresses<'e>(
+        executor: impl PgExecutor<'e>,
+        customer_id: Uuid,
+    ) -> Result<Vec<CustomerAddress>, sqlx::Error> {
+        sqlx::query_as!(
+            CustomerAddress,
+            r#"
+            SELECT id::UUID, customer_id, kind as "kind: AddressKind", line1, line2, city, region,
+                   postal_code, country_code, phone, is_default, created_at
+            FROM customer_addresses
+            WHERE customer_id = $1
+            ORDER BY is_default DESC, created_at
+            "#,
+            customer_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Retrieve all addresses of the given customers, defaults first
+    pub async fn list_addresses_by_customers<'e>(
+        executor: impl PgExecutor<'e>,
+        customer_ids: &[Uuid],
+    ) -> Result<Vec<CustomerAddress>, sqlx::Error> {
+        sqlx::query_as!(
+            CustomerAddress,
+            r#"
+            SELECT id::UUID, customer_id, kind as "kind: AddressKind", line1, line2, city, region,
+                   postal_code, country_code, phone, is_default, created_at
+            FROM customer_addresses
+            WHERE customer_id = ANY($1)
+            ORDER BY customer_id, is_default DESC, created_at
+            "#,
+            customer_ids
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Retrieve a single address of a customer
+    pub async fn get_address<'e>(
+        executor: impl PgExecutor<'e>,
+        customer_id: Uuid,
+        id: Uuid,
+    ) -> Result<CustomerAddress, sqlx::Error> {
+        sqlx::query_as!(
+            CustomerAddress,
+            r#"
+            SELECT id::UUID, customer_id, kind as "kind: AddressKind", line1, line2, city, region,
+                   postal_code, country_code, phone, is_default, created_at
+            FROM customer_addresses
+            WHERE customer_id = $1 AND id = $2
+            "#,
+            customer_id,
+            id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Replace an address, demoting the previous default of the same kind
+    pub async fn update_address<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        customer_id: Uuid,
+        id: Uuid,
+        payload: &CustomerAddressPayload,
+    ) -> Result<CustomerAddress, sqlx::Error> {
+        let mut tx = conn.begin().await?;
+        if payload.is_default {
+            Self::clear_default(&mut tx, customer_id, payload.kind).await?;
+        }
+        let address = sqlx::query_as!(
+            CustomerAddress,
+            r#"
+            UPDATE customer_addresses
+            SET kind = $1, line1 = $2, line2 = $3, city = $4, region = $5, postal_code = $6,
+                country_code = $7, phone = $8, is_default = $9
+            WHERE customer_id = $10 AND id = $11
+            RETURNING id::UUID, customer_id, kind as "kind: AddressKind", line1, line2, city, region,
+                      postal_code, country_code, phone, is_default, created_at
+            "#,
+            payload.kind as AddressKind,
+            payload.line1,
+            payload.line2,
+            payload.city,
+            payload.region,
+            payload.postal_code,
+            payload.country_code,
+            payload.phone,
+            payload.is_default,
+            customer_id,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(address)
+    }
+
+    /// Delete an address of a customer
+    pub async fn delete_address<'e>(executor: impl PgExecutor<'e>, customer_id: Uuid, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM customer_addresses
+            WHERE customer_id = $1 AND id = $2
+            "#,
+            customer_id,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn clear_default(
+        tx: &mut Transaction<'_, Postgres>,
+        customer_id: Uuid,
+        kind: AddressKind,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE customer_addresses
+            SET is_default = false
+            WHERE customer_id = $1 AND kind = $2 AND is_default
+            "#,
+            customer_id,
+            kind as AddressKind
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
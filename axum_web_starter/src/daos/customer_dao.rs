@@ -1,11 +1,17 @@
-use sqlx::PgPool;
+use futures::stream::BoxStream;
+use sqlx::{Acquire, PgExecutor, PgPool, Postgres};
 use uuid::Uuid;
-use crate::models::customer::Customer;
+use crate::models::address::{AddressKind, CustomerAddress};
+use crate::models::customer::{Customer, CustomerDetails};
 
 pub struct CustomerDAO;
 
 impl CustomerDAO {
-    pub async fn create_customer(pool: &PgPool, name: String, email: String) -> Result<Customer, sqlx::Error> {
+    pub async fn create_customer<'e>(
+        executor: impl PgExecutor<'e>,
+        name: String,
+        email: String,
+    ) -> Result<Customer, sqlx::Error> {
         sqlx::query_as!(
             Customer,
             r#"
@@ -16,11 +22,11 @@ impl CustomerDAO {
             name,
             email
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn list_customers(pool: &PgPool) -> Result<Vec<Customer>, sqlx::Error>{
+    pub async fn list_customers<'e>(executor: impl PgExecutor<'e>) -> Result<Vec<Customer>, sqlx::Error>{
         sqlx::query_as!(
             Customer,
             r#"
@@ -28,11 +34,57 @@ impl CustomerDAO {
             FROM customers
             "#,
         )
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await
     }
 
-    pub async fn get_customer(pool: &PgPool, id: Uuid) -> Result<Customer, sqlx::Error>{
+    /// One page of customers in ID order
+    pub async fn list_customers_page<'e>(
+        executor: impl PgExecutor<'e>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Customer>, sqlx::Error> {
+        sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id::UUID, name, email
+            FROM customers
+            ORDER BY id
+            LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Stream every customer without loading the whole table into memory
+    pub fn stream_customers(pool: &PgPool) -> BoxStream<'_, Result<Customer, sqlx::Error>> {
+        sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id::UUID, name, email
+            FROM customers
+            ORDER BY id
+            "#,
+        )
+        .fetch(pool)
+    }
+
+    pub async fn count_customers<'e>(executor: impl PgExecutor<'e>) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM customers
+            "#,
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(record.count)
+    }
+
+    pub async fn get_customer<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<Customer, sqlx::Error>{
         sqlx::query_as!(
             Customer,
             r#"
@@ -42,11 +94,255 @@ impl CustomerDAO {
             "#,
             id
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Case-insensitive lookup backed by the unique `lower(email)` index
+    pub async fn get_customer_by_email<'e>(executor: impl PgExecutor<'e>, email: &str) -> Result<Customer, sqlx::Error> {
+        sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id::UUID, name, email
+            FROM customers
+            WHERE lower(email) = lower($1)
+            "#,
+            email
+        )
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn update_customer(pool: &PgPool, id: Uuid, name: String, email: String) -> Result<Customer, sqlx::Error> {
+    /// Create the customer with this email, or update the name of the existing one.
+    /// Returns the customer and whether it was created.
+    pub async fn upsert_customer_by_email<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        name: String,
+        email: String,
+    ) -> Result<(Customer, bool), sqlx::Error> {
+        let mut tx = conn.begin().await?;
+        let existing = sqlx::query!(
+            r#"
+            SELECT id::UUID as "id!"
+            FROM customers
+            WHERE lower(email) = lower($1)
+            FOR UPDATE
+            "#,
+            email
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let result = match existing {
+            Some(existing) => {
+                let customer = sqlx::query_as!(
+                    Customer,
+                    r#"
+                    UPDATE customers
+                    SET name = $1, email = $2
+                    WHERE id = $3
+                    RETURNING id::UUID, name, email
+                    "#,
+                    name,
+                    email,
+                    existing.id
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                (customer, false)
+            }
+            None => {
+                let customer = sqlx::query_as!(
+                    Customer,
+                    r#"
+                    INSERT INTO customers(name, email)
+                    VALUES ($1, $2)
+                    RETURNING id::UUID, name, email
+                    "#,
+                    name,
+                    email
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                (customer, true)
+            }
+        };
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// Retrieve a customer together with all of its addresses in a single query
+    pub async fn get_customer_with_addresses<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+    ) -> Result<CustomerDetails, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT c.id::UUID as "id!", c.name, c.email,
+                   a.id as "address_id?", a.kind as "kind?: AddressKind", a.line1 as "line1?", a.line2,
+                   a.city as "city?", a.region, a.postal_code as "postal_code?",
+                   a.country_code as "country_code?", a.phone, a.is_default as "is_default?",
+                   a.created_at as "created_at?"
+            FROM customers c
+            LEFT JOIN customer_addresses a ON a.customer_id = c.id
+            WHERE c.id = $1
+            ORDER BY a.is_default DESC, a.created_at
+            "#,
+            id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        let first = rows.first().ok_or(sqlx::Error::RowNotFound)?;
+        let customer = Customer {
+            id: first.id,
+            name: first.name.clone(),
+            email: first.email.clone(),
+        };
+        let addresses = rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(CustomerAddress {
+                    id: row.address_id?,
+                    customer_id: customer.id,
+                    kind: row.kind?,
+                    line1: row.line1?,
+                    line2: row.line2,
+                    city: row.city?,
+                    region: row.region,
+                    postal_code: row.postal_code?,
+                    country_code: row.country_code?,
+                    phone: row.phone,
+                    is_default: row.is_default?,
+                    created_at: row.created_at?,
+                })
+            })
+            .collect();
+
+        Ok(CustomerDetails {
+            customer,
+            addresses: Some(addresses),
+        })
+    }
+
+    /// Retrieve the customers with any of these IDs, in no particular order
+    pub async fn list_customers_by_ids<'e>(executor: impl PgExecutor<'e>, ids: &[Uuid]) -> Result<Vec<Customer>, sqlx::Error> {
+        sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id::UUID, name, email
+            FROM customers
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Case-insensitive lookup of the customers owning any of these emails
+    pub async fn list_customers_by_emails<'e>(
+        executor: impl PgExecutor<'e>,
+        emails: &[String],
+    ) -> Result<Vec<Customer>, sqlx::Error> {
+        let emails: Vec<String> = emails.iter().map(|email| email.to_lowercase()).collect();
+        sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT id::UUID, name, email
+            FROM customers
+            WHERE lower(email) = ANY($1)
+            "#,
+            &emails[..]
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Insert, update and delete customers with one statement each, in a single transaction.
+    /// Returns the IDs of updated or deleted customers that do not exist; the transaction is
+    /// only committed when there are none.
+    pub async fn apply_batch<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        creates: &[Customer],
+        updates: &[Customer],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut tx = conn.begin().await?;
+
+        if !creates.is_empty() {
+            let ids: Vec<Uuid> = creates.iter().map(|customer| customer.id).collect();
+            let names: Vec<String> = creates.iter().map(|customer| customer.name.clone()).collect();
+            let emails: Vec<String> = creates.iter().map(|customer| customer.email.clone()).collect();
+            sqlx::query!(
+                r#"
+                INSERT INTO customers (id, name, email)
+                SELECT * FROM UNNEST($1::UUID[], $2::TEXT[], $3::TEXT[])
+                "#,
+                &ids[..],
+                &names[..],
+                &emails[..]
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let mut found = Vec::new();
+        if !updates.is_empty() {
+            let ids: Vec<Uuid> = updates.iter().map(|customer| customer.id).collect();
+            let names: Vec<String> = updates.iter().map(|customer| customer.name.clone()).collect();
+            let emails: Vec<String> = updates.iter().map(|customer| customer.email.clone()).collect();
+            let rows = sqlx::query!(
+                r#"
+                UPDATE customers AS c
+                SET name = u.name, email = u.email
+                FROM UNNEST($1::UUID[], $2::TEXT[], $3::TEXT[]) AS u(id, name, email)
+                WHERE c.id = u.id
+                RETURNING c.id::UUID as "id!"
+                "#,
+                &ids[..],
+                &names[..],
+                &emails[..]
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            found.extend(rows.into_iter().map(|row| row.id));
+        }
+
+        if !deletes.is_empty() {
+            let rows = sqlx::query!(
+                r#"
+                DELETE FROM customers
+                WHERE id = ANY($1)
+                RETURNING id::UUID as "id!"
+                "#,
+                deletes
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            found.extend(rows.into_iter().map(|row| row.id));
+        }
+
+        let missing: Vec<Uuid> = updates
+            .iter()
+            .map(|customer| customer.id)
+            .chain(deletes.iter().copied())
+            .filter(|id| !found.contains(id))
+            .collect();
+        if missing.is_empty() {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+        Ok(missing)
+    }
+
+    pub async fn update_customer<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+        name: String,
+        email: String,
+    ) -> Result<Customer, sqlx::Error> {
         sqlx::query_as!(
             Customer,
             r#"
@@ -59,11 +355,11 @@ impl CustomerDAO {
             email,
             id
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn delete_customer(pool: &PgPool, id: Uuid) -> Result<u64, sqlx::Error> {
+    pub async fn delete_customer<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<u64, sqlx::Error> {
         let result = sqlx::query!(
             r#"
             DELETE FROM customers
@@ -71,7 +367,7 @@ impl CustomerDAO {
             "#,
             id
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(result.rows_affected())
     }
@@ -0,0 +1,122 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use crate::models::idempotency::{IdempotencyClaim, IdempotencyRecord, IdempotencyStatus};
+
+pub struct IdempotencyDAO;
+
+impl IdempotencyDAO {
+    /// Reserve a key for a new request, or return what is stored for it.
+    /// Expired keys are dropped first so they can be used again.
+    pub async fn claim(
+        pool: &PgPool,
+        principal: &str,
+        key: &str,
+        request_hash: &[u8],
+        in_flight_seconds: i32,
+    ) -> Result<IdempotencyClaim, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE principal = $1 AND key = $2 AND expires_at < now()
+            "#,
+            principal,
+            key
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (principal, key, request_hash, expires_at)
+            VALUES ($1, $2, $3, now() + $4::INT * INTERVAL '1 second')
+            ON CONFLICT (principal, key) DO NOTHING
+            "#,
+            principal,
+            key,
+            request_hash,
+            in_flight_seconds
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let claim = if inserted > 0 {
+            IdempotencyClaim::Claimed
+        } else {
+            let record = sqlx::query_as!(
+                IdempotencyRecord,
+                r#"
+                SELECT request_hash, status as "status: IdempotencyStatus", response_status,
+                       response_headers, response_body
+                FROM idempotency_keys
+                WHERE principal = $1 AND key = $2
+                "#,
+                principal,
+                key
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            IdempotencyClaim::Existing(record)
+        };
+        tx.commit().await?;
+        Ok(claim)
+    }
+
+    /// Store the response of a claimed key, keeping it for `ttl_seconds`
+    pub async fn complete(
+        pool: &PgPool,
+        principal: &str,
+        key: &str,
+        response_status: i32,
+        response_headers: Value,
+        response_body: &[u8],
+        ttl_seconds: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+            SET status = 'completed', response_status = $3, response_headers = $4, response_body = $5,
+                expires_at = now() + $6::INT * INTERVAL '1 second'
+            WHERE principal = $1 AND key = $2
+            "#,
+            principal,
+            key,
+            response_status,
+            response_headers,
+            response_body,
+            ttl_seconds
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Give up a claimed key so the client can retry with it
+    pub async fn release(pool: &PgPool, principal: &str, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE principal = $1 AND key = $2 AND status = 'in_flight'
+            "#,
+            principal,
+            key
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete every expired key
+    pub async fn purge_expired(pool: &PgPool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE expires_at < now()
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
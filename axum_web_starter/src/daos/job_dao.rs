@@ -0,0 +1,187 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::job::{Job, JobKind, JobStatus};
+
+/// Extra lease time on top of the job timeout before another worker may reclaim a running job
+const LEASE_GRACE_SECONDS: i32 = 30;
+
+pub struct JobDAO;
+
+impl JobDAO {
+    /// Queue a new job on behalf of `principal`, runnable immediately
+    pub async fn enqueue(
+        pool: &PgPool,
+        principal: &str,
+        kind: JobKind,
+        payload: Value,
+        max_attempts: i32,
+        timeout_seconds: i32,
+    ) -> Result<Job, sqlx::Error> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            INSERT INTO jobs (kind, payload, max_attempts, timeout_seconds, principal)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id::UUID, kind as "kind: JobKind", status as "status: JobStatus", payload, progress,
+                      attempts, max_attempts, timeout_seconds, result, last_error, run_at, created_at, updated_at
+            "#,
+            kind as JobKind,
+            payload,
+            max_attempts,
+            timeout_seconds,
+            principal
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Retrieve a single job by ID, only if `principal` submitted it when one is given
+    pub async fn get_job(pool: &PgPool, id: Uuid, principal: Option<&str>) -> Result<Job, sqlx::Error> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            SELECT id::UUID, kind as "kind: JobKind", status as "status: JobStatus", payload, progress,
+                   attempts, max_attempts, timeout_seconds, result, last_error, run_at, created_at, updated_at
+            FROM jobs
+            WHERE id = $1 AND ($2::TEXT IS NULL OR principal = $2)
+            "#,
+            id,
+            principal
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Retrieve the 100 most recent jobs, optionally only those in one status
+    pub async fn list_jobs(pool: &PgPool, status: Option<JobStatus>) -> Result<Vec<Job>, sqlx::Error> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            SELECT id::UUID, kind as "kind: JobKind", status as "status: JobStatus", payload, progress,
+                   attempts, max_attempts, timeout_seconds, result, last_error, run_at, created_at, updated_at
+            FROM jobs
+            WHERE $1::TEXT IS NULL OR status = $1
+            ORDER BY created_at DESC
+            LIMIT 100
+            "#,
+            status as Option<JobStatus>
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Lease the next runnable job to the calling worker, counting it as an attempt.
+    /// Running jobs whose lease expired (the worker died) are claimed again.
+    pub async fn claim_next(pool: &PgPool) -> Result<Option<Job>, sqlx::Error> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs
+            SET status = 'running',
+                attempts = attempts + 1,
+                locked_until = now() + (timeout_seconds + $1::INT) * INTERVAL '1 second',
+                updated_at = now()
+            WHERE id = (
+                SELECT id
+                FROM jobs
+                WHERE (status = 'queued' AND run_at <= now())
+                   OR (status = 'running' AND locked_until < now())
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id::UUID, kind as "kind: JobKind", status as "status: JobStatus", payload, progress,
+                      attempts, max_attempts, timeout_seconds, result, last_error, run_at, created_at, updated_at
+            "#,
+            LEASE_GRACE_SECONDS
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record progress of the given attempt; ignored once the attempt lost its lease
+    pub async fn update_progress(pool: &PgPool, id: Uuid, attempt: i32, progress: i32) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET progress = $3, updated_at = now()
+            WHERE id = $1 AND status = 'running' AND attempts = $2
+            "#,
+            id,
+            attempt,
+            progress.clamp(0, 100)
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the given attempt as succeeded. Returns false if the attempt lost its lease.
+    pub async fn complete(pool: &PgPool, id: Uuid, attempt: i32, result: Value) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET status = 'succeeded', progress = 100, result = $3, last_error = NULL,
+                locked_until = NULL, updated_at = now()
+            WHERE id = $1 AND status = 'running' AND attempts = $2
+            "#,
+            id,
+            attempt,
+            result
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a failed attempt. The job is queued again after `retry_in_seconds`, or
+    /// dead-lettered when it is out of attempts or the failure is permanent.
+    /// Returns the new status, or `None` if the attempt lost its lease.
+    pub async fn fail(
+        pool: &PgPool,
+        id: Uuid,
+        attempt: i32,
+        error: &str,
+        retry_in_seconds: i32,
+        permanent: bool,
+    ) -> Result<Option<JobStatus>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET status = CASE WHEN $5 OR attempts >= max_attempts THEN 'dead' ELSE 'queued' END,
+                last_error = $3,
+                run_at = now() + $4::INT * INTERVAL '1 second',
+                locked_until = NULL,
+                updated_at = now()
+            WHERE id = $1 AND status = 'running' AND attempts = $2
+            RETURNING status as "status: JobStatus"
+            "#,
+            id,
+            attempt,
+            error,
+            retry_in_seconds,
+            permanent
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(record.map(|record| record.status))
+    }
+
+    /// Move a dead-lettered job back to the queue with a fresh set of attempts
+    pub async fn retry_job(pool: &PgPool, id: Uuid) -> Result<Job, sqlx::Error> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs
+            SET status = 'queued', attempts = 0, progress = 0, run_at = now(), updated_at = now()
+            WHERE id = $1 AND status = 'dead'
+            RETURNING id::UUID, kind as "kind: JobKind", status as "status: JobStatus", payload, progress,
+                      attempts, max_attempts, timeout_seconds, result, last_error, run_at, created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
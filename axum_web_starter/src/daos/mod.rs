@@ -1,2 +1,8 @@
 pub mod customer_dao;
 pub mod seller_dao;
+pub mod order_dao;
+pub mod product_dao;
+pub mod address_dao;
+pub mod job_dao;
+pub mod idempotency_dao;
+pub mod search_dao;
@@ -0,0 +1,547 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use sqlx::{Acquire, PgConnection, PgExecutor, Postgres};
+use uuid::Uuid;
+use crate::unit_of_work::TransactionError;
+use crate::models::order::{Order, OrderAction, OrderItem, OrderItemPayload, OrderStatus, OrderStatusChange};
+
+pub struct OrderDAO;
+
+#[derive(Debug)]
+pub enum OrderError {
+    NotFound,
+    IllegalTransition { from: OrderStatus, action: OrderAction },
+    /// The product does not exist or is not sold by the order's seller
+    UnknownProduct(Uuid),
+    InsufficientStock(Uuid),
+    CurrencyMismatch(Uuid),
+    Invalid(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for OrderError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => OrderError::NotFound,
+            e => OrderError::Database(e),
+        }
+    }
+}
+
+impl TransactionError for OrderError {
+    fn database_error(&self) -> Option<&sqlx::Error> {
+        match self {
+            OrderError::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// An order line once catalog products have been resolved
+struct OrderLine {
+    product_id: Option<Uuid>,
+    sku: String,
+    description: String,
+    quantity: i32,
+    unit_price: i64,
+}
+
+/// An `orders` row before its items are attached
+struct OrderRow {
+    id: Uuid,
+    customer_id: Uuid,
+    seller_id: Uuid,
+    status: OrderStatus,
+    currency: String,
+    total_amount: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl OrderRow {
+    fn into_order(self, items: Vec<OrderItem>) -> Order {
+        Order {
+            id: self.id,
+            customer_id: self.customer_id,
+            seller_id: self.seller_id,
+            status: self.status,
+            currency: self.currency,
+            total_amount: self.total_amount,
+            items,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+struct OrderItemRow {
+    order_id: Uuid,
+    id: Uuid,
+    product_id: Option<Uuid>,
+    sku: String,
+    description: String,
+    quantity: i32,
+    unit_price: i64,
+    line_total: i64,
+}
+
+impl OrderDAO {
+    /// Create an order and its line items in a single transaction.
+    ///
+    /// Stock of catalog products is decremented in the same transaction with a
+    /// conditional update, so concurrent orders can never oversell a product.
+    pub async fn create_order<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        customer_id: Uuid,
+        seller_id: Uuid,
+        currency: String,
+        items: Vec<OrderItemPayload>,
+    ) -> Result<Order, OrderError> {
+        let mut tx = conn.begin().await?;
+
+        let mut lines = Vec::with_capacity(items.len());
+        for item in items {
+            let Some(product_id) = item.product_id else {
+                lines.push(OrderLine {
+                    product_id: None,
+                    sku: item.sku,
+                    description: item.description,
+                    quantity: item.quantity,
+                    unit_price: item.unit_price,
+                });
+                continue;
+            };
+
+            let reserved = sqlx::query!(
+                r#"
+                UPDATE products
+                SET stock_quantity = stock_quantity - $1, updated_at = now()
+                WHERE id = $2 AND seller_id = $3 AND stock_quantity >= $1
+                RETURNING sku, title, price, currency
+                "#,
+                item.quantity,
+                product_id,
+                seller_id
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(product) = reserved else {
+                let exists = sqlx::query!(
+                    r#"
+                    SELECT EXISTS (SELECT 1 FROM products WHERE id = $1 AND seller_id = $2) as "exists!"
+                    "#,
+                    product_id,
+                    seller_id
+                )
+                .fetch_one(&mut *tx)
+                .await?
+                .exists;
+                return Err(if exists {
+                    OrderError::InsufficientStock(product_id)
+                } else {
+                    OrderError::UnknownProduct(product_id)
+                });
+            };
+            if product.currency != currency {
+                return Err(OrderError::CurrencyMismatch(product_id));
+            }
+
+            lines.push(OrderLine {
+                product_id: Some(product_id),
+                sku: product.sku,
+                description: product.title,
+                quantity: item.quantity,
+                unit_price: product.price,
+            });
+        }
+
+        let mut total_amount: i64 = 0;
+        for line in &lines {
+            total_amount = line
+                .unit_price
+                .checked_mul(i64::from(line.quantity))
+                .and_then(|line_total| total_amount.checked_add(line_total))
+                .ok_or_else(|| OrderError::Invalid("Order total is too large".to_string()))?;
+        }
+
+        let row = sqlx::query_as!(
+            OrderRow,
+            r#"
+            INSERT INTO orders (customer_id, seller_id, currency, total_amount)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id::UUID, customer_id, seller_id, status as "status: OrderStatus",
+                      currency, total_amount, created_at, updated_at
+            "#,
+            customer_id,
+            seller_id,
+            currency,
+            total_amount
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut order_items = Vec::with_capacity(lines.len());
+        for line in lines {
+            let line_total = line.unit_price * i64::from(line.quantity);
+            let order_item = sqlx::query_as!(
+                OrderItem,
+                r#"
+                INSERT INTO order_items (order_id, product_id, sku, description, quantity, unit_price, line_total)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING id::UUID, product_id, sku, description, quantity, unit_price, line_total
+                "#,
+                row.id,
+                line.product_id,
+                line.sku,
+                line.description,
+                line.quantity,
+                line.unit_price,
+                line_total
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            order_items.push(order_item);
+        }
+
+        tx.commit().await?;
+        Ok(row.into_order(order_items))
+    }
+
+    /// Retrieve all orders from the database
+    pub async fn list_orders<'c>(conn: impl Acquire<'c, Database = Postgres>) -> Result<Vec<Order>, sqlx::Error> {
+        let mut conn = conn.acquire().await?;
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id::UUID, customer_id, seller_id, status as "status: OrderStatus",
+                   currency, total_amount, created_at, updated_at
+            FROM orders
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Self::attach_items(&mut conn, rows).await
+    }
+
+    /// Retrieve a single order with its items by ID
+    pub async fn get_order<'c>(conn: impl Acquire<'c, Database = Postgres>, id: Uuid) -> Result<Order, sqlx::Error> {
+        let mut conn = conn.acquire().await?;
+        let row = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id::UUID, customer_id, seller_id, status as "status: OrderStatus",
+                   currency, total_amount, created_at, updated_at
+            FROM orders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let mut orders = Self::attach_items(&mut conn, vec![row]).await?;
+        Ok(orders.remove(0))
+    }
+
+    /// Retrieve all orders placed by a customer
+    pub async fn list_orders_by_customer<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        customer_id: Uuid,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        let mut conn = conn.acquire().await?;
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id::UUID, customer_id, seller_id, status as "status: OrderStatus",
+                   currency, total_amount, created_at, updated_at
+            FROM orders
+            WHERE customer_id = $1
+            ORDER BY created_at DESC
+            "#,
+            customer_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Self::attach_items(&mut conn, rows).await
+    }
+
+    /// Retrieve all orders received by a seller
+    pub async fn list_orders_by_seller<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        seller_id: Uuid,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        let mut conn = conn.acquire().await?;
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id::UUID, customer_id, seller_id, status as "status: OrderStatus",
+                   currency, total_amount, created_at, updated_at
+            FROM orders
+            WHERE seller_id = $1
+            ORDER BY created_at DESC
+            "#,
+            seller_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Self::attach_items(&mut conn, rows).await
+    }
+
+    /// Retrieve the most recent orders of each of the given customers, newest first
+    pub async fn list_recent_orders_by_customers<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        customer_ids: &[Uuid],
+        per_customer: i64,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        let mut conn = conn.acquire().await?;
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id as "id!", customer_id as "customer_id!", seller_id as "seller_id!",
+                   status as "status!: OrderStatus", currency as "currency!", total_amount as "total_amount!",
+                   created_at as "created_at!", updated_at as "updated_at!"
+            FROM (
+                SELECT id::UUID, customer_id, seller_id, status, currency, total_amount, created_at, updated_at,
+                       ROW_NUMBER() OVER (PARTITION BY customer_id ORDER BY created_at DESC) AS position
+                FROM orders
+                WHERE customer_id = ANY($1)
+            ) AS recent
+            WHERE position <= $2
+            ORDER BY customer_id, created_at DESC
+            "#,
+            customer_ids,
+            per_customer
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Self::attach_items(&mut conn, rows).await
+    }
+
+    /// Retrieve the most recent orders of each of the given sellers, newest first
+    pub async fn list_recent_orders_by_sellers<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        seller_ids: &[Uuid],
+        per_seller: i64,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        let mut conn = conn.acquire().await?;
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id as "id!", customer_id as "customer_id!", seller_id as "seller_id!",
+                   status as "status!: OrderStatus", currency as "currency!", total_amount as "total_amount!",
+                   created_at as "created_at!", updated_at as "updated_at!"
+            FROM (
+                SELECT id::UUID, customer_id, seller_id, status, currency, total_amount, created_at, updated_at,
+                       ROW_NUMBER() OVER (PARTITION BY seller_id ORDER BY created_at DESC) AS position
+                FROM orders
+                WHERE seller_id = ANY($1)
+            ) AS recent
+            WHERE position <= $2
+            ORDER BY seller_id, created_at DESC
+            "#,
+            seller_ids,
+            per_seller
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        Self::attach_items(&mut conn, rows).await
+    }
+
+    /// Count the orders of a customer that are not yet delivered, cancelled or refunded
+    pub async fn count_open_orders_by_customer<'e>(executor: impl PgExecutor<'e>, customer_id: Uuid) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM orders
+            WHERE customer_id = $1 AND status IN ('pending', 'paid', 'shipped')
+            "#,
+            customer_id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(record.count)
+    }
+
+    /// Those of the given customers that have placed any order, which keeps them from being deleted
+    pub async fn customers_with_orders<'e>(
+        executor: impl PgExecutor<'e>,
+        customer_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT customer_id
+            FROM orders
+            WHERE customer_id = ANY($1)
+            "#,
+            customer_ids
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(rows.into_iter().map(|row| row.customer_id).collect())
+    }
+
+    /// Those of the given sellers that have received any order, which keeps them from being deleted
+    pub async fn sellers_with_orders<'e>(executor: impl PgExecutor<'e>, seller_ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT seller_id
+            FROM orders
+            WHERE seller_id = ANY($1)
+            "#,
+            seller_ids
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(rows.into_iter().map(|row| row.seller_id).collect())
+    }
+
+    /// Count the orders of a seller that are not yet delivered, cancelled or refunded
+    pub async fn count_open_orders_by_seller<'e>(executor: impl PgExecutor<'e>, seller_id: Uuid) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM orders
+            WHERE seller_id = $1 AND status IN ('pending', 'paid', 'shipped')
+            "#,
+            seller_id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(record.count)
+    }
+
+    /// Apply a lifecycle action to an order and record it in the status history.
+    ///
+    /// The order row is locked for the duration of the transaction so concurrent
+    /// transitions are serialized and each one is checked against the latest status.
+    pub async fn transition_order<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        id: Uuid,
+        action: OrderAction,
+        actor: String,
+        reason: Option<String>,
+    ) -> Result<Order, OrderError> {
+        let mut tx = conn.begin().await?;
+
+        let current = sqlx::query!(
+            r#"
+            SELECT status as "status: OrderStatus"
+            FROM orders
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .status;
+
+        let next = current
+            .apply(action)
+            .ok_or(OrderError::IllegalTransition { from: current, action })?;
+
+        sqlx::query!(
+            r#"
+            UPDATE orders
+            SET status = $1, updated_at = now()
+            WHERE id = $2
+            "#,
+            next as OrderStatus,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if next.releases_stock() {
+            // Give reserved catalog stock back
+            sqlx::query!(
+                r#"
+                UPDATE products AS p
+                SET stock_quantity = p.stock_quantity + i.quantity, updated_at = now()
+                FROM order_items AS i
+                WHERE i.order_id = $1 AND i.product_id = p.id
+                "#,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO order_status_history (order_id, from_status, to_status, actor, reason)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            current as OrderStatus,
+            next as OrderStatus,
+            actor,
+            reason
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let order = Self::get_order(&mut *tx, id).await?;
+        tx.commit().await?;
+        Ok(order)
+    }
+
+    /// Retrieve the status transitions of an order, oldest first
+    pub async fn list_status_history<'e>(
+        executor: impl PgExecutor<'e>,
+        order_id: Uuid,
+    ) -> Result<Vec<OrderStatusChange>, sqlx::Error> {
+        sqlx::query_as!(
+            OrderStatusChange,
+            r#"
+            SELECT id::UUID, from_status as "from_status: OrderStatus", to_status as "to_status: OrderStatus",
+                   actor, reason, created_at
+            FROM order_status_history
+            WHERE order_id = $1
+            ORDER BY created_at
+            "#,
+            order_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Load the items of all given orders with one query and attach them
+    async fn attach_items(conn: &mut PgConnection, rows: Vec<OrderRow>) -> Result<Vec<Order>, sqlx::Error> {
+        let order_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let item_rows = sqlx::query_as!(
+            OrderItemRow,
+            r#"
+            SELECT order_id, id::UUID, product_id, sku, description, quantity, unit_price, line_total
+            FROM order_items
+            WHERE order_id = ANY($1)
+            ORDER BY order_id, sku
+            "#,
+            &order_ids
+        )
+        .fetch_all(conn)
+        .await?;
+
+        let mut items_by_order: HashMap<Uuid, Vec<OrderItem>> = HashMap::new();
+        for item in item_rows {
+            items_by_order.entry(item.order_id).or_default().push(OrderItem {
+                id: item.id,
+                product_id: item.product_id,
+                sku: item.sku,
+                description: item.description,
+                quantity: item.quantity,
+                unit_price: item.unit_price,
+                line_total: item.line_total,
+            });
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let items = items_by_order.remove(&row.id).unwrap_or_default();
+                row.into_order(items)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,181 @@
+use sqlx::PgExecutor;
+use uuid::Uuid;
+use crate::models::product::Product;
+
+pub struct ProductDAO;
+
+/// Upper bound for a single page of search results
+const MAX_SEARCH_LIMIT: i64 = 200;
+
+impl ProductDAO {
+    /// Create a new product in a seller's catalog
+    pub async fn create_product<'e>(
+        executor: impl PgExecutor<'e>,
+        seller_id: Uuid,
+        sku: String,
+        title: String,
+        price: i64,
+        currency: String,
+        stock_quantity: i32,
+    ) -> Result<Product, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            INSERT INTO products (seller_id, sku, title, price, currency, stock_quantity)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id::UUID, seller_id, sku, title, price, currency, stock_quantity, created_at, updated_at
+            "#,
+            seller_id,
+            sku,
+            title,
+            price,
+            currency,
+            stock_quantity
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Retrieve all products of a seller
+    pub async fn list_products_by_seller<'e>(
+        executor: impl PgExecutor<'e>,
+        seller_id: Uuid,
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id::UUID, seller_id, sku, title, price, currency, stock_quantity, created_at, updated_at
+            FROM products
+            WHERE seller_id = $1
+            ORDER BY sku
+            "#,
+            seller_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Retrieve all products of the given sellers
+    pub async fn list_products_by_sellers<'e>(
+        executor: impl PgExecutor<'e>,
+        seller_ids: &[Uuid],
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id::UUID, seller_id, sku, title, price, currency, stock_quantity, created_at, updated_at
+            FROM products
+            WHERE seller_id = ANY($1)
+            ORDER BY seller_id, sku
+            "#,
+            seller_ids
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// IDs of all products of the given sellers
+    pub async fn list_product_ids_by_sellers<'e>(
+        executor: impl PgExecutor<'e>,
+        seller_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id::UUID as "id!"
+            FROM products
+            WHERE seller_id = ANY($1)
+            "#,
+            seller_ids
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+
+    /// Search the whole catalog by title or SKU
+    pub async fn search_products<'e>(
+        executor: impl PgExecutor<'e>,
+        q: Option<String>,
+        seller_id: Option<Uuid>,
+        in_stock: bool,
+        limit: i64,
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        let pattern = q.map(|q| format!("%{}%", q.replace('%', "\\%").replace('_', "\\_")));
+        sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id::UUID, seller_id, sku, title, price, currency, stock_quantity, created_at, updated_at
+            FROM products
+            WHERE ($1::TEXT IS NULL OR title ILIKE $1 OR sku ILIKE $1)
+              AND ($2::UUID IS NULL OR seller_id = $2)
+              AND (NOT $3 OR stock_quantity > 0)
+            ORDER BY title, id
+            LIMIT $4
+            "#,
+            pattern,
+            seller_id,
+            in_stock,
+            limit.clamp(1, MAX_SEARCH_LIMIT)
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Retrieve a single product by ID
+    pub async fn get_product<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<Product, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id::UUID, seller_id, sku, title, price, currency, stock_quantity, created_at, updated_at
+            FROM products
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Update an existing product's details and stock level
+    pub async fn update_product<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+        sku: String,
+        title: String,
+        price: i64,
+        currency: String,
+        stock_quantity: i32,
+    ) -> Result<Product, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            UPDATE products
+            SET sku = $1, title = $2, price = $3, currency = $4, stock_quantity = $5, updated_at = now()
+            WHERE id = $6
+            RETURNING id::UUID, seller_id, sku, title, price, currency, stock_quantity, created_at, updated_at
+            "#,
+            sku,
+            title,
+            price,
+            currency,
+            stock_quantity,
+            id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Delete a product from the catalog by ID
+    pub async fn delete_product<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM products
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
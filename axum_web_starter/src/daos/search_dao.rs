@@ -0,0 +1,132 @@
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+use crate::utils::is_undefined_function;
+
+/// A customer or seller row matching a search, with its secondary field (email or company name)
+#[derive(sqlx::FromRow)]
+pub struct SearchHit {
+    pub kind: String,
+    pub id: Uuid,
+    pub name: String,
+    pub secondary: String,
+    pub score: f32,
+    /// Number of matches across all pages
+    pub total: i64,
+}
+
+pub struct SearchDAO;
+
+impl SearchDAO {
+    /// Rank customers and sellers by trigram similarity to the query, returning one page of
+    /// matches and the number across all pages. A row matches when every term occurs in its
+    /// fields, or when one field is similar enough to the whole query.
+    /// Databases without pg_trgm fall back to substring matching, ordered by name.
+    pub async fn search(
+        pool: &PgPool,
+        query: &str,
+        terms: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<SearchHit>, i64), sqlx::Error> {
+        let patterns: Vec<String> = terms.iter().map(|term| format!("%{}%", escape_like(term))).collect();
+        let hits = Self::search_page(pool, query, &patterns, limit, offset).await?;
+        let total = match hits.first() {
+            Some(hit) => hit.total,
+            // Past the last page there is no row to carry the count, so count from the first one
+            None if offset > 0 => {
+                let first = Self::search_page(pool, query, &patterns, 1, 0).await?;
+                first.first().map_or(0, |hit| hit.total)
+            }
+            None => 0,
+        };
+        Ok((hits, total))
+    }
+
+    async fn search_page(
+        pool: &PgPool,
+        query: &str,
+        patterns: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        match Self::search_trigram(pool, query, patterns, limit, offset).await {
+            Err(e) if is_undefined_function(&e) => {
+                warn!("Trigram search unavailable, falling back to substring matching: {}", e);
+                Self::search_substring(pool, patterns, limit, offset).await
+            }
+            result => result,
+        }
+    }
+
+    async fn search_trigram(
+        pool: &PgPool,
+        query: &str,
+        patterns: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        // Checked at runtime rather than by `query_as!`, which could not compile against a
+        // database without pg_trgm, where this fails with undefined_function instead
+        sqlx::query_as::<_, SearchHit>(
+            r#"
+            SELECT kind, id, name, secondary, score, COUNT(*) OVER () AS total
+            FROM (
+                SELECT 'customer' AS kind, id::UUID AS id, name, email AS secondary,
+                       GREATEST(similarity(name, $1), similarity(email, $1))::REAL AS score
+                FROM customers
+                WHERE name % $1 OR email % $1 OR (name || ' ' || email) ILIKE ALL ($2::TEXT[])
+                UNION ALL
+                SELECT 'seller', id::UUID, name, company_name,
+                       GREATEST(similarity(name, $1), similarity(company_name, $1))::REAL
+                FROM sellers
+                WHERE name % $1 OR company_name % $1 OR (name || ' ' || company_name) ILIKE ALL ($2::TEXT[])
+            ) AS hits
+            ORDER BY score DESC, name, id
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(query)
+        .bind(patterns)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    }
+
+    async fn search_substring(
+        pool: &PgPool,
+        patterns: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        sqlx::query_as!(
+            SearchHit,
+            r#"
+            SELECT kind as "kind!", id as "id!", name as "name!", secondary as "secondary!",
+                   score as "score!", COUNT(*) OVER () as "total!"
+            FROM (
+                SELECT 'customer' AS kind, id::UUID AS id, name, email AS secondary, 0::REAL AS score
+                FROM customers
+                WHERE (name || ' ' || email) ILIKE ALL ($1::TEXT[])
+                UNION ALL
+                SELECT 'seller', id::UUID, name, company_name, 0::REAL
+                FROM sellers
+                WHERE (name || ' ' || company_name) ILIKE ALL ($1::TEXT[])
+            ) AS hits
+            ORDER BY name, id
+            LIMIT $2 OFFSET $3
+            "#,
+            patterns,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// Match the term literally inside an ILIKE pattern
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
@@ -1,4 +1,5 @@
-use sqlx::PgPool;
+use futures::stream::BoxStream;
+use sqlx::{Acquire, PgExecutor, PgPool, Postgres};
 use uuid::Uuid;
 use crate::models::seller::Seller;
 
@@ -6,7 +7,11 @@ pub struct SellerDAO;
 
 impl SellerDAO {
     /// Create a new seller in the database
-    pub async fn create_seller(pool: &PgPool, name: String, company_name: String) -> Result<Seller, sqlx::Error> {
+    pub async fn create_seller<'e>(
+        executor: impl PgExecutor<'e>,
+        name: String,
+        company_name: String,
+    ) -> Result<Seller, sqlx::Error> {
         sqlx::query_as!(
             Seller,
             r#"
@@ -17,12 +22,12 @@ impl SellerDAO {
             name,
             company_name
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
     /// Retrieve all sellers from the database
-    pub async fn list_sellers(pool: &PgPool) -> Result<Vec<Seller>, sqlx::Error> {
+    pub async fn list_sellers<'e>(executor: impl PgExecutor<'e>) -> Result<Vec<Seller>, sqlx::Error> {
         sqlx::query_as!(
             Seller,
             r#"
@@ -30,12 +35,58 @@ impl SellerDAO {
             FROM sellers
             "#,
         )
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await
     }
 
+    /// One page of sellers in ID order
+    pub async fn list_sellers_page<'e>(
+        executor: impl PgExecutor<'e>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Seller>, sqlx::Error> {
+        sqlx::query_as!(
+            Seller,
+            r#"
+            SELECT id::UUID, name, company_name
+            FROM sellers
+            ORDER BY id
+            LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Stream every seller without loading the whole table into memory
+    pub fn stream_sellers(pool: &PgPool) -> BoxStream<'_, Result<Seller, sqlx::Error>> {
+        sqlx::query_as!(
+            Seller,
+            r#"
+            SELECT id::UUID, name, company_name
+            FROM sellers
+            ORDER BY id
+            "#,
+        )
+        .fetch(pool)
+    }
+
+    pub async fn count_sellers<'e>(executor: impl PgExecutor<'e>) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM sellers
+            "#,
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(record.count)
+    }
+
     /// Retrieve a single seller by ID
-    pub async fn get_seller(pool: &PgPool, id: Uuid) -> Result<Seller, sqlx::Error> {
+    pub async fn get_seller<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<Seller, sqlx::Error> {
         sqlx::query_as!(
             Seller,
             r#"
@@ -45,12 +96,32 @@ impl SellerDAO {
             "#,
             id
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Retrieve the sellers with any of these IDs, in no particular order
+    pub async fn list_sellers_by_ids<'e>(executor: impl PgExecutor<'e>, ids: &[Uuid]) -> Result<Vec<Seller>, sqlx::Error> {
+        sqlx::query_as!(
+            Seller,
+            r#"
+            SELECT id::UUID, name, company_name
+            FROM sellers
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(executor)
         .await
     }
 
     /// Update an existing seller's details
-    pub async fn update_seller(pool: &PgPool, id: Uuid, name: String, company_name: String) -> Result<Seller, sqlx::Error> {
+    pub async fn update_seller<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+        name: String,
+        company_name: String,
+    ) -> Result<Seller, sqlx::Error> {
         sqlx::query_as!(
             Seller,
             r#"
@@ -63,12 +134,90 @@ impl SellerDAO {
             company_name,
             id
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
+    /// Insert, update and delete sellers with one statement each, in a single transaction.
+    /// Returns the IDs of updated or deleted sellers that do not exist; the transaction is
+    /// only committed when there are none.
+    pub async fn apply_batch<'c>(
+        conn: impl Acquire<'c, Database = Postgres>,
+        creates: &[Seller],
+        updates: &[Seller],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut tx = conn.begin().await?;
+
+        if !creates.is_empty() {
+            let ids: Vec<Uuid> = creates.iter().map(|seller| seller.id).collect();
+            let names: Vec<String> = creates.iter().map(|seller| seller.name.clone()).collect();
+            let company_names: Vec<String> = creates.iter().map(|seller| seller.company_name.clone()).collect();
+            sqlx::query!(
+                r#"
+                INSERT INTO sellers (id, name, company_name)
+                SELECT * FROM UNNEST($1::UUID[], $2::TEXT[], $3::TEXT[])
+                "#,
+                &ids[..],
+                &names[..],
+                &company_names[..]
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let mut found = Vec::new();
+        if !updates.is_empty() {
+            let ids: Vec<Uuid> = updates.iter().map(|seller| seller.id).collect();
+            let names: Vec<String> = updates.iter().map(|seller| seller.name.clone()).collect();
+            let company_names: Vec<String> = updates.iter().map(|seller| seller.company_name.clone()).collect();
+            let rows = sqlx::query!(
+                r#"
+                UPDATE sellers AS s
+                SET name = u.name, company_name = u.company_name
+                FROM UNNEST($1::UUID[], $2::TEXT[], $3::TEXT[]) AS u(id, name, company_name)
+                WHERE s.id = u.id
+                RETURNING s.id::UUID as "id!"
+                "#,
+                &ids[..],
+                &names[..],
+                &company_names[..]
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            found.extend(rows.into_iter().map(|row| row.id));
+        }
+
+        if !deletes.is_empty() {
+            let rows = sqlx::query!(
+                r#"
+                DELETE FROM sellers
+                WHERE id = ANY($1)
+                RETURNING id::UUID as "id!"
+                "#,
+                deletes
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            found.extend(rows.into_iter().map(|row| row.id));
+        }
+
+        let missing: Vec<Uuid> = updates
+            .iter()
+            .map(|seller| seller.id)
+            .chain(deletes.iter().copied())
+            .filter(|id| !found.contains(id))
+            .collect();
+        if missing.is_empty() {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+        Ok(missing)
+    }
+
     /// Delete a seller from the database by ID
-    pub async fn delete_seller(pool: &PgPool, id: Uuid) -> Result<u64, sqlx::Error> {
+    pub async fn delete_seller<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<u64, sqlx::Error> {
         let result = sqlx::query!(
             r#"
             DELETE FROM sellers
@@ -76,7 +225,7 @@ impl SellerDAO {
             "#,
             id
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(result.rows_affected())
     }
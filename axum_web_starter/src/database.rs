@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Executor, PgPool};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+use crate::config::AppConfig;
+
+/// Trigram indexes backing `GET /search`. Multi-word queries match words spread over both fields
+/// of a row, hence the indexes over both.
+const SEARCH_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS customers_name_trgm_idx ON customers USING GIN (name gin_trgm_ops)",
+    "CREATE INDEX IF NOT EXISTS customers_email_trgm_idx ON customers USING GIN (email gin_trgm_ops)",
+    "CREATE INDEX IF NOT EXISTS sellers_name_trgm_idx ON sellers USING GIN (name gin_trgm_ops)",
+    "CREATE INDEX IF NOT EXISTS sellers_company_name_trgm_idx ON sellers USING GIN (company_name gin_trgm_ops)",
+    "CREATE INDEX IF NOT EXISTS customers_search_trgm_idx ON customers USING GIN ((name || ' ' || email) gin_trgm_ops)",
+    "CREATE INDEX IF NOT EXISTS sellers_search_trgm_idx ON sellers USING GIN ((name || ' ' || company_name) gin_trgm_ops)",
+];
+
+/// Wait before the second attempt to connect at startup, doubled for every attempt after it
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Pool settings from the `DB_*` variables, shared by the primary and the read replica
+pub fn pool_options(config: &AppConfig) -> PgPoolOptions {
+    let statement_timeout = config.db_statement_timeout;
+    PgPoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .acquire_timeout(config.db_acquire_timeout)
+        .idle_timeout(config.db_idle_timeout)
+        .max_lifetime(config.db_max_lifetime)
+        .after_connect(move |conn, _meta| {
+            Box::pin(async move {
+                if let Some(timeout) = statement_timeout {
+                    conn.execute(format!("SET statement_timeout = {}", timeout.as_millis()).as_str()).await?;
+                }
+                Ok(())
+            })
+        })
+}
+
+/// The pool for `url`. With `DB_LAZY_CONNECT` it connects on the first query; otherwise the
+/// database is tried up to `DB_CONNECT_RETRIES` more times with backoff before startup fails,
+/// so the app can start alongside a database that is still coming up.
+pub async fn connect(config: &AppConfig, url: &str) -> Result<PgPool> {
+    let options = pool_options(config);
+    if config.db_lazy_connect {
+        return Ok(options.connect_lazy(url)?);
+    }
+    let mut retry = 0;
+    loop {
+        match options.clone().connect(url).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if retry < config.db_connect_retries => {
+                let backoff = INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(retry)).min(MAX_BACKOFF);
+                warn!("Failed to connect to the database, retrying in {:?}: {}", backoff, e);
+                sleep(backoff).await;
+                retry += 1;
+            }
+            Err(e) => return Err(e).context("Failed to connect to the database"),
+        }
+    }
+}
+
+/// Create the trigram indexes of `GET /search`, returning whether they exist. They are optional:
+/// without them the search falls back to substring matching. CockroachDB (23.2+) supports
+/// `gin_trgm_ops` natively and never lists pg_trgm as installed, so only PostgreSQL needs the
+/// extension, which takes a privilege the database user may not have.
+pub async fn create_search_indexes(pool: &PgPool) -> Result<bool> {
+    let version: String = sqlx::query_scalar("SELECT version()").fetch_one(pool).await?;
+    if !version.contains("CockroachDB") {
+        if let Err(e) = pool.execute("CREATE EXTENSION IF NOT EXISTS pg_trgm").await {
+            warn!("pg_trgm is unavailable, search falls back to substring matching: {}", e);
+            return Ok(false);
+        }
+    }
+    for statement in SEARCH_INDEXES {
+        if let Err(e) = pool.execute(*statement).await {
+            warn!("Trigram indexes are unavailable, search falls back to substring matching: {}", e);
+            return Ok(false);
+        }
+    }
+    info!("Trigram search indexes are in place");
+    Ok(true)
+}
@@ -0,0 +1,192 @@
+#[cfg(feature = "server")]
+use axum::http::header::{RETRY_AFTER, WWW_AUTHENTICATE};
+#[cfg(feature = "server")]
+use axum::http::HeaderValue;
+#[cfg(feature = "server")]
+use axum::response::{IntoResponse, Response};
+#[cfg(feature = "server")]
+use axum::Json;
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+#[cfg(feature = "server")]
+use tonic::{Code, Status};
+#[cfg(feature = "server")]
+use tracing::error;
+use uuid::Uuid;
+#[cfg(feature = "server")]
+use utoipa::ToSchema;
+#[cfg(feature = "server")]
+use crate::utils::is_transient;
+
+/// Error returned by every handler, rendered as an `ErrorBody` JSON document
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    /// No credentials were sent; answered with `WWW-Authenticate: Bearer`
+    Unauthorized(String),
+    /// The credentials sent do not grant access
+    Forbidden(String),
+    NotFound(String),
+    Conflict { message: String, existing_id: Option<Uuid> },
+    Unprocessable(String),
+    PayloadTooLarge(String),
+    /// Details are logged but never sent to the client
+    Internal(String),
+    /// The database is unreachable or overloaded; details are logged and the client is told
+    /// to retry after `RETRY_AFTER_SECS`
+    Unavailable(String),
+}
+
+/// `Retry-After` of 503 responses
+pub const RETRY_AFTER_SECS: u64 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ErrorBody {
+    pub error: String,
+    /// ID of the record the request collided with, for 409 responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "server", schema(value_type = Option<String>, example = "d290f1ee-6c54-4b01-90e6-d701748f0851"))]
+    pub existing_id: Option<Uuid>,
+}
+
+impl AppError {
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict { message: message.into(), existing_id: None }
+    }
+
+    /// `NotFound` with `message` for a missing row, any other database error as usual
+    #[cfg(feature = "server")]
+    pub fn not_found(e: sqlx::Error, message: &str) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => AppError::NotFound(message.to_string()),
+            e => AppError::from(e),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    #[cfg(feature = "server")]
+    pub fn into_body(self) -> ErrorBody {
+        match self {
+            AppError::BadRequest(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::NotFound(message)
+            | AppError::Unprocessable(message)
+            | AppError::PayloadTooLarge(message) => ErrorBody { error: message, existing_id: None },
+            AppError::Conflict { message, existing_id } => ErrorBody { error: message, existing_id },
+            AppError::Internal(details) => {
+                error!("Internal error: {}", details);
+                ErrorBody { error: "Internal server error".to_string(), existing_id: None }
+            }
+            AppError::Unavailable(details) => {
+                error!("Database unavailable: {}", details);
+                ErrorBody { error: "Service temporarily unavailable".to_string(), existing_id: None }
+            }
+        }
+    }
+}
+
+impl AppError {
+    /// The error a client receives, rebuilt from the status and body of the response. `None`
+    /// for statuses handlers never answer with.
+    pub fn from_response(status: StatusCode, body: ErrorBody) -> Option<Self> {
+        let message = body.error;
+        match status {
+            StatusCode::BAD_REQUEST => Some(AppError::BadRequest(message)),
+            StatusCode::UNAUTHORIZED => Some(AppError::Unauthorized(message)),
+            StatusCode::FORBIDDEN => Some(AppError::Forbidden(message)),
+            StatusCode::NOT_FOUND => Some(AppError::NotFound(message)),
+            StatusCode::CONFLICT => Some(AppError::Conflict { message, existing_id: body.existing_id }),
+            StatusCode::UNPROCESSABLE_ENTITY => Some(AppError::Unprocessable(message)),
+            StatusCode::PAYLOAD_TOO_LARGE => Some(AppError::PayloadTooLarge(message)),
+            StatusCode::INTERNAL_SERVER_ERROR => Some(AppError::Internal(message)),
+            StatusCode::SERVICE_UNAVAILABLE => Some(AppError::Unavailable(message)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::BadRequest(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict { message, .. }
+            | AppError::Unprocessable(message)
+            | AppError::PayloadTooLarge(message)
+            | AppError::Internal(message)
+            | AppError::Unavailable(message) => write!(f, "{}: {}", self.status(), message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+#[cfg(feature = "server")]
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if is_transient(&e) {
+            AppError::Unavailable(e.to_string())
+        } else {
+            AppError::Internal(e.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let header = match self {
+            AppError::Unauthorized(_) => Some((WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))),
+            AppError::Unavailable(_) => Some((RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS))),
+            _ => None,
+        };
+        let mut response = (status, Json(self.into_body())).into_response();
+        if let Some((name, value)) = header {
+            response.headers_mut().insert(name, value);
+        }
+        response
+    }
+}
+
+/// gRPC counterpart of the HTTP status, so both APIs report a failure the same way
+#[cfg(feature = "server")]
+impl From<AppError> for Status {
+    fn from(e: AppError) -> Self {
+        let code = match &e {
+            AppError::BadRequest(_) | AppError::Unprocessable(_) => Code::InvalidArgument,
+            AppError::Unauthorized(_) => Code::Unauthenticated,
+            AppError::Forbidden(_) => Code::PermissionDenied,
+            AppError::NotFound(_) => Code::NotFound,
+            AppError::Conflict { existing_id: Some(_), .. } => Code::AlreadyExists,
+            AppError::Conflict { existing_id: None, .. } => Code::FailedPrecondition,
+            AppError::PayloadTooLarge(_) => Code::ResourceExhausted,
+            AppError::Internal(_) => Code::Internal,
+            AppError::Unavailable(_) => Code::Unavailable,
+        };
+        let body = e.into_body();
+        let mut status = Status::new(code, body.error);
+        if let Some(Ok(existing_id)) = body.existing_id.map(|id| id.to_string().parse()) {
+            status.metadata_mut().insert("existing-id", existing_id);
+        }
+        status
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Request, Schema};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+use crate::daos::address_dao::AddressDAO;
+use crate::daos::order_dao::OrderDAO;
+use crate::daos::product_dao::ProductDAO;
+use crate::models::address::CustomerAddress;
+use crate::models::customer::Customer;
+use crate::models::order::Order;
+use crate::models::product::Product;
+use crate::models::seller::Seller;
+use crate::repositories::customer_repository::CustomerRepository;
+use crate::repositories::seller_repository::SellerRepository;
+use crate::state::AppState;
+
+pub type AppSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Orders loaded per customer or seller; `orders(last:)` is capped at the same number
+const MAX_RECENT_ORDERS: i64 = 50;
+
+/// Read-only schema over the REST resources, rejecting queries nested or costly beyond the limits
+pub fn build_schema(max_depth: usize, max_complexity: usize) -> AppSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(max_depth)
+        .limit_complexity(max_complexity)
+        .finish()
+}
+
+/// Attach the read pool, the repositories and a fresh set of dataloaders, so lookups are batched
+/// and cached per request only
+pub fn with_loaders(request: Request, app_state: &AppState) -> Request {
+    let pool = app_state.reads.pool();
+    request
+        .data(pool.clone())
+        .data(app_state.customers.clone())
+        .data(app_state.sellers.clone())
+        .data(DataLoader::new(CustomerLoader(app_state.customers.clone()), tokio::spawn))
+        .data(DataLoader::new(SellerLoader(app_state.sellers.clone()), tokio::spawn))
+        .data(DataLoader::new(AddressesLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(ProductsLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(CustomerOrdersLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(SellerOrdersLoader(pool.clone()), tokio::spawn))
+}
+
+/// Log the cause and hide it from the client, like `AppError::Internal`
+fn internal(e: impl std::fmt::Display) -> async_graphql::Error {
+    error!("GraphQL query failed: {}", e);
+    async_graphql::Error::new("Internal server error")
+}
+
+fn group_by<T>(rows: Vec<T>, key: impl Fn(&T) -> Uuid) -> HashMap<Uuid, Vec<T>> {
+    let mut groups: HashMap<Uuid, Vec<T>> = HashMap::new();
+    for row in rows {
+        groups.entry(key(&row)).or_default().push(row);
+    }
+    groups
+}
+
+pub struct CustomerLoader(Arc<dyn CustomerRepository>);
+
+impl Loader<Uuid> for CustomerLoader {
+    type Value = Customer;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Customer>, Self::Error> {
+        let customers = self.0.list_customers_by_ids(keys).await?;
+        Ok(customers.into_iter().map(|customer| (customer.id, customer)).collect())
+    }
+}
+
+pub struct SellerLoader(Arc<dyn SellerRepository>);
+
+impl Loader<Uuid> for SellerLoader {
+    type Value = Seller;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Seller>, Self::Error> {
+        let sellers = self.0.list_sellers_by_ids(keys).await?;
+        Ok(sellers.into_iter().map(|seller| (seller.id, seller)).collect())
+    }
+}
+
+/// Addresses keyed by customer ID
+pub struct AddressesLoader(PgPool);
+
+impl Loader<Uuid> for AddressesLoader {
+    type Value = Vec<CustomerAddress>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<CustomerAddress>>, Self::Error> {
+        let addresses = AddressDAO::list_addresses_by_customers(&self.0, keys).await?;
+        Ok(group_by(addresses, |address| address.customer_id))
+    }
+}
+
+/// Products keyed by seller ID
+pub struct ProductsLoader(PgPool);
+
+impl Loader<Uuid> for ProductsLoader {
+    type Value = Vec<Product>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<Product>>, Self::Error> {
+        let products = ProductDAO::list_products_by_sellers(&self.0, keys).await?;
+        Ok(group_by(products, |product| product.seller_id))
+    }
+}
+
+/// Most recent orders keyed by customer ID
+pub struct CustomerOrdersLoader(PgPool);
+
+impl Loader<Uuid> for CustomerOrdersLoader {
+    type Value = Vec<Order>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<Order>>, Self::Error> {
+        let orders = OrderDAO::list_recent_orders_by_customers(&self.0, keys, MAX_RECENT_ORDERS).await?;
+        Ok(group_by(orders, |order| order.customer_id))
+    }
+}
+
+/// Most recent orders keyed by seller ID
+pub struct SellerOrdersLoader(PgPool);
+
+impl Loader<Uuid> for SellerOrdersLoader {
+    type Value = Vec<Order>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<Order>>, Self::Error> {
+        let orders = OrderDAO::list_recent_orders_by_sellers(&self.0, keys, MAX_RECENT_ORDERS).await?;
+        Ok(group_by(orders, |order| order.seller_id))
+    }
+}
+
+fn recent(orders: Option<Vec<Order>>, last: i32) -> Vec<Order> {
+    let mut orders = orders.unwrap_or_default();
+    orders.truncate(last as usize);
+    orders
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn customer(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Customer>> {
+        ctx.data_unchecked::<DataLoader<CustomerLoader>>().load_one(id).await.map_err(internal)
+    }
+
+    /// A page of customers, paged like `GET /customers?limit=&offset=`
+    #[graphql(complexity = "first as usize * child_complexity")]
+    async fn customers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] first: i32,
+        #[graphql(default = 0, validator(minimum = 0))] offset: i32,
+    ) -> async_graphql::Result<Vec<Customer>> {
+        let customers = ctx.data_unchecked::<Arc<dyn CustomerRepository>>();
+        customers.list_customers_page(i64::from(first), i64::from(offset)).await.map_err(internal)
+    }
+
+    async fn seller(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Seller>> {
+        ctx.data_unchecked::<DataLoader<SellerLoader>>().load_one(id).await.map_err(internal)
+    }
+
+    /// A page of sellers, paged like `GET /sellers?limit=&offset=`
+    #[graphql(complexity = "first as usize * child_complexity")]
+    async fn sellers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] first: i32,
+        #[graphql(default = 0, validator(minimum = 0))] offset: i32,
+    ) -> async_graphql::Result<Vec<Seller>> {
+        let sellers = ctx.data_unchecked::<Arc<dyn SellerRepository>>();
+        sellers.list_sellers_page(i64::from(first), i64::from(offset)).await.map_err(internal)
+    }
+
+    async fn order(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Order>> {
+        match OrderDAO::get_order(ctx.data_unchecked::<PgPool>(), id).await {
+            Ok(order) => Ok(Some(order)),
+            Err(sqlx::Error::RowNotFound) => Ok(None),
+            Err(e) => Err(internal(e)),
+        }
+    }
+
+    async fn product(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Product>> {
+        match ProductDAO::get_product(ctx.data_unchecked::<PgPool>(), id).await {
+            Ok(product) => Ok(Some(product)),
+            Err(sqlx::Error::RowNotFound) => Ok(None),
+            Err(e) => Err(internal(e)),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Customer {
+    async fn addresses(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<CustomerAddress>> {
+        let addresses = ctx.data_unchecked::<DataLoader<AddressesLoader>>().load_one(self.id).await;
+        addresses.map(Option::unwrap_or_default).map_err(internal)
+    }
+
+    /// Most recent orders first
+    #[graphql(complexity = "last as usize * child_complexity")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10, validator(minimum = 1, maximum = 50))] last: i32,
+    ) -> async_graphql::Result<Vec<Order>> {
+        let orders = ctx.data_unchecked::<DataLoader<CustomerOrdersLoader>>().load_one(self.id).await;
+        orders.map(|orders| recent(orders, last)).map_err(internal)
+    }
+}
+
+#[ComplexObject]
+impl Seller {
+    #[graphql(complexity = "10 * child_complexity")]
+    async fn products(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Product>> {
+        let products = ctx.data_unchecked::<DataLoader<ProductsLoader>>().load_one(self.id).await;
+        products.map(Option::unwrap_or_default).map_err(internal)
+    }
+
+    /// Most recent orders first
+    #[graphql(complexity = "last as usize * child_complexity")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10, validator(minimum = 1, maximum = 50))] last: i32,
+    ) -> async_graphql::Result<Vec<Order>> {
+        let orders = ctx.data_unchecked::<DataLoader<SellerOrdersLoader>>().load_one(self.id).await;
+        orders.map(|orders| recent(orders, last)).map_err(internal)
+    }
+}
+
+#[ComplexObject]
+impl Order {
+    async fn customer(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Customer>> {
+        ctx.data_unchecked::<DataLoader<CustomerLoader>>().load_one(self.customer_id).await.map_err(internal)
+    }
+
+    async fn seller(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Seller>> {
+        ctx.data_unchecked::<DataLoader<SellerLoader>>().load_one(self.seller_id).await.map_err(internal)
+    }
+}
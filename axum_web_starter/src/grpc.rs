@@ -0,0 +1,189 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::StatusCode;
+use tokio::task::JoinHandle;
+use tonic::service::Routes;
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+use uuid::Uuid;
+use crate::handlers::customer_handler::{
+    create_customer_api, delete_customer_api, get_customer_api, get_customer_by_email_api, list_customers_api,
+    update_customer_api, upsert_customer_api,
+};
+use crate::handlers::seller_handler::SellerHandler;
+use crate::models::customer::{self, CustomerPayload, CustomerQueryParams};
+use crate::models::page::Page;
+use crate::models::seller::{self, SellerPayload};
+use crate::state::AppState;
+use pb::customer_service_server::{CustomerService, CustomerServiceServer};
+use pb::seller_service_server::{SellerService, SellerServiceServer};
+
+pub mod pb {
+    tonic::include_proto!("sillycat.v1");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("sillycat_descriptor");
+}
+
+fn parse_id(id: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(id).map_err(|_| Status::invalid_argument(format!("Invalid ID: {}", id)))
+}
+
+impl From<customer::Customer> for pb::Customer {
+    fn from(customer: customer::Customer) -> Self {
+        pb::Customer { id: customer.id.to_string(), name: customer.name, email: customer.email }
+    }
+}
+
+impl From<seller::Seller> for pb::Seller {
+    fn from(seller: seller::Seller) -> Self {
+        pb::Seller { id: seller.id.to_string(), name: seller.name, company_name: seller.company_name }
+    }
+}
+
+/// Serves the REST customer operations over gRPC, sharing their validation, DAO calls and caching
+pub struct CustomerGrpcService {
+    app_state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl CustomerService for CustomerGrpcService {
+    async fn create_customer(
+        &self,
+        request: Request<pb::CreateCustomerRequest>,
+    ) -> Result<Response<pb::Customer>, Status> {
+        let request = request.into_inner();
+        let payload = CustomerPayload { name: request.name, email: request.email };
+        let Json(customer) = create_customer_api(State(self.app_state.clone()), Json(payload)).await?;
+        Ok(Response::new(customer.into()))
+    }
+
+    async fn upsert_customer(
+        &self,
+        request: Request<pb::UpsertCustomerRequest>,
+    ) -> Result<Response<pb::UpsertCustomerResponse>, Status> {
+        let request = request.into_inner();
+        let payload = CustomerPayload { name: request.name, email: request.email };
+        let (status, Json(customer)) = upsert_customer_api(State(self.app_state.clone()), Json(payload)).await?;
+        Ok(Response::new(pb::UpsertCustomerResponse {
+            customer: Some(customer.into()),
+            created: status == StatusCode::CREATED,
+        }))
+    }
+
+    async fn list_customers(
+        &self,
+        _request: Request<pb::ListCustomersRequest>,
+    ) -> Result<Response<pb::ListCustomersResponse>, Status> {
+        let Json(customers) = list_customers_api(State(self.app_state.clone()), Query(Page::default())).await?;
+        Ok(Response::new(pb::ListCustomersResponse {
+            customers: customers.into_iter().map(pb::Customer::from).collect(),
+        }))
+    }
+
+    async fn get_customer(&self, request: Request<pb::GetCustomerRequest>) -> Result<Response<pb::Customer>, Status> {
+        let id = parse_id(&request.into_inner().id)?;
+        let params = CustomerQueryParams { expand: None };
+        let Json(details) = get_customer_api(State(self.app_state.clone()), Path(id), Query(params)).await?;
+        Ok(Response::new(details.customer.into()))
+    }
+
+    async fn get_customer_by_email(
+        &self,
+        request: Request<pb::GetCustomerByEmailRequest>,
+    ) -> Result<Response<pb::Customer>, Status> {
+        let email = request.into_inner().email;
+        let Json(customer) = get_customer_by_email_api(State(self.app_state.clone()), Path(email)).await?;
+        Ok(Response::new(customer.into()))
+    }
+
+    async fn update_customer(
+        &self,
+        request: Request<pb::UpdateCustomerRequest>,
+    ) -> Result<Response<pb::Customer>, Status> {
+        let request = request.into_inner();
+        let id = parse_id(&request.id)?;
+        let payload = CustomerPayload { name: request.name, email: request.email };
+        let Json(customer) = update_customer_api(State(self.app_state.clone()), Path(id), Json(payload)).await?;
+        Ok(Response::new(customer.into()))
+    }
+
+    async fn delete_customer(
+        &self,
+        request: Request<pb::DeleteCustomerRequest>,
+    ) -> Result<Response<pb::DeleteCustomerResponse>, Status> {
+        let id = parse_id(&request.into_inner().id)?;
+        delete_customer_api(State(self.app_state.clone()), Path(id)).await?;
+        Ok(Response::new(pb::DeleteCustomerResponse {}))
+    }
+}
+
+/// Serves the REST seller operations over gRPC
+pub struct SellerGrpcService {
+    app_state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl SellerService for SellerGrpcService {
+    async fn create_seller(&self, request: Request<pb::CreateSellerRequest>) -> Result<Response<pb::Seller>, Status> {
+        let request = request.into_inner();
+        let payload = SellerPayload { name: request.name, company_name: request.company_name };
+        let Json(seller) = SellerHandler::create_seller(State(self.app_state.clone()), Json(payload)).await?;
+        Ok(Response::new(seller.into()))
+    }
+
+    async fn list_sellers(
+        &self,
+        _request: Request<pb::ListSellersRequest>,
+    ) -> Result<Response<pb::ListSellersResponse>, Status> {
+        let Json(sellers) = SellerHandler::list_sellers(State(self.app_state.clone()), Query(Page::default())).await?;
+        Ok(Response::new(pb::ListSellersResponse {
+            sellers: sellers.into_iter().map(pb::Seller::from).collect(),
+        }))
+    }
+
+    async fn get_seller(&self, request: Request<pb::GetSellerRequest>) -> Result<Response<pb::Seller>, Status> {
+        let id = parse_id(&request.into_inner().id)?;
+        let Json(seller) = SellerHandler::get_seller(State(self.app_state.clone()), Path(id)).await?;
+        Ok(Response::new(seller.into()))
+    }
+
+    async fn update_seller(&self, request: Request<pb::UpdateSellerRequest>) -> Result<Response<pb::Seller>, Status> {
+        let request = request.into_inner();
+        let id = parse_id(&request.id)?;
+        let payload = SellerPayload { name: request.name, company_name: request.company_name };
+        let Json(seller) = SellerHandler::update_seller(State(self.app_state.clone()), Path(id), Json(payload)).await?;
+        Ok(Response::new(seller.into()))
+    }
+
+    async fn delete_seller(
+        &self,
+        request: Request<pb::DeleteSellerRequest>,
+    ) -> Result<Response<pb::DeleteSellerResponse>, Status> {
+        let id = parse_id(&request.into_inner().id)?;
+        SellerHandler::delete_seller(State(self.app_state.clone()), Path(id)).await?;
+        Ok(Response::new(pb::DeleteSellerResponse {}))
+    }
+}
+
+/// Customer and seller services plus server reflection, so tools like grpcurl can list them
+pub fn grpc_routes(app_state: Arc<AppState>) -> Routes {
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(pb::FILE_DESCRIPTOR_SET)
+        .build_v1()
+        .expect("the generated file descriptor set is valid");
+    Routes::new(CustomerServiceServer::new(CustomerGrpcService { app_state: app_state.clone() }))
+        .add_service(SellerServiceServer::new(SellerGrpcService { app_state }))
+        .add_service(reflection)
+}
+
+/// Serve gRPC on its own port instead of sharing the HTTP one
+pub fn spawn_server(routes: Routes, port: u16) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+        info!("gRPC server running on {}", addr);
+        if let Err(e) = tonic::transport::Server::builder().add_routes(routes).serve(addr).await {
+            error!("gRPC server failed: {}", e);
+        }
+    })
+}
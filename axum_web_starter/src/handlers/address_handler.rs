@@ -0,0 +1,193 @@
+use axum::extract::{State, Json, Path};
+use std::sync::Arc;
+use crate::daos::address_dao::AddressDAO;
+use crate::handlers::customer_handler::invalidate_customer_addresses;
+use crate::models::address::CustomerAddress;
+use crate::models::address::CustomerAddressPayload;
+use crate::errors::AppError;
+use crate::state::AppState;
+use crate::utils::is_unique_violation;
+use uuid::Uuid;
+
+pub struct AddressHandler;
+
+/// Two concurrent requests both promoting an address to default trip the partial unique index
+fn default_conflict() -> AppError {
+    AppError::conflict("Another default address of this kind was set concurrently")
+}
+
+#[utoipa::path(
+    post,
+    path = "/customers/{id}/addresses",
+    request_body = CustomerAddressPayload,
+    params(
+        ("id" = String, description = "ID of the customer", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Address created successfully", body = CustomerAddress),
+        (status = 400, description = "Invalid address payload"),
+        (status = 404, description = "Customer not found"),
+        (status = 409, description = "Concurrent default address change")
+    )
+)]
+pub async fn create_address_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(customer_id): Path<Uuid>,
+    Json(mut payload): Json<CustomerAddressPayload>,
+) -> Result<Json<CustomerAddress>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    app_state.customers.get_customer(customer_id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Customer not found"))?;
+
+    match AddressDAO::create_address(&app_state.db_pool, customer_id, &payload).await {
+        Ok(address) => {
+            invalidate_customer_addresses(&app_state, customer_id).await;
+            Ok(Json(address))
+        }
+        Err(e) if is_unique_violation(&e) => Err(default_conflict()),
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/{id}/addresses",
+    params(
+        ("id" = String, description = "ID of the customer", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Addresses of the customer, defaults first", body = [CustomerAddress]),
+        (status = 404, description = "Customer not found")
+    )
+)]
+pub async fn list_addresses_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(customer_id): Path<Uuid>,
+) -> Result<Json<Vec<CustomerAddress>>, AppError> {
+    app_state.customers.get_customer(customer_id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Customer not found"))?;
+    AddressDAO::list_addresses(app_state.reads.pool(), customer_id)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/{id}/addresses/{address_id}",
+    params(
+        ("id" = String, description = "ID of the customer", example = "d290f1ee-6c54-4b01-90e6-d701748f0851"),
+        ("address_id" = String, description = "ID of the address", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Address details", body = CustomerAddress),
+        (status = 404, description = "Address not found")
+    )
+)]
+pub async fn get_address_api(
+    State(app_state): State<Arc<AppState>>,
+    Path((customer_id, address_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<CustomerAddress>, AppError> {
+    AddressDAO::get_address(app_state.reads.pool(), customer_id, address_id)
+        .await
+        .map(Json)
+        .map_err(|e| AppError::not_found(e, "Address not found"))
+}
+
+#[utoipa::path(
+    put,
+    path = "/customers/{id}/addresses/{address_id}",
+    request_body = CustomerAddressPayload,
+    params(
+        ("id" = String, description = "ID of the customer", example = "d290f1ee-6c54-4b01-90e6-d701748f0851"),
+        ("address_id" = String, description = "ID of the address", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Updated address", body = CustomerAddress),
+        (status = 400, description = "Invalid address payload"),
+        (status = 404, description = "Address not found"),
+        (status = 409, description = "Concurrent default address change")
+    )
+)]
+pub async fn update_address_api(
+    State(app_state): State<Arc<AppState>>,
+    Path((customer_id, address_id)): Path<(Uuid, Uuid)>,
+    Json(mut payload): Json<CustomerAddressPayload>,
+) -> Result<Json<CustomerAddress>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    match AddressDAO::update_address(&app_state.db_pool, customer_id, address_id, &payload).await {
+        Ok(address) => {
+            invalidate_customer_addresses(&app_state, customer_id).await;
+            Ok(Json(address))
+        }
+        Err(e) if is_unique_violation(&e) => Err(default_conflict()),
+        Err(e) => Err(AppError::not_found(e, "Address not found")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/customers/{id}/addresses/{address_id}",
+    params(
+        ("id" = String, description = "ID of the customer", example = "d290f1ee-6c54-4b01-90e6-d701748f0851"),
+        ("address_id" = String, description = "ID of the address", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Address deleted successfully"),
+        (status = 404, description = "Address not found")
+    )
+)]
+pub async fn delete_address_api(
+    State(app_state): State<Arc<AppState>>,
+    Path((customer_id, address_id)): Path<(Uuid, Uuid)>,
+) -> Result<&'static str, AppError> {
+    match AddressDAO::delete_address(&app_state.db_pool, customer_id, address_id).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            invalidate_customer_addresses(&app_state, customer_id).await;
+            Ok("Address deleted")
+        }
+        Ok(_) => Err(AppError::NotFound("Address not found".to_string())),
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+impl AddressHandler {
+    pub async fn create_address(
+        state: State<Arc<AppState>>,
+        customer_id: Path<Uuid>,
+        payload: Json<CustomerAddressPayload>,
+    ) -> Result<Json<CustomerAddress>, AppError> {
+        create_address_api(state, customer_id, payload).await
+    }
+
+    pub async fn list_addresses(
+        state: State<Arc<AppState>>,
+        customer_id: Path<Uuid>,
+    ) -> Result<Json<Vec<CustomerAddress>>, AppError> {
+        list_addresses_api(state, customer_id).await
+    }
+
+    pub async fn get_address(
+        state: State<Arc<AppState>>,
+        ids: Path<(Uuid, Uuid)>,
+    ) -> Result<Json<CustomerAddress>, AppError> {
+        get_address_api(state, ids).await
+    }
+
+    pub async fn update_address(
+        state: State<Arc<AppState>>,
+        ids: Path<(Uuid, Uuid)>,
+        payload: Json<CustomerAddressPayload>,
+    ) -> Result<Json<CustomerAddress>, AppError> {
+        update_address_api(state, ids, payload).await
+    }
+
+    pub async fn delete_address(
+        state: State<Arc<AppState>>,
+        ids: Path<(Uuid, Uuid)>,
+    ) -> Result<&'static str, AppError> {
+        delete_address_api(state, ids).await
+    }
+}
@@ -0,0 +1,171 @@
+use axum::extract::{Json, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use futures::TryStreamExt;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::auth::Principal;
+use crate::errors::{AppError, ErrorBody};
+use crate::handlers::customer_handler::{customer_addresses_cache_key, customer_cache_key, evict_customer};
+use crate::handlers::job_handler::submit_job;
+use crate::handlers::product_handler::{invalidate_products, product_cache_key};
+use crate::handlers::seller_handler::{evict_seller, seller_cache_key};
+use crate::models::cache::{CacheDeletion, CacheEntity, CachePrefixParams, CacheStats, WarmCacheRequest};
+use crate::models::job::{CacheJobPayload, Job, JobKind};
+use crate::state::AppState;
+use uuid::Uuid;
+
+fn cache_error(e: opendal::Error) -> AppError {
+    AppError::Internal(format!("Cache error: {}", e))
+}
+
+/// Every key cached for one row
+fn row_keys(entity: CacheEntity, id: Uuid) -> Vec<String> {
+    match entity {
+        CacheEntity::Customer => vec![customer_cache_key(id), customer_addresses_cache_key(id)],
+        CacheEntity::Seller => vec![seller_cache_key(id)],
+        CacheEntity::Product => vec![product_cache_key(id)],
+    }
+}
+
+/// The keys starting with `prefix` that rows in the store could be cached under, for backends
+/// that cannot list their keys. `customer:` narrows down to customers, while `c` covers them all.
+async fn stored_keys(app_state: &AppState, prefix: &str) -> Result<Vec<String>, AppError> {
+    let mut keys = Vec::new();
+    for entity in CacheEntity::ALL {
+        let entity_prefix = entity.prefix();
+        if !prefix.starts_with(&entity_prefix) && !entity_prefix.starts_with(prefix) {
+            continue;
+        }
+        let ids = match entity {
+            CacheEntity::Customer => {
+                let customers = app_state.customers.stream_customers();
+                customers.map_ok(|customer| customer.id).try_collect().await?
+            }
+            CacheEntity::Seller => seller_ids(app_state).await?,
+            CacheEntity::Product => app_state.sellers.list_product_ids(&seller_ids(app_state).await?).await?,
+        };
+        keys.extend(ids.into_iter().flat_map(|id| row_keys(entity, id)));
+    }
+    keys.retain(|key| key.starts_with(prefix));
+    Ok(keys)
+}
+
+async fn seller_ids(app_state: &AppState) -> Result<Vec<Uuid>, sqlx::Error> {
+    app_state.sellers.stream_sellers().map_ok(|seller| seller.id).try_collect().await
+}
+
+/// Queue a job loading customers or sellers into the cache and answer 202 pointing at it
+#[utoipa::path(
+    post,
+    path = "/admin/cache/warm",
+    request_body = WarmCacheRequest,
+    responses(
+        (status = 202, description = "Job queued, poll the Location header for its status", body = Job),
+        (status = 400, description = "Products cannot be warmed, or ids is empty", body = ErrorBody),
+        (status = 401, description = "No admin credentials", body = ErrorBody),
+        (status = 403, description = "Credentials of someone who is not an admin", body = ErrorBody)
+    )
+)]
+pub async fn warm_cache(
+    State(app_state): State<Arc<AppState>>,
+    principal: Principal,
+    Json(request): Json<WarmCacheRequest>,
+) -> Result<Response, AppError> {
+    let kind = match request.entity {
+        CacheEntity::Customer => JobKind::WarmCustomerCache,
+        CacheEntity::Seller => JobKind::WarmSellerCache,
+        CacheEntity::Product => {
+            return Err(AppError::BadRequest("Only customers and sellers can be warmed".to_string()));
+        }
+    };
+    if request.ids.as_ref().is_some_and(Vec::is_empty) {
+        return Err(AppError::BadRequest("ids must not be empty".to_string()));
+    }
+    let payload = serde_json::to_value(CacheJobPayload { ids: request.ids }).unwrap();
+    submit_job(&app_state, &principal, kind, payload, None, None).await
+}
+
+/// Drop everything cached for one row
+#[utoipa::path(
+    delete,
+    path = "/admin/cache/{entity}/{id}",
+    params(
+        ("entity" = CacheEntity, Path, description = "`customer`, `seller` or `product`"),
+        ("id" = String, Path, description = "ID of the row", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 204, description = "Nothing is cached for the row anymore"),
+        (status = 401, description = "No admin credentials", body = ErrorBody),
+        (status = 403, description = "Credentials of someone who is not an admin", body = ErrorBody)
+    )
+)]
+pub async fn evict_cache_entry(
+    State(app_state): State<Arc<AppState>>,
+    Path((entity, id)): Path<(CacheEntity, Uuid)>,
+) -> StatusCode {
+    match entity {
+        CacheEntity::Customer => evict_customer(&app_state, id).await,
+        CacheEntity::Seller => evict_seller(&app_state, id).await,
+        CacheEntity::Product => invalidate_products(&app_state, [id]).await,
+    }
+    StatusCode::NO_CONTENT
+}
+
+/// Drop every key starting with `prefix`. Backends that cannot list their keys, such as Redis,
+/// delete the keys of every stored row matching the prefix instead, so `deleted` counts those
+/// keys whether or not they were cached.
+#[utoipa::path(
+    delete,
+    path = "/admin/cache",
+    params(CachePrefixParams),
+    responses(
+        (status = 200, description = "Keys deleted", body = CacheDeletion),
+        (status = 400, description = "Empty prefix", body = ErrorBody),
+        (status = 401, description = "No admin credentials", body = ErrorBody),
+        (status = 403, description = "Credentials of someone who is not an admin", body = ErrorBody),
+        (status = 500, description = "The cache failed", body = ErrorBody)
+    )
+)]
+pub async fn delete_cache_prefix(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<CachePrefixParams>,
+) -> Result<Json<CacheDeletion>, AppError> {
+    if params.prefix.is_empty() {
+        return Err(AppError::BadRequest("prefix must not be empty".to_string()));
+    }
+    let cache = &app_state.cache;
+    if cache.can_list() {
+        let deleted = cache.delete_prefix(&params.prefix).await.map_err(cache_error)?;
+        return Ok(Json(CacheDeletion { deleted }));
+    }
+    let keys = stored_keys(&app_state, &params.prefix).await?;
+    for key in &keys {
+        cache.delete(key).await.map_err(cache_error)?;
+    }
+    Ok(Json(CacheDeletion { deleted: keys.len() }))
+}
+
+/// Hits and misses since startup, the circuit breaker, and the keys of each entity
+#[utoipa::path(
+    get,
+    path = "/admin/cache/stats",
+    responses(
+        (status = 200, description = "Cache counters, with `keys` null when the backend cannot list them", body = CacheStats),
+        (status = 401, description = "No admin credentials", body = ErrorBody),
+        (status = 403, description = "Credentials of someone who is not an admin", body = ErrorBody)
+    )
+)]
+pub async fn cache_stats(State(app_state): State<Arc<AppState>>) -> Result<Json<CacheStats>, AppError> {
+    let keys = if app_state.cache.can_list() {
+        let mut keys = BTreeMap::new();
+        for entity in CacheEntity::ALL {
+            let count = app_state.cache.keys(&entity.prefix()).await.map_err(cache_error)?.len();
+            keys.insert(entity.name(), count);
+        }
+        Some(keys)
+    } else {
+        None
+    };
+    Ok(Json(CacheStats { keys, cache: app_state.cache.health() }))
+}
@@ -1,56 +1,300 @@
-use axum::extract::{State, Json, Path};
+use axum::body::Bytes;
+use axum::extract::{State, Json, Path, Query};
+use axum::response::{IntoResponse, Response};
 use std::sync::Arc;
-use axum::http::StatusCode;
-use crate::daos::customer_dao::CustomerDAO;
+use axum::http::{HeaderMap, StatusCode};
 use crate::models::customer::Customer;
+use crate::models::customer::CustomerDetails;
 use crate::models::customer::CustomerPayload;
+use crate::models::address::CustomerAddress;
+use crate::models::customer::CustomerQueryParams;
+use crate::models::page::Page;
+use crate::models::customer::{CustomerBatchOperation, CustomerBatchRequest};
+use crate::models::batch::{check_batch_size, run_batch, BatchPlan, BatchResponse, Planned};
+use crate::models::transfer::{DataFormat, ExportParams, ImportParams, ImportReport};
+use crate::transfer;
+use crate::auth::Principal;
+use crate::handlers::job_handler::submit_import;
+use crate::models::job::{Job, JobKind};
+use crate::errors::{AppError, ErrorBody};
+use crate::read_replica::ReadTarget;
 use crate::state::AppState;
+use crate::unit_of_work;
+use crate::utils::{is_foreign_key_violation, is_unique_violation};
 use uuid::Uuid;
-use tracing::{error, info};
+use tracing::error;
 
 
 pub struct CustomerHandler;
 
+pub fn customer_cache_key(id: Uuid) -> String {
+    format!("customer:{}", id)
+}
+
+/// The `?expand=addresses` representation is cached separately from the plain customer
+pub fn customer_addresses_cache_key(id: Uuid) -> String {
+    format!("customer:{}:addresses", id)
+}
+
+pub async fn invalidate_customer_addresses(app_state: &AppState, id: Uuid) {
+    if let Err(e) = app_state.cache.delete(&customer_addresses_cache_key(id)).await {
+        error!("Cache delete error: {}", e);
+    }
+}
+
+pub async fn cache_customer(app_state: &AppState, customer: &Customer) {
+    app_state.cache.put(&customer_cache_key(customer.id), customer).await;
+}
+
+/// Turn a unique email violation into a 409 pointing at the customer that owns the email
+async fn email_conflict(app_state: &AppState, email: &str, e: sqlx::Error) -> AppError {
+    if !is_unique_violation(&e) {
+        return AppError::from(e);
+    }
+    let existing_id = app_state.customers.get_customer_by_email(email)
+        .await
+        .ok()
+        .map(|customer| customer.id);
+    AppError::Conflict {
+        message: format!("A customer with email {} already exists", email),
+        existing_id,
+    }
+}
+
+pub async fn evict_customer(app_state: &AppState, id: Uuid) {
+    let cache_key = customer_cache_key(id);
+    if let Err(e) = app_state.cache.delete(&cache_key).await {
+        error!("Cache delete error: {}", e);
+    }
+    invalidate_customer_addresses(app_state, id).await;
+}
+
+/// Create a customer from an already validated payload
+async fn insert_customer(app_state: &AppState, payload: CustomerPayload) -> Result<Customer, AppError> {
+    match app_state.customers.create_customer(payload.name, payload.email.clone()).await {
+        Ok(customer) => {
+            // Cache the newly created customer
+            cache_customer(app_state, &customer).await;
+            Ok(customer)
+        }
+        Err(e) => Err(email_conflict(app_state, &payload.email, e).await),
+    }
+}
+
+/// Update a customer from an already validated payload
+async fn replace_customer(app_state: &AppState, id: Uuid, payload: CustomerPayload) -> Result<Customer, AppError> {
+    match app_state.customers.update_customer(id, payload.name, payload.email.clone()).await {
+        Ok(customer) => {
+            // Update cache
+            cache_customer(app_state, &customer).await;
+            invalidate_customer_addresses(app_state, id).await;
+            Ok(customer)
+        }
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Customer not found".to_string())),
+        Err(e) => Err(email_conflict(app_state, &payload.email, e).await),
+    }
+}
+
+async fn remove_customer(app_state: &AppState, id: Uuid) -> Result<(), AppError> {
+    // The open order check and the delete commit together
+    let customers = app_state.customers.clone();
+    let result = unit_of_work::run(app_state, move |uow| {
+        let customers = customers.clone();
+        Box::pin(async move {
+            let open_orders = customers.count_open_orders(uow, id).await?;
+            if open_orders > 0 {
+                return Ok(Err(open_orders));
+            }
+            let rows_affected = customers.delete_customer(uow, id).await?;
+            if rows_affected > 0 {
+                uow.cache_delete(customer_cache_key(id));
+                uow.cache_delete(customer_addresses_cache_key(id));
+            }
+            Ok::<_, sqlx::Error>(Ok(rows_affected))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(rows_affected)) if rows_affected > 0 => Ok(()),
+        Ok(Ok(_)) => Err(AppError::NotFound("Customer not found".to_string())),
+        Ok(Err(open_orders)) => Err(AppError::conflict(format!(
+            "Customer has {} open orders and cannot be deleted",
+            open_orders
+        ))),
+        Err(e) if is_foreign_key_violation(&e) => Err(AppError::conflict(
+            "Customer is referenced by past orders and cannot be deleted",
+        )),
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+async fn apply_customer_operation(
+    app_state: &AppState,
+    operation: CustomerBatchOperation,
+) -> Result<(StatusCode, Uuid), AppError> {
+    match operation {
+        CustomerBatchOperation::Create { payload } => insert_customer(app_state, payload)
+            .await
+            .map(|customer| (StatusCode::CREATED, customer.id)),
+        CustomerBatchOperation::Update { id, payload } => replace_customer(app_state, id, payload)
+            .await
+            .map(|customer| (StatusCode::OK, customer.id)),
+        CustomerBatchOperation::Delete { id } => remove_customer(app_state, id)
+            .await
+            .map(|_| (StatusCode::OK, id)),
+    }
+}
+
+/// Apply validated operations in one transaction; the cache is only touched once it has committed
+async fn apply_customer_batch(
+    app_state: &AppState,
+    operations: Vec<CustomerBatchOperation>,
+) -> Result<(StatusCode, BatchResponse), AppError> {
+    let plan = BatchPlan::new(
+        operations,
+        |operation| match operation {
+            CustomerBatchOperation::Create { payload } => {
+                Planned::Create(Customer { id: Uuid::new_v4(), name: payload.name, email: payload.email })
+            }
+            CustomerBatchOperation::Update { id, payload } => {
+                Planned::Update(Customer { id, name: payload.name, email: payload.email })
+            }
+            CustomerBatchOperation::Delete { id } => Planned::Delete(id),
+        },
+        |customer| customer.id,
+    );
+    let (creates, updates, deletes) = (&plan.creates, &plan.updates, &plan.deletes);
+
+    let customers = &app_state.customers;
+    if !deletes.is_empty() {
+        let referenced = customers.customers_with_orders(deletes).await?;
+        if !referenced.is_empty() {
+            return Ok(plan.abort(|id| {
+                referenced
+                    .contains(&id)
+                    .then(|| AppError::conflict("Customer is referenced by orders and cannot be deleted"))
+            }));
+        }
+    }
+
+    match customers.apply_batch(creates, updates, deletes).await {
+        Ok(missing) if missing.is_empty() => {}
+        Ok(missing) => {
+            return Ok(plan.abort(|id| {
+                missing
+                    .contains(&id)
+                    .then(|| AppError::NotFound(format!("Customer {} not found", id)))
+            }));
+        }
+        Err(e) if is_unique_violation(&e) => {
+            let written: Vec<&Customer> = creates.iter().chain(updates).collect();
+            let emails: Vec<String> = written.iter().map(|customer| customer.email.clone()).collect();
+            let owners = customers.list_customers_by_emails(&emails).await?;
+            return Ok(plan.abort(|id| {
+                let customer = written.iter().find(|customer| customer.id == id)?;
+                let owner = owners
+                    .iter()
+                    .find(|owner| owner.id != id && owner.email.to_lowercase() == customer.email.to_lowercase())?;
+                Some(AppError::Conflict {
+                    message: format!("A customer with email {} already exists", customer.email),
+                    existing_id: Some(owner.id),
+                })
+            }));
+        }
+        Err(e) if is_foreign_key_violation(&e) => {
+            // An order was placed between the check above and the delete
+            return Ok(plan.abort(|id| {
+                deletes
+                    .contains(&id)
+                    .then(|| AppError::conflict("Customer is referenced by orders and cannot be deleted"))
+            }));
+        }
+        Err(e) => return Err(AppError::from(e)),
+    }
+
+    // Warm the cache with the rows that now exist
+    for customer in creates.iter().chain(updates) {
+        cache_customer(app_state, customer).await;
+    }
+    for customer in updates {
+        invalidate_customer_addresses(app_state, customer.id).await;
+    }
+    for id in deletes {
+        evict_customer(app_state, *id).await;
+    }
+    Ok(plan.applied())
+}
+
 #[utoipa::path(
     post,
     path = "/customers",
     request_body = CustomerPayload,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response when the request is retried with the same key")
+    ),
     responses(
-        (status = 201, description = "Customer created successfully", body = Customer),
+        (status = 200, description = "Customer created successfully", body = Customer),
+        (status = 400, description = "Invalid customer payload"),
+        (status = 409, description = "Email already in use, or a retry while the first request is in progress", body = ErrorBody),
+        (status = 422, description = "Idempotency-Key reused for a different request", body = ErrorBody),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn create_customer_api(
     State(app_state): State<Arc<AppState>>,
-    Json(payload): Json<CustomerPayload>,
-) -> Result<Json<Customer>, (StatusCode, String)> {
-    match CustomerDAO::create_customer(&app_state.db_pool, payload.name, payload.email).await {
-        Ok(customer) => {
-            // Cache the newly created customer
-            let cache_key = format!("customer:{}", customer.id);
-            if let Err(e) = app_state.cache.write(&cache_key, serde_json::to_string(&customer).unwrap()).await {
-                error!("Cache write error: {}", e);
-            }
-            Ok(Json(customer))
-        }
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
-    }
+    Json(mut payload): Json<CustomerPayload>,
+) -> Result<Json<Customer>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    insert_customer(&app_state, payload).await.map(Json)
 }
 
+#[utoipa::path(
+    put,
+    path = "/customers",
+    request_body = CustomerPayload,
+    responses(
+        (status = 200, description = "Existing customer with this email updated", body = Customer),
+        (status = 201, description = "Customer created", body = Customer),
+        (status = 400, description = "Invalid customer payload")
+    )
+)]
+pub async fn upsert_customer_api(
+    State(app_state): State<Arc<AppState>>,
+    Json(mut payload): Json<CustomerPayload>,
+) -> Result<(StatusCode, Json<Customer>), AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    let mut result = app_state.customers.upsert_customer_by_email(payload.name.clone(), payload.email.clone()).await;
+    if matches!(&result, Err(e) if is_unique_violation(e)) {
+        // A concurrent upsert inserted the same email first; this time it will be found
+        result = app_state.customers.upsert_customer_by_email(payload.name, payload.email).await;
+    }
+    let (customer, created) = result?;
+    cache_customer(&app_state, &customer).await;
+    invalidate_customer_addresses(&app_state, customer.id).await;
+    let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(customer)))
+}
 
 #[utoipa::path(
     get,
     path = "/customers",
+    params(Page),
     responses(
-        (status = 200, description = "List of all customers", body = [Customer]),
+        (status = 200, description = "All customers, or one page of them in ID order", body = [Customer]),
+        (status = 400, description = "Invalid paging", body = ErrorBody),
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn list_customers_api(State(app_state): State<Arc<AppState>>) -> Result<Json<Vec<Customer>>, (StatusCode, String)> {
-    CustomerDAO::list_customers(&app_state.db_pool)
-        .await
-        .map(Json)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+pub async fn list_customers_api(
+    State(app_state): State<Arc<AppState>>,
+    Query(page): Query<Page>,
+) -> Result<Json<Vec<Customer>>, AppError> {
+    let customers = match page.bounds().map_err(AppError::BadRequest)? {
+        Some((limit, offset)) => app_state.customers.list_customers_page(limit, offset).await?,
+        None => app_state.customers.list_customers().await?,
+    };
+    Ok(Json(customers))
 }
 
 
@@ -58,38 +302,73 @@ pub async fn list_customers_api(State(app_state): State<Arc<AppState>>) -> Resul
     get,
     path = "/customers/{id}",
     params(
-        ("id" = String, description = "ID of the customer to retrieve", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+        ("id" = String, description = "ID of the customer to retrieve", example = "d290f1ee-6c54-4b01-90e6-d701748f0851"),
+        CustomerQueryParams
     ),
     responses(
-        (status = 200, description = "Customer details", body = Customer),
+        (status = 200, description = "Customer details", body = CustomerDetails),
+        (status = 400, description = "Unknown expand value"),
         (status = 404, description = "Customer not found")
     )
 )]
 pub async fn get_customer_api(
     State(app_state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Customer>, (StatusCode, String)> {
-    let cache_key = format!("customer:{}", id);
-    match app_state.cache.read(&cache_key).await {
-        Ok(cached_customer) => {
-            if let Ok(customer) = serde_json::from_slice(&cached_customer.to_vec()) {
-                info!("Cache hit with cahce_key = {}", cache_key);
-                return Ok(Json(customer));
-            } else {
-                error!("Cache read parse error");
+    Query(params): Query<CustomerQueryParams>,
+) -> Result<Json<CustomerDetails>, AppError> {
+    let expand_addresses = params.expand_addresses().map_err(AppError::BadRequest)?;
+    if !expand_addresses {
+        let cache_key = customer_cache_key(id);
+        let customer = match app_state.cache.get(&cache_key).await {
+            Some(customer) => customer,
+            None => {
+                let customer = app_state.customers.get_customer(id)
+                    .await
+                    .map_err(|e| AppError::not_found(e, "Customer not found"))?;
+                // A lagging replica could otherwise outlive its lag in the cache
+                if app_state.reads.target() == ReadTarget::Primary {
+                    app_state.cache.put(&cache_key, &customer).await;
+                }
+                customer
             }
-        }
-        Err(e) => info!("Cache miss with cache_key: {}, with {}", cache_key, e),
+        };
+        return Ok(Json(CustomerDetails { customer, addresses: None }));
     }
 
-    match CustomerDAO::get_customer(&app_state.db_pool, id).await {
-        Ok(customer) => {
-            if let Err(e) = app_state.cache.write(&cache_key, serde_json::to_string(&customer).unwrap()).await {
-                error!("Cache write error: {}", e);
-            }
-            Ok(Json(customer))
-        }
-        Err(_) => Err((StatusCode::NOT_FOUND, "Customer not found".to_string())),
+    // Cached as a tuple, which unlike the flattened `CustomerDetails` every cache format encodes
+    let cache_key = customer_addresses_cache_key(id);
+    if let Some((customer, addresses)) = app_state.cache.get::<(Customer, Vec<CustomerAddress>)>(&cache_key).await {
+        return Ok(Json(CustomerDetails { customer, addresses: Some(addresses) }));
+    }
+    let details = app_state.customers.get_customer_with_addresses(id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Customer not found"))?;
+    if app_state.reads.target() == ReadTarget::Primary {
+        let addresses = details.addresses.as_deref().unwrap_or_default();
+        app_state.cache.put(&cache_key, &(&details.customer, addresses)).await;
+    }
+    Ok(Json(details))
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/by-email/{email}",
+    params(
+        ("email" = String, description = "Email of the customer, matched case-insensitively", example = "jane@example.com")
+    ),
+    responses(
+        (status = 200, description = "Customer details", body = Customer),
+        (status = 404, description = "Customer not found")
+    )
+)]
+pub async fn get_customer_by_email_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(email): Path<String>,
+) -> Result<Json<Customer>, AppError> {
+    match app_state.customers.get_customer_by_email(email.trim()).await {
+        Ok(customer) => Ok(Json(customer)),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Customer not found".to_string())),
+        Err(e) => Err(AppError::from(e)),
     }
 }
 
@@ -102,25 +381,18 @@ pub async fn get_customer_api(
     ),
     responses(
         (status = 200, description = "Updated customer details", body = Customer),
-        (status = 404, description = "Customer not found")
+        (status = 400, description = "Invalid customer payload"),
+        (status = 404, description = "Customer not found"),
+        (status = 409, description = "Email already in use by another customer", body = ErrorBody)
     )
 )]
 pub async fn update_customer_api(
     State(app_state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<CustomerPayload>,
-) -> Result<Json<Customer>, (StatusCode, String)> {
-    match CustomerDAO::update_customer(&app_state.db_pool, id, payload.name, payload.email).await {
-        Ok(customer) => {
-            // Update cache
-            let cache_key = format!("customer:{}", id);
-            if let Err(e) = app_state.cache.write(&cache_key, serde_json::to_string(&customer).unwrap()).await {
-                error!("Cache write error: {}", e);
-            }
-            Ok(Json(customer))
-        }
-        Err(_) => Err((StatusCode::NOT_FOUND, "Customer not found".to_string())),
-    }
+    Json(mut payload): Json<CustomerPayload>,
+) -> Result<Json<Customer>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    replace_customer(&app_state, id, payload).await.map(Json)
 }
 
 #[utoipa::path(
@@ -131,58 +403,167 @@ pub async fn update_customer_api(
     ),
     responses(
         (status = 200, description = "Customer deleted successfully"),
-        (status = 404, description = "Customer not found")
+        (status = 404, description = "Customer not found"),
+        (status = 409, description = "Customer still has orders")
     )
 )]
 pub async fn delete_customer_api(
     State(app_state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> Result<&'static str, (StatusCode, String)> {
-    match CustomerDAO::delete_customer(&app_state.db_pool, id).await {
-        Ok(rows_affected) if rows_affected > 0 => {
-            // Invalidate cache after deletion
-            let cache_key = format!("customer:{}", id);
-            if let Err(e) = app_state.cache.delete(&cache_key).await {
-                error!("Cache delete error: {}", e);
-            }
-            Ok("Customer deleted")
-        }
-        _ => Err((StatusCode::NOT_FOUND, "Customer not found".to_string())),
+) -> Result<&'static str, AppError> {
+    remove_customer(&app_state, id).await.map(|_| "Customer deleted")
+}
+
+#[utoipa::path(
+    post,
+    path = "/customers:batch",
+    request_body = CustomerBatchRequest,
+    responses(
+        (status = 200, description = "Batch applied; in best_effort mode each result carries its own status", body = BatchResponse),
+        (status = 400, description = "Invalid operations, nothing was applied", body = BatchResponse),
+        (status = 404, description = "An updated or deleted customer does not exist, nothing was applied", body = BatchResponse),
+        (status = 409, description = "Email conflict or customer with orders, nothing was applied", body = BatchResponse),
+        (status = 413, description = "Too many operations in one batch", body = ErrorBody)
+    )
+)]
+pub async fn batch_customers_api(
+    State(app_state): State<Arc<AppState>>,
+    Json(mut request): Json<CustomerBatchRequest>,
+) -> Result<(StatusCode, Json<BatchResponse>), AppError> {
+    check_batch_size(request.operations.len())?;
+    let invalid = request.validate();
+
+    let (status, response) = run_batch(
+        request.mode,
+        request.operations,
+        invalid,
+        |operations| apply_customer_batch(&app_state, operations),
+        |operation| apply_customer_operation(&app_state, operation),
+    )
+    .await?;
+    Ok((status, Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/export",
+    params(ExportParams),
+    responses(
+        (status = 200, description = "Every customer, as CSV with an `id,name,email` header or as NDJSON", body = String, content_type = "text/csv")
+    )
+)]
+pub async fn export_customers_api(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ExportParams>,
+) -> Response {
+    transfer::export_customers(app_state.customers.clone(), params.format.unwrap_or(DataFormat::Csv))
+}
+
+#[utoipa::path(
+    post,
+    path = "/customers/import",
+    params(ImportParams),
+    request_body(content = String, description = "CSV with a `name,email` header, or NDJSON", content_type = "text/csv"),
+    responses(
+        (status = 200, description = "Valid lines imported, rejected lines listed by line number", body = ImportReport),
+        (status = 202, description = "Import queued as a background job", body = Job),
+        (status = 400, description = "Unknown upload format", body = ErrorBody),
+        (status = 413, description = "Upload too large, 8 MB for background imports"),
+        (status = 422, description = "Rejected lines in atomic mode, nothing was imported", body = ImportReport)
+    )
+)]
+pub async fn import_customers_api(
+    State(app_state): State<Arc<AppState>>,
+    principal: Principal,
+    Query(params): Query<ImportParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let format = transfer::import_format(params.format, &headers)?;
+    let mode = params.mode.unwrap_or_default();
+    if params.background.unwrap_or(false) {
+        return submit_import(&app_state, &principal, JobKind::ImportCustomers, format, mode, &body).await;
     }
+    transfer::import_customers(app_state.customers.as_ref(), format, mode, &body)
+        .await
+        .map(|report| (report.status(), Json(report)).into_response())
+        .map_err(AppError::from)
 }
 
 impl CustomerHandler {
     pub async fn create_customer(
         state: State<Arc<AppState>>,
         payload: Json<CustomerPayload>,
-    ) -> Result<Json<Customer>, (StatusCode, String)> {
+    ) -> Result<Json<Customer>, AppError> {
         create_customer_api(state, payload).await
     }
 
-    pub async fn list_customers(state: State<Arc<AppState>>) -> Result<Json<Vec<Customer>>, (StatusCode, String)> {
-        list_customers_api(state).await
+    pub async fn upsert_customer(
+        state: State<Arc<AppState>>,
+        payload: Json<CustomerPayload>,
+    ) -> Result<(StatusCode, Json<Customer>), AppError> {
+        upsert_customer_api(state, payload).await
+    }
+
+    pub async fn get_customer_by_email(
+        state: State<Arc<AppState>>,
+        email: Path<String>,
+    ) -> Result<Json<Customer>, AppError> {
+        get_customer_by_email_api(state, email).await
+    }
+
+    pub async fn list_customers(
+        state: State<Arc<AppState>>,
+        page: Query<Page>,
+    ) -> Result<Json<Vec<Customer>>, AppError> {
+        list_customers_api(state, page).await
     }
 
     pub async fn get_customer(
         state: State<Arc<AppState>>,
         id: Path<Uuid>,
-    ) -> Result<Json<Customer>, (StatusCode, String)> {
-        get_customer_api(state, id).await
+        params: Query<CustomerQueryParams>,
+    ) -> Result<Json<CustomerDetails>, AppError> {
+        get_customer_api(state, id, params).await
     }
 
     pub async fn update_customer(
         state: State<Arc<AppState>>,
         id: Path<Uuid>,
         payload: Json<CustomerPayload>,
-    ) -> Result<Json<Customer>, (StatusCode, String)> {
+    ) -> Result<Json<Customer>, AppError> {
         update_customer_api(state, id, payload).await
     }
 
     pub async fn delete_customer(
         state: State<Arc<AppState>>,
         id: Path<Uuid>,
-    ) -> Result<&'static str, (StatusCode, String)> {
+    ) -> Result<&'static str, AppError> {
         delete_customer_api(state, id).await
     }
 
+    pub async fn batch_customers(
+        state: State<Arc<AppState>>,
+        request: Json<CustomerBatchRequest>,
+    ) -> Result<(StatusCode, Json<BatchResponse>), AppError> {
+        batch_customers_api(state, request).await
+    }
+
+    pub async fn export_customers(
+        state: State<Arc<AppState>>,
+        params: Query<ExportParams>,
+    ) -> Response {
+        export_customers_api(state, params).await
+    }
+
+    pub async fn import_customers(
+        state: State<Arc<AppState>>,
+        principal: Principal,
+        params: Query<ImportParams>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<Response, AppError> {
+        import_customers_api(state, principal, params, headers, body).await
+    }
+
 }
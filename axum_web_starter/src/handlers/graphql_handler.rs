@@ -0,0 +1,26 @@
+use axum::extract::{Extension, OriginalUri, State};
+use axum::response::Html;
+use async_graphql::http::GraphiQLSource;
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use std::sync::Arc;
+use crate::graphql::{with_loaders, AppSchema};
+use crate::state::AppState;
+
+pub struct GraphQLHandler;
+
+impl GraphQLHandler {
+    pub async fn execute(
+        State(app_state): State<Arc<AppState>>,
+        Extension(schema): Extension<AppSchema>,
+        request: GraphQLRequest,
+    ) -> GraphQLResponse {
+        let request = with_loaders(request.into_inner(), &app_state);
+        schema.execute(request).await.into()
+    }
+
+    /// GraphiQL playground, only routed in development. It posts queries back to the path it
+    /// was served from, which keeps any prefix the app is nested under.
+    pub async fn graphiql(OriginalUri(uri): OriginalUri) -> Html<String> {
+        Html(GraphiQLSource::build().endpoint(uri.path()).finish())
+    }
+}
@@ -0,0 +1,42 @@
+use axum::extract::{Json, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::Arc;
+use crate::cache::{CacheHealth, CircuitState};
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct Health {
+    /// `degraded` while the cache is being skipped; the API keeps serving from the database
+    pub status: &'static str,
+    pub cache: CacheHealth,
+}
+
+pub async fn health(State(app_state): State<Arc<AppState>>) -> Json<Health> {
+    let cache = app_state.cache.health();
+    let status = if cache.state == CircuitState::Closed { "ok" } else { "degraded" };
+    Json(Health { status, cache })
+}
+
+/// The cache circuit breaker in the Prometheus text format
+pub async fn metrics(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    let cache = app_state.cache.health();
+    let mut body = String::new();
+    let metrics = [
+        ("cache_circuit_state", "gauge", "Cache circuit: 0 closed, 1 half open, 2 open", cache.state.gauge().into()),
+        ("cache_hits_total", "counter", "Cache reads that found a current entry", cache.hits),
+        ("cache_misses_total", "counter", "Cache reads that found nothing usable, skipped ones aside", cache.misses),
+        ("cache_circuit_trips_total", "counter", "Times the cache circuit opened", cache.trips),
+        ("cache_calls_skipped_total", "counter", "Cache calls skipped while the circuit was open", cache.skipped),
+        ("cache_failures_total", "counter", "Cache calls that failed", cache.failures),
+        ("cache_timeouts_total", "counter", "Cache calls that timed out", cache.timeouts),
+        ("cache_pending_deletes", "gauge", "Cache keys left stale by a failed call, waiting to be deleted", cache.pending_deletes),
+    ];
+    for (name, kind, help, value) in metrics {
+        // Writing to a String cannot fail
+        let _ = write!(body, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n");
+    }
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
@@ -0,0 +1,202 @@
+use axum::extract::{State, Json, Path, Query};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use std::sync::Arc;
+use crate::auth::{AdminAuth, Principal};
+use crate::daos::job_dao::JobDAO;
+use crate::models::batch::BatchMode;
+use crate::models::job::{ImportJobPayload, Job, JobKind, JobPayload, JobQueryParams, JobStatus};
+use crate::models::transfer::DataFormat;
+use crate::errors::{AppError, ErrorBody};
+use crate::state::AppState;
+use uuid::Uuid;
+use tracing::info;
+
+/// Used when a job does not set its own limits
+const DEFAULT_MAX_ATTEMPTS: i32 = 3;
+const DEFAULT_TIMEOUT_SECONDS: i32 = 300;
+
+pub struct JobHandler;
+
+/// Queue a job on behalf of another handler and answer 202 pointing at `GET /jobs/{id}`,
+/// which only `principal` and admins may read
+pub async fn submit_job(
+    app_state: &AppState,
+    principal: &Principal,
+    kind: JobKind,
+    payload: serde_json::Value,
+    max_attempts: Option<i32>,
+    timeout_seconds: Option<i32>,
+) -> Result<Response, AppError> {
+    let job = JobDAO::enqueue(
+        &app_state.db_pool,
+        &principal.0,
+        kind,
+        payload,
+        max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+        timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS),
+    )
+    .await?;
+    info!("Queued job {} ({:?})", job.id, job.kind);
+    let location = format!("/jobs/{}", job.id);
+    Ok((StatusCode::ACCEPTED, [(header::LOCATION, location)], Json(job)).into_response())
+}
+
+/// Queue an upload as an `import_customers` or `import_sellers` job
+pub async fn submit_import(
+    app_state: &AppState,
+    principal: &Principal,
+    kind: JobKind,
+    format: DataFormat,
+    mode: BatchMode,
+    data: &[u8],
+) -> Result<Response, AppError> {
+    if data.len() > ImportJobPayload::MAX_DATA_BYTES {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Background imports are limited to {} bytes",
+            ImportJobPayload::MAX_DATA_BYTES
+        )));
+    }
+    let payload = ImportJobPayload::new(format, mode, data).map_err(AppError::BadRequest)?;
+    let payload = serde_json::to_value(payload).unwrap();
+    submit_job(app_state, principal, kind, payload, None, None).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    request_body = JobPayload,
+    responses(
+        (status = 202, description = "Job queued, poll the Location header for its status", body = Job),
+        (status = 400, description = "Invalid job payload", body = ErrorBody),
+        (status = 401, description = "Cache job without admin credentials", body = ErrorBody),
+        (status = 403, description = "Cache job from someone who is not an admin", body = ErrorBody)
+    )
+)]
+pub async fn create_job_api(
+    State(app_state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminAuth>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(payload): Json<JobPayload>,
+) -> Result<Response, AppError> {
+    if payload.kind.requires_admin() {
+        admin.authorize(&headers, &principal)?;
+    }
+    payload.validate().map_err(AppError::BadRequest)?;
+    submit_job(&app_state, &principal, payload.kind, payload.payload, payload.max_attempts, payload.timeout_seconds).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    params(JobQueryParams),
+    responses(
+        (status = 200, description = "The 100 most recent jobs", body = [Job]),
+        (status = 401, description = "No admin credentials", body = ErrorBody),
+        (status = 403, description = "Credentials of someone who is not an admin", body = ErrorBody)
+    )
+)]
+pub async fn list_jobs_api(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<JobQueryParams>,
+) -> Result<Json<Vec<Job>>, AppError> {
+    JobDAO::list_jobs(&app_state.db_pool, params.status)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    params(
+        ("id" = String, description = "ID of the job", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Status, progress and result of the job", body = Job),
+        (status = 404, description = "Job not found, or submitted by someone else")
+    )
+)]
+pub async fn get_job_api(
+    State(app_state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminAuth>,
+    principal: Principal,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, AppError> {
+    let submitter = (!admin.is_admin(&headers, &principal)).then_some(principal.0.as_str());
+    JobDAO::get_job(&app_state.db_pool, id, submitter)
+        .await
+        .map(Json)
+        .map_err(|e| AppError::not_found(e, "Job not found"))
+}
+
+#[utoipa::path(
+    post,
+    path = "/jobs/{id}/retry",
+    params(
+        ("id" = String, description = "ID of the dead-lettered job", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Job queued again with a fresh set of attempts", body = Job),
+        (status = 401, description = "No admin credentials", body = ErrorBody),
+        (status = 403, description = "Credentials of someone who is not an admin", body = ErrorBody),
+        (status = 404, description = "Job not found"),
+        (status = 409, description = "Only dead jobs can be retried", body = ErrorBody)
+    )
+)]
+pub async fn retry_job_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, AppError> {
+    match JobDAO::retry_job(&app_state.db_pool, id).await {
+        Ok(job) => Ok(Json(job)),
+        Err(sqlx::Error::RowNotFound) => match JobDAO::get_job(&app_state.db_pool, id, None).await {
+            Ok(job) if job.status != JobStatus::Dead => Err(AppError::conflict(format!(
+                "Job is {:?} and cannot be retried",
+                job.status
+            ))),
+            Ok(_) => Err(AppError::NotFound("Job not found".to_string())),
+            Err(e) => Err(AppError::not_found(e, "Job not found")),
+        },
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+impl JobHandler {
+    pub async fn create_job(
+        state: State<Arc<AppState>>,
+        admin: Extension<AdminAuth>,
+        principal: Principal,
+        headers: HeaderMap,
+        payload: Json<JobPayload>,
+    ) -> Result<Response, AppError> {
+        create_job_api(state, admin, principal, headers, payload).await
+    }
+
+    pub async fn list_jobs(
+        state: State<Arc<AppState>>,
+        params: Query<JobQueryParams>,
+    ) -> Result<Json<Vec<Job>>, AppError> {
+        list_jobs_api(state, params).await
+    }
+
+    pub async fn get_job(
+        state: State<Arc<AppState>>,
+        admin: Extension<AdminAuth>,
+        principal: Principal,
+        headers: HeaderMap,
+        id: Path<Uuid>,
+    ) -> Result<Json<Job>, AppError> {
+        get_job_api(state, admin, principal, headers, id).await
+    }
+
+    pub async fn retry_job(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Job>, AppError> {
+        retry_job_api(state, id).await
+    }
+}
@@ -1,2 +1,10 @@
 pub mod customer_handler;
-pub mod seller_handler;
\ No newline at end of file
+pub mod seller_handler;
+pub mod order_handler;
+pub mod product_handler;
+pub mod address_handler;
+pub mod job_handler;
+pub mod search_handler;
+pub mod graphql_handler;
+pub mod health_handler;
+pub mod admin_handler;
\ No newline at end of file
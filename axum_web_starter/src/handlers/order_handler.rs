@@ -0,0 +1,295 @@
+use axum::extract::{State, Json, Path};
+use std::sync::Arc;
+use crate::auth::Principal;
+use crate::daos::order_dao::{OrderDAO, OrderError};
+use crate::models::order::Order;
+use crate::models::order::OrderAction;
+use crate::models::order::OrderPayload;
+use crate::models::order::OrderStatusChange;
+use crate::models::order::OrderTransitionPayload;
+use crate::handlers::product_handler::product_cache_key;
+use crate::errors::AppError;
+use crate::state::AppState;
+use crate::unit_of_work::{self, UnitOfWork};
+use crate::utils::is_foreign_key_violation;
+use uuid::Uuid;
+use tracing::info;
+
+
+pub struct OrderHandler;
+
+fn order_error_response(e: OrderError) -> AppError {
+    match e {
+        OrderError::NotFound => AppError::NotFound("Order not found".to_string()),
+        OrderError::IllegalTransition { from, action } => {
+            AppError::conflict(format!("Cannot {} an order that is {}", action.as_str(), from.as_str()))
+        }
+        OrderError::UnknownProduct(id) => {
+            AppError::Unprocessable(format!("Product {} is not sold by this seller", id))
+        }
+        OrderError::InsufficientStock(id) => {
+            AppError::conflict(format!("Not enough stock left for product {}", id))
+        }
+        OrderError::CurrencyMismatch(id) => {
+            AppError::Unprocessable(format!("Product {} is priced in a different currency", id))
+        }
+        OrderError::Invalid(message) => AppError::BadRequest(message),
+        OrderError::Database(e) if is_foreign_key_violation(&e) => {
+            AppError::Unprocessable("Customer or seller does not exist".to_string())
+        }
+        OrderError::Database(e) => AppError::from(e),
+    }
+}
+
+/// Reserved stock of the order's products changed, so their cached copies are stale
+fn evict_products(uow: &mut UnitOfWork, order: &Order) {
+    for id in order.items.iter().filter_map(|item| item.product_id) {
+        uow.cache_delete(product_cache_key(id));
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders",
+    request_body = OrderPayload,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response when the request is retried with the same key")
+    ),
+    responses(
+        (status = 200, description = "Order created successfully", body = Order),
+        (status = 400, description = "Invalid order payload"),
+        (status = 409, description = "Not enough stock for a product, or a retry while the first request is in progress"),
+        (status = 422, description = "Customer, seller or product does not exist, or Idempotency-Key reused for a different request"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_order_api(
+    State(app_state): State<Arc<AppState>>,
+    Json(payload): Json<OrderPayload>,
+) -> Result<Json<Order>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    unit_of_work::run(&app_state, move |uow| {
+        let (customer_id, seller_id) = (payload.customer_id, payload.seller_id);
+        let (currency, items) = (payload.currency.clone(), payload.items.clone());
+        Box::pin(async move {
+            let order = OrderDAO::create_order(uow.connection().await?, customer_id, seller_id, currency, items).await?;
+            evict_products(uow, &order);
+            Ok::<_, OrderError>(order)
+        })
+    })
+    .await
+    .map(Json)
+    .map_err(order_error_response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders",
+    responses(
+        (status = 200, description = "List of all orders", body = [Order]),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_orders_api(State(app_state): State<Arc<AppState>>) -> Result<Json<Vec<Order>>, AppError> {
+    OrderDAO::list_orders(app_state.reads.pool())
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/{id}",
+    params(
+        ("id" = String, description = "ID of the order to retrieve", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Order details", body = Order),
+        (status = 404, description = "Order not found")
+    )
+)]
+pub async fn get_order_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Order>, AppError> {
+    OrderDAO::get_order(app_state.reads.pool(), id)
+        .await
+        .map(Json)
+        .map_err(|e| AppError::not_found(e, "Order not found"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/{id}/orders",
+    params(
+        ("id" = String, description = "ID of the customer whose orders to list", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Orders placed by the customer", body = [Order]),
+        (status = 404, description = "Customer not found")
+    )
+)]
+pub async fn list_customer_orders_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Order>>, AppError> {
+    app_state.customers.get_customer(id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Customer not found"))?;
+    OrderDAO::list_orders_by_customer(app_state.reads.pool(), id)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/sellers/{id}/orders",
+    params(
+        ("id" = String, description = "ID of the seller whose orders to list", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Orders received by the seller", body = [Order]),
+        (status = 404, description = "Seller not found")
+    )
+)]
+pub async fn list_seller_orders_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Order>>, AppError> {
+    app_state.sellers.get_seller(id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Seller not found"))?;
+    OrderDAO::list_orders_by_seller(app_state.reads.pool(), id)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+/// The authenticated principal, so the history cannot be written in someone else's name; only
+/// anonymous requests fall back to the `actor` they send
+fn transition_actor(principal: Principal, actor: Option<String>) -> Result<String, AppError> {
+    if principal != Principal::anonymous() {
+        return Ok(principal.0);
+    }
+    match actor.as_deref().map(str::trim) {
+        Some(actor) if !actor.is_empty() => Ok(actor.to_string()),
+        _ => Err(AppError::BadRequest("Actor must not be empty".to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders/{id}/{action}",
+    request_body = OrderTransitionPayload,
+    params(
+        ("id" = String, description = "ID of the order to transition", example = "d290f1ee-6c54-4b01-90e6-d701748f0851"),
+        ("action" = OrderAction, description = "Lifecycle action to apply")
+    ),
+    responses(
+        (status = 200, description = "Order after the transition", body = Order),
+        (status = 400, description = "Missing actor on an anonymous request"),
+        (status = 404, description = "Order not found"),
+        (status = 409, description = "Transition not allowed from the current status")
+    )
+)]
+pub async fn transition_order_api(
+    State(app_state): State<Arc<AppState>>,
+    Path((id, action)): Path<(Uuid, OrderAction)>,
+    principal: Principal,
+    Json(payload): Json<OrderTransitionPayload>,
+) -> Result<Json<Order>, AppError> {
+    let actor = transition_actor(principal, payload.actor)?;
+    let result = unit_of_work::run(&app_state, move |uow| {
+        let (actor, reason) = (actor.clone(), payload.reason.clone());
+        Box::pin(async move {
+            let order = OrderDAO::transition_order(uow.connection().await?, id, action, actor, reason).await?;
+            if order.status.releases_stock() {
+                evict_products(uow, &order);
+            }
+            Ok::<_, OrderError>(order)
+        })
+    })
+    .await;
+    match result {
+        Ok(order) => {
+            info!("Order {} is now {}", order.id, order.status.as_str());
+            Ok(Json(order))
+        }
+        Err(e) => Err(order_error_response(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/{id}/history",
+    params(
+        ("id" = String, description = "ID of the order", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Status transitions of the order, oldest first", body = [OrderStatusChange]),
+        (status = 404, description = "Order not found")
+    )
+)]
+pub async fn list_order_history_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<OrderStatusChange>>, AppError> {
+    OrderDAO::get_order(app_state.reads.pool(), id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Order not found"))?;
+    OrderDAO::list_status_history(app_state.reads.pool(), id)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+impl OrderHandler {
+    pub async fn create_order(
+        state: State<Arc<AppState>>,
+        payload: Json<OrderPayload>,
+    ) -> Result<Json<Order>, AppError> {
+        create_order_api(state, payload).await
+    }
+
+    pub async fn list_orders(state: State<Arc<AppState>>) -> Result<Json<Vec<Order>>, AppError> {
+        list_orders_api(state).await
+    }
+
+    pub async fn get_order(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Order>, AppError> {
+        get_order_api(state, id).await
+    }
+
+    pub async fn list_customer_orders(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Vec<Order>>, AppError> {
+        list_customer_orders_api(state, id).await
+    }
+
+    pub async fn list_seller_orders(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Vec<Order>>, AppError> {
+        list_seller_orders_api(state, id).await
+    }
+
+    pub async fn transition_order(
+        state: State<Arc<AppState>>,
+        path: Path<(Uuid, OrderAction)>,
+        principal: Principal,
+        payload: Json<OrderTransitionPayload>,
+    ) -> Result<Json<Order>, AppError> {
+        transition_order_api(state, path, principal, payload).await
+    }
+
+    pub async fn list_order_history(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Vec<OrderStatusChange>>, AppError> {
+        list_order_history_api(state, id).await
+    }
+}
@@ -0,0 +1,268 @@
+use axum::extract::{State, Json, Path, Query};
+use std::sync::Arc;
+use crate::daos::product_dao::ProductDAO;
+use crate::models::product::Product;
+use crate::models::product::ProductPayload;
+use crate::models::product::ProductSearchParams;
+use crate::errors::AppError;
+use crate::read_replica::ReadTarget;
+use crate::state::AppState;
+use crate::utils::is_unique_violation;
+use uuid::Uuid;
+use tracing::error;
+
+/// Page size for `GET /products` when no `limit` is given
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+pub struct ProductHandler;
+
+pub fn product_cache_key(id: Uuid) -> String {
+    format!("product:{}", id)
+}
+
+/// Drop cached products whose stock or details changed outside the product handlers
+pub async fn invalidate_products(app_state: &AppState, ids: impl IntoIterator<Item = Uuid>) {
+    for id in ids {
+        if let Err(e) = app_state.cache.delete(&product_cache_key(id)).await {
+            error!("Cache delete error: {}", e);
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/sellers/{id}/products",
+    request_body = ProductPayload,
+    params(
+        ("id" = String, description = "ID of the seller listing the product", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Product created successfully", body = Product),
+        (status = 400, description = "Invalid product payload"),
+        (status = 404, description = "Seller not found"),
+        (status = 409, description = "The seller already lists a product with this SKU")
+    )
+)]
+pub async fn create_product_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(seller_id): Path<Uuid>,
+    Json(payload): Json<ProductPayload>,
+) -> Result<Json<Product>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    app_state.sellers.get_seller(seller_id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Seller not found"))?;
+
+    match ProductDAO::create_product(
+        &app_state.db_pool,
+        seller_id,
+        payload.sku,
+        payload.title,
+        payload.price,
+        payload.currency,
+        payload.stock_quantity,
+    )
+    .await
+    {
+        Ok(product) => {
+            app_state.cache.put(&product_cache_key(product.id), &product).await;
+            Ok(Json(product))
+        }
+        Err(e) if is_unique_violation(&e) => {
+            Err(AppError::conflict("Seller already lists a product with this SKU"))
+        }
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/sellers/{id}/products",
+    params(
+        ("id" = String, description = "ID of the seller", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Products listed by the seller", body = [Product]),
+        (status = 404, description = "Seller not found")
+    )
+)]
+pub async fn list_seller_products_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(seller_id): Path<Uuid>,
+) -> Result<Json<Vec<Product>>, AppError> {
+    app_state.sellers.get_seller(seller_id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Seller not found"))?;
+    ProductDAO::list_products_by_seller(app_state.reads.pool(), seller_id)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/products",
+    params(ProductSearchParams),
+    responses(
+        (status = 200, description = "Matching products across all sellers", body = [Product]),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn search_products_api(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ProductSearchParams>,
+) -> Result<Json<Vec<Product>>, AppError> {
+    ProductDAO::search_products(
+        app_state.reads.pool(),
+        params.q.filter(|q| !q.trim().is_empty()),
+        params.seller_id,
+        params.in_stock.unwrap_or(false),
+        params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+    )
+    .await
+    .map(Json)
+    .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/products/{id}",
+    params(
+        ("id" = String, description = "ID of the product to retrieve", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Product details", body = Product),
+        (status = 404, description = "Product not found")
+    )
+)]
+pub async fn get_product_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Product>, AppError> {
+    let cache_key = product_cache_key(id);
+    if let Some(product) = app_state.cache.get(&cache_key).await {
+        return Ok(Json(product));
+    }
+
+    let product = ProductDAO::get_product(app_state.reads.pool(), id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Product not found"))?;
+    // A lagging replica could otherwise outlive its lag in the cache
+    if app_state.reads.target() == ReadTarget::Primary {
+        app_state.cache.put(&cache_key, &product).await;
+    }
+    Ok(Json(product))
+}
+
+#[utoipa::path(
+    put,
+    path = "/products/{id}",
+    request_body = ProductPayload,
+    params(
+        ("id" = String, description = "ID of the product to update", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Updated product details", body = Product),
+        (status = 400, description = "Invalid product payload"),
+        (status = 404, description = "Product not found"),
+        (status = 409, description = "The seller already lists a product with this SKU")
+    )
+)]
+pub async fn update_product_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ProductPayload>,
+) -> Result<Json<Product>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    match ProductDAO::update_product(
+        &app_state.db_pool,
+        id,
+        payload.sku,
+        payload.title,
+        payload.price,
+        payload.currency,
+        payload.stock_quantity,
+    )
+    .await
+    {
+        Ok(product) => {
+            app_state.cache.put(&product_cache_key(id), &product).await;
+            Ok(Json(product))
+        }
+        Err(e) if is_unique_violation(&e) => {
+            Err(AppError::conflict("Seller already lists a product with this SKU"))
+        }
+        Err(e) => Err(AppError::not_found(e, "Product not found")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/products/{id}",
+    params(
+        ("id" = String, description = "ID of the product to delete", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Product deleted successfully"),
+        (status = 404, description = "Product not found")
+    )
+)]
+pub async fn delete_product_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<&'static str, AppError> {
+    match ProductDAO::delete_product(&app_state.db_pool, id).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            invalidate_products(&app_state, [id]).await;
+            Ok("Product deleted")
+        }
+        Ok(_) => Err(AppError::NotFound("Product not found".to_string())),
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+impl ProductHandler {
+    pub async fn create_product(
+        state: State<Arc<AppState>>,
+        seller_id: Path<Uuid>,
+        payload: Json<ProductPayload>,
+    ) -> Result<Json<Product>, AppError> {
+        create_product_api(state, seller_id, payload).await
+    }
+
+    pub async fn list_seller_products(
+        state: State<Arc<AppState>>,
+        seller_id: Path<Uuid>,
+    ) -> Result<Json<Vec<Product>>, AppError> {
+        list_seller_products_api(state, seller_id).await
+    }
+
+    pub async fn search_products(
+        state: State<Arc<AppState>>,
+        params: Query<ProductSearchParams>,
+    ) -> Result<Json<Vec<Product>>, AppError> {
+        search_products_api(state, params).await
+    }
+
+    pub async fn get_product(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Product>, AppError> {
+        get_product_api(state, id).await
+    }
+
+    pub async fn update_product(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+        payload: Json<ProductPayload>,
+    ) -> Result<Json<Product>, AppError> {
+        update_product_api(state, id, payload).await
+    }
+
+    pub async fn delete_product(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<&'static str, AppError> {
+        delete_product_api(state, id).await
+    }
+}
@@ -0,0 +1,76 @@
+use axum::extract::{State, Json, Query};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::daos::search_dao::{SearchDAO, SearchHit};
+use crate::models::customer::Customer;
+use crate::models::seller::Seller;
+use crate::models::search::{highlight, SearchParams, SearchRecord, SearchResponse, SearchResult};
+use crate::errors::{AppError, ErrorBody};
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+const MAX_QUERY_LENGTH: usize = 200;
+/// Every term is matched separately, so long queries are cut off to bound the cost
+const MAX_TERMS: usize = 8;
+
+pub struct SearchHandler;
+
+/// Turn a row into the typed record it points at, with highlights for the fields a term occurs in
+fn to_result(hit: SearchHit, terms: &[String]) -> SearchResult {
+    let secondary_field = if hit.kind == "customer" { "email" } else { "company_name" };
+    let highlights: BTreeMap<String, String> = [("name", &hit.name), (secondary_field, &hit.secondary)]
+        .into_iter()
+        .filter_map(|(field, value)| Some((field.to_string(), highlight(value, terms)?)))
+        .collect();
+    let record = if hit.kind == "customer" {
+        SearchRecord::Customer(Customer { id: hit.id, name: hit.name, email: hit.secondary })
+    } else {
+        SearchRecord::Seller(Seller { id: hit.id, name: hit.name, company_name: hit.secondary })
+    };
+    SearchResult { record, score: hit.score, highlights }
+}
+
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "Customers and sellers ranked by relevance", body = SearchResponse),
+        (status = 400, description = "Empty or too long query, or invalid paging", body = ErrorBody)
+    )
+)]
+pub async fn search_api(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<SearchResponse>, AppError> {
+    let query = params.q.trim().to_string();
+    if query.is_empty() || query.len() > MAX_QUERY_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "q must be between 1 and {} characters",
+            MAX_QUERY_LENGTH
+        )));
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+    let offset = params.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(AppError::BadRequest("offset must not be negative".to_string()));
+    }
+
+    let terms: Vec<String> = query.split_whitespace().take(MAX_TERMS).map(str::to_string).collect();
+    let (hits, total) = SearchDAO::search(app_state.reads.pool(), &query, &terms, limit, offset).await?;
+    let results = hits.into_iter().map(|hit| to_result(hit, &terms)).collect();
+    Ok(Json(SearchResponse { query, total, limit, offset, results }))
+}
+
+impl SearchHandler {
+    pub async fn search(
+        state: State<Arc<AppState>>,
+        params: Query<SearchParams>,
+    ) -> Result<Json<SearchResponse>, AppError> {
+        search_api(state, params).await
+    }
+}
@@ -1,73 +1,368 @@
-use axum::extract::{State, Json, Path};
+use axum::body::Bytes;
+use axum::extract::{State, Json, Path, Query};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use std::sync::Arc;
-use axum::http::StatusCode;
-use crate::daos::seller_dao::SellerDAO;
-use crate::models::seller::Seller;
+use crate::handlers::product_handler::{invalidate_products, product_cache_key};
+use crate::models::seller::{Seller, SellerBatchOperation, SellerBatchRequest, SellerPayload};
+use crate::models::batch::{check_batch_size, run_batch, BatchPlan, BatchResponse, Planned};
+use crate::models::transfer::{DataFormat, ExportParams, ImportParams};
+use crate::transfer;
+use crate::auth::Principal;
+use crate::handlers::job_handler::submit_import;
+use crate::models::job::JobKind;
+use crate::errors::{AppError, ErrorBody};
+use crate::models::page::Page;
+use crate::read_replica::ReadTarget;
 use crate::state::AppState;
+use crate::unit_of_work;
+use crate::utils::is_foreign_key_violation;
 use uuid::Uuid;
-use serde::Deserialize;
+use tracing::error;
 
 pub struct SellerHandler;
 
+pub fn seller_cache_key(id: Uuid) -> String {
+    format!("seller:{}", id)
+}
+
+pub async fn cache_seller(app_state: &AppState, seller: &Seller) {
+    app_state.cache.put(&seller_cache_key(seller.id), seller).await;
+}
+
+pub async fn evict_seller(app_state: &AppState, id: Uuid) {
+    if let Err(e) = app_state.cache.delete(&seller_cache_key(id)).await {
+        error!("Cache delete error: {}", e);
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/sellers",
+    request_body = SellerPayload,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response when the request is retried with the same key")
+    ),
+    responses(
+        (status = 200, description = "Seller created successfully", body = Seller),
+        (status = 400, description = "Invalid seller payload", body = ErrorBody),
+        (status = 409, description = "A retry while the first request is in progress", body = ErrorBody),
+        (status = 422, description = "Idempotency-Key reused for a different request", body = ErrorBody),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_seller_api(
+    State(app_state): State<Arc<AppState>>,
+    Json(mut payload): Json<SellerPayload>,
+) -> Result<Json<Seller>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    app_state.sellers.create_seller(payload.name, payload.company_name)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/sellers",
+    params(Page),
+    responses(
+        (status = 200, description = "All sellers, or one page of them in ID order", body = [Seller]),
+        (status = 400, description = "Invalid paging", body = ErrorBody),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_sellers_api(
+    State(app_state): State<Arc<AppState>>,
+    Query(page): Query<Page>,
+) -> Result<Json<Vec<Seller>>, AppError> {
+    let sellers = match page.bounds().map_err(AppError::BadRequest)? {
+        Some((limit, offset)) => app_state.sellers.list_sellers_page(limit, offset).await?,
+        None => app_state.sellers.list_sellers().await?,
+    };
+    Ok(Json(sellers))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sellers/{id}",
+    params(
+        ("id" = String, description = "ID of the seller to retrieve", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Seller details", body = Seller),
+        (status = 404, description = "Seller not found", body = ErrorBody)
+    )
+)]
+pub async fn get_seller_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Seller>, AppError> {
+    if let Some(seller) = app_state.cache.get(&seller_cache_key(id)).await {
+        return Ok(Json(seller));
+    }
+
+    let seller = app_state.sellers.get_seller(id)
+        .await
+        .map_err(|e| AppError::not_found(e, "Seller not found"))?;
+    // A lagging replica could otherwise outlive its lag in the cache
+    if app_state.reads.target() == ReadTarget::Primary {
+        cache_seller(&app_state, &seller).await;
+    }
+    Ok(Json(seller))
+}
+
+#[utoipa::path(
+    put,
+    path = "/sellers/{id}",
+    request_body = SellerPayload,
+    params(
+        ("id" = String, description = "ID of the seller to update", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Updated seller details", body = Seller),
+        (status = 400, description = "Invalid seller payload", body = ErrorBody),
+        (status = 404, description = "Seller not found", body = ErrorBody)
+    )
+)]
+pub async fn update_seller_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<SellerPayload>,
+) -> Result<Json<Seller>, AppError> {
+    payload.validate().map_err(AppError::BadRequest)?;
+    let seller = app_state.sellers.update_seller(id, payload.name, payload.company_name)
+        .await
+        .map_err(|e| AppError::not_found(e, "Seller not found"))?;
+    // A lagging replica could otherwise outlive its lag in the cache
+    if app_state.reads.target() == ReadTarget::Primary {
+        cache_seller(&app_state, &seller).await;
+    }
+    Ok(Json(seller))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/sellers/{id}",
+    params(
+        ("id" = String, description = "ID of the seller to delete", example = "d290f1ee-6c54-4b01-90e6-d701748f0851")
+    ),
+    responses(
+        (status = 200, description = "Seller deleted successfully"),
+        (status = 404, description = "Seller not found", body = ErrorBody),
+        (status = 409, description = "Seller still has orders", body = ErrorBody)
+    )
+)]
+pub async fn delete_seller_api(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<&'static str, AppError> {
+    remove_seller(&app_state, id).await.map(|_| "Seller deleted")
+}
+
 impl SellerHandler {
     pub async fn create_seller(
-        State(app_state): State<Arc<AppState>>,
-        Json(payload): Json<SellerPayload>,
-    ) -> Result<Json<Seller>, (StatusCode, String)> {
-        SellerDAO::create_seller(&app_state.db_pool, payload.name, payload.company_name)
-            .await
-            .map(Json)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        state: State<Arc<AppState>>,
+        payload: Json<SellerPayload>,
+    ) -> Result<Json<Seller>, AppError> {
+        create_seller_api(state, payload).await
     }
-    
+
     pub async fn list_sellers(
-        State(app_state): State<Arc<AppState>>
-    ) -> Result<Json<Vec<Seller>>, (StatusCode, String)>{
-        SellerDAO::list_sellers(&app_state.db_pool)
-            .await
-            .map(Json)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        state: State<Arc<AppState>>,
+        page: Query<Page>,
+    ) -> Result<Json<Vec<Seller>>, AppError> {
+        list_sellers_api(state, page).await
     }
-    
+
     pub async fn get_seller(
-        State(app_state): State<Arc<AppState>>,
-        Path(id): Path<Uuid>,
-    ) -> Result<Json<Seller>, (StatusCode, String)> {
-        SellerDAO::get_seller(&app_state.db_pool, id)
-            .await
-            .map(Json)
-            .map_err(|_| (StatusCode::NOT_FOUND, "Seller not found".to_string()))
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<Json<Seller>, AppError> {
+        get_seller_api(state, id).await
     }
-    
+
     pub async fn update_seller(
-        State(app_state): State<Arc<AppState>>,
-        Path(id): Path<Uuid>,
-        Json(payload): Json<SellerPayload>,
-    ) -> Result<Json<Seller>, (StatusCode, String)> {
-        SellerDAO::update_seller(&app_state.db_pool, id, payload.name, payload.company_name)
-            .await
-            .map(Json)
-            .map_err(|_| (StatusCode::NOT_FOUND, "Seller not found".to_string()))
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+        payload: Json<SellerPayload>,
+    ) -> Result<Json<Seller>, AppError> {
+        update_seller_api(state, id, payload).await
     }
-    
+
     pub async fn delete_seller(
+        state: State<Arc<AppState>>,
+        id: Path<Uuid>,
+    ) -> Result<&'static str, AppError> {
+        delete_seller_api(state, id).await
+    }
+
+    pub async fn batch_sellers(
+        State(app_state): State<Arc<AppState>>,
+        Json(mut request): Json<SellerBatchRequest>,
+    ) -> Result<(StatusCode, Json<BatchResponse>), AppError> {
+        check_batch_size(request.operations.len())?;
+        let invalid = request.validate();
+
+        let (status, response) = run_batch(
+            request.mode,
+            request.operations,
+            invalid,
+            |operations| apply_seller_batch(&app_state, operations),
+            |operation| apply_seller_operation(&app_state, operation),
+        )
+        .await?;
+        Ok((status, Json(response)))
+    }
+
+    pub async fn export_sellers(
         State(app_state): State<Arc<AppState>>,
-        Path(id): Path<Uuid>,
-    ) -> Result<&'static str, (StatusCode, String)> {
-        let rows_affected = SellerDAO::delete_seller(&app_state.db_pool, id)
+        Query(params): Query<ExportParams>,
+    ) -> Response {
+        transfer::export_sellers(app_state.sellers.clone(), params.format.unwrap_or(DataFormat::Csv))
+    }
+
+    pub async fn import_sellers(
+        State(app_state): State<Arc<AppState>>,
+        principal: Principal,
+        Query(params): Query<ImportParams>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<Response, AppError> {
+        let format = transfer::import_format(params.format, &headers)?;
+        let mode = params.mode.unwrap_or_default();
+        if params.background.unwrap_or(false) {
+            return submit_import(&app_state, &principal, JobKind::ImportSellers, format, mode, &body).await;
+        }
+        transfer::import_sellers(app_state.sellers.as_ref(), format, mode, &body)
             .await
-            .map_err(|_| (StatusCode::NOT_FOUND, "Seller not found".to_string()))?;
-    
-        if rows_affected > 0 {
-            Ok("Seller deleted")
-        } else {
-            Err((StatusCode::NOT_FOUND, "Seller not found".to_string()))
+            .map(|report| (report.status(), Json(report)).into_response())
+            .map_err(AppError::from)
+    }
+}
+
+async fn remove_seller(app_state: &AppState, id: Uuid) -> Result<(), AppError> {
+    // The open order check and the delete commit together
+    let sellers = app_state.sellers.clone();
+    let result = unit_of_work::run(app_state, move |uow| {
+        let sellers = sellers.clone();
+        Box::pin(async move {
+            let open_orders = sellers.count_open_orders(uow, id).await?;
+            if open_orders > 0 {
+                return Ok(Err(open_orders));
+            }
+            // Products are removed along with the seller, so remember which ones to evict
+            let products = sellers.list_product_ids(&[id]).await?;
+            let rows_affected = sellers.delete_seller(uow, id).await?;
+            if rows_affected > 0 {
+                uow.cache_delete(seller_cache_key(id));
+                for product in products {
+                    uow.cache_delete(product_cache_key(product));
+                }
+            }
+            Ok::<_, sqlx::Error>(Ok(rows_affected))
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(rows_affected)) if rows_affected > 0 => Ok(()),
+        Ok(Ok(_)) => Err(AppError::NotFound("Seller not found".to_string())),
+        Ok(Err(open_orders)) => Err(AppError::conflict(format!(
+            "Seller has {} open orders and cannot be deleted",
+            open_orders
+        ))),
+        Err(e) if is_foreign_key_violation(&e) => Err(AppError::conflict(
+            "Seller is referenced by past orders and cannot be deleted",
+        )),
+        Err(e) => Err(AppError::from(e)),
+    }
+}
+
+async fn apply_seller_operation(
+    app_state: &AppState,
+    operation: SellerBatchOperation,
+) -> Result<(StatusCode, Uuid), AppError> {
+    match operation {
+        SellerBatchOperation::Create { payload } => {
+            app_state.sellers.create_seller(payload.name, payload.company_name)
+                .await
+                .map(|seller| (StatusCode::CREATED, seller.id))
+                .map_err(AppError::from)
         }
+        SellerBatchOperation::Update { id, payload } => {
+            let seller = app_state.sellers.update_seller(id, payload.name, payload.company_name)
+                .await
+                .map_err(|e| AppError::not_found(e, "Seller not found"))?;
+            cache_seller(app_state, &seller).await;
+            Ok((StatusCode::OK, seller.id))
+        }
+        SellerBatchOperation::Delete { id } => remove_seller(app_state, id).await.map(|_| (StatusCode::OK, id)),
     }
 }
 
-#[derive(Deserialize)]
-pub struct SellerPayload {
-    name: String,
-    company_name: String,
+/// Apply validated operations in one transaction, evicting the products of deleted sellers once it has committed
+async fn apply_seller_batch(
+    app_state: &AppState,
+    operations: Vec<SellerBatchOperation>,
+) -> Result<(StatusCode, BatchResponse), AppError> {
+    let plan = BatchPlan::new(
+        operations,
+        |operation| match operation {
+            SellerBatchOperation::Create { payload } => Planned::Create(Seller {
+                id: Uuid::new_v4(),
+                name: payload.name,
+                company_name: payload.company_name,
+            }),
+            SellerBatchOperation::Update { id, payload } => {
+                Planned::Update(Seller { id, name: payload.name, company_name: payload.company_name })
+            }
+            SellerBatchOperation::Delete { id } => Planned::Delete(id),
+        },
+        |seller| seller.id,
+    );
+    let (creates, updates, deletes) = (&plan.creates, &plan.updates, &plan.deletes);
+
+    let sellers = &app_state.sellers;
+    let mut products = Vec::new();
+    if !deletes.is_empty() {
+        let referenced = sellers.sellers_with_orders(deletes).await?;
+        if !referenced.is_empty() {
+            return Ok(plan.abort(|id| {
+                referenced
+                    .contains(&id)
+                    .then(|| AppError::conflict("Seller is referenced by orders and cannot be deleted"))
+            }));
+        }
+        products = sellers.list_product_ids(deletes).await?;
+    }
+
+    match sellers.apply_batch(creates, updates, deletes).await {
+        Ok(missing) if missing.is_empty() => {}
+        Ok(missing) => {
+            return Ok(plan.abort(|id| {
+                missing
+                    .contains(&id)
+                    .then(|| AppError::NotFound(format!("Seller {} not found", id)))
+            }));
+        }
+        Err(e) if is_foreign_key_violation(&e) => {
+            // An order was placed between the check above and the delete
+            return Ok(plan.abort(|id| {
+                deletes
+                    .contains(&id)
+                    .then(|| AppError::conflict("Seller is referenced by orders and cannot be deleted"))
+            }));
+        }
+        Err(e) => return Err(AppError::from(e)),
+    }
+
+    for seller in updates {
+        cache_seller(app_state, seller).await;
+    }
+    for id in deletes {
+        evict_seller(app_state, *id).await;
+    }
+    invalidate_products(app_state, products).await;
+    Ok(plan.applied())
 }
\ No newline at end of file
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+use axum::body::{to_bytes, Body};
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, LOCATION};
+use axum::http::request::Parts;
+use axum::http::{HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use tracing::{error, info};
+use crate::auth::Principal;
+use crate::config::AppConfig;
+use crate::daos::idempotency_dao::IdempotencyDAO;
+use crate::errors::AppError;
+use crate::models::idempotency::{IdempotencyClaim, IdempotencyRecord, IdempotencyStatus};
+use crate::state::AppState;
+use crate::transfer::MAX_IMPORT_BYTES;
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on responses replayed from the store instead of running the handler
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// How long a completed response is replayed
+const TTL_SECONDS: i32 = 24 * 60 * 60;
+/// How long a key stays locked by a request that never finishes, e.g. after a crash
+const IN_FLIGHT_SECONDS: i32 = 60;
+const MAX_KEY_LENGTH: usize = 255;
+/// Response headers stored alongside the body
+const REPLAYED_HEADERS: [HeaderName; 2] = [CONTENT_TYPE, LOCATION];
+
+/// State of the `idempotency` middleware. Bodies are buffered to fingerprint the request, up to
+/// the limit the route itself accepts.
+#[derive(Clone)]
+pub struct Idempotency {
+    app_state: Arc<AppState>,
+    max_body_bytes: usize,
+    /// Keys are stored in the database, so without one (`STORAGE_BACKEND=memory`) they are ignored
+    enabled: bool,
+}
+
+impl Idempotency {
+    pub fn new(app_state: Arc<AppState>, config: &AppConfig) -> Self {
+        Idempotency {
+            app_state,
+            max_body_bytes: config.max_body_bytes,
+            enabled: config.storage_backend == "postgres",
+        }
+    }
+
+    /// Imports raise their own limit to `MAX_IMPORT_BYTES`; every other route keeps `MAX_BODY_BYTES`
+    fn body_limit(&self, parts: &Parts) -> usize {
+        let is_import = parts
+            .extensions
+            .get::<MatchedPath>()
+            .is_some_and(|path| path.as_str().ends_with("/import"));
+        if is_import {
+            MAX_IMPORT_BYTES
+        } else {
+            self.max_body_bytes
+        }
+    }
+}
+
+/// Who the keys of a request belong to: its principal, or for anonymous requests the digest of
+/// the credentials they carry. Requests without either run as if they had no key, since any
+/// other client could replay their responses.
+fn owner(parts: &Parts) -> Option<String> {
+    let principal = Principal::from_parts(parts);
+    if principal != Principal::anonymous() {
+        return Some(principal.0);
+    }
+    let credentials = parts.headers.get(AUTHORIZATION)?;
+    let digest: String = Sha256::digest(credentials.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+    Some(format!("credentials:{}", digest))
+}
+
+/// Middleware giving every POST endpoint `Idempotency-Key` semantics: the first response for a
+/// caller and key is stored and replayed to retries of the same request. A retry racing the
+/// first request gets 409, and reusing a key for a different request gets 422.
+pub async fn idempotency(State(idempotency): State<Idempotency>, request: Request, next: Next) -> Response {
+    if !idempotency.enabled || request.method() != Method::POST {
+        return next.run(request).await;
+    }
+    let key = match request.headers().get(&IDEMPOTENCY_KEY) {
+        None => return next.run(request).await,
+        Some(key) => match key.to_str() {
+            Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+            _ => {
+                return AppError::BadRequest(format!(
+                    "Idempotency-Key must be 1 to {} visible ASCII characters",
+                    MAX_KEY_LENGTH
+                ))
+                .into_response()
+            }
+        },
+    };
+    let (parts, body) = request.into_parts();
+    let Some(owner) = owner(&parts) else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+    match run_once(&idempotency, owner, key, parts, body, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn run_once(
+    idempotency: &Idempotency,
+    owner: String,
+    key: String,
+    parts: Parts,
+    body: Body,
+    next: Next,
+) -> Result<Response, AppError> {
+    let body = to_bytes(body, idempotency.body_limit(&parts))
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body is too large".to_string()))?;
+    let request_hash = fingerprint(&parts, &body);
+
+    let pool = &idempotency.app_state.db_pool;
+    match IdempotencyDAO::claim(pool, &owner, &key, &request_hash, IN_FLIGHT_SECONDS).await? {
+        IdempotencyClaim::Existing(record) if record.request_hash != request_hash => Err(AppError::Unprocessable(
+            "Idempotency-Key was already used for a different request".to_string(),
+        )),
+        IdempotencyClaim::Existing(record) if record.status == IdempotencyStatus::InFlight => Err(
+            AppError::conflict("A request with this Idempotency-Key is still being processed"),
+        ),
+        IdempotencyClaim::Existing(record) => {
+            info!("Replaying response for Idempotency-Key {}", key);
+            Ok(replay(record))
+        }
+        IdempotencyClaim::Claimed => {
+            let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+            Ok(store(pool, &owner, &key, response).await)
+        }
+    }
+}
+
+fn fingerprint(parts: &Parts, body: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(parts.method.as_str());
+    hasher.update(b"\n");
+    hasher.update(parts.uri.path_and_query().map_or("", |path| path.as_str()));
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().to_vec()
+}
+
+/// Keep the response for replays. Server errors release the key instead so the retry runs again.
+async fn store(pool: &PgPool, owner: &str, key: &str, response: Response) -> Response {
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Failed to buffer response for Idempotency-Key {}: {}", key, e);
+            if let Err(e) = IdempotencyDAO::release(pool, owner, key).await {
+                error!("Failed to release Idempotency-Key {}: {}", key, e);
+            }
+            return AppError::Internal(e.to_string()).into_response();
+        }
+    };
+
+    let result = if parts.status.is_server_error() {
+        IdempotencyDAO::release(pool, owner, key).await
+    } else {
+        let headers: Map<String, Value> = REPLAYED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                let value = parts.headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), Value::from(value)))
+            })
+            .collect();
+        IdempotencyDAO::complete(
+            pool,
+            owner,
+            key,
+            i32::from(parts.status.as_u16()),
+            Value::Object(headers),
+            &body,
+            TTL_SECONDS,
+        )
+        .await
+    };
+    if let Err(e) = result {
+        error!("Failed to store response for Idempotency-Key {}: {}", key, e);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn replay(record: IdempotencyRecord) -> Response {
+    let mut response = Response::new(Body::from(record.response_body.unwrap_or_default()));
+    *response.status_mut() = record
+        .response_status
+        .and_then(|status| StatusCode::from_u16(status as u16).ok())
+        .unwrap_or(StatusCode::OK);
+    if let Some(Value::Object(headers)) = record.response_headers {
+        for (name, value) in headers {
+            let name = HeaderName::try_from(name.as_str());
+            let value = value.as_str().map(HeaderValue::try_from);
+            if let (Ok(name), Some(Ok(value))) = (name, value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+    }
+    response.headers_mut().insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// Delete expired keys every hour for as long as the server runs
+pub fn spawn_purge(pool: PgPool) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(60 * 60));
+        loop {
+            ticker.tick().await;
+            match IdempotencyDAO::purge_expired(&pool).await {
+                Ok(purged) => info!("Purged {} expired idempotency keys", purged),
+                Err(e) => error!("Failed to purge idempotency keys: {}", e),
+            }
+        }
+    })
+}
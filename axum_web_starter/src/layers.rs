@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use axum::extract::DefaultBodyLimit;
+use axum::http::header::{
+    AUTHORIZATION, CONTENT_TYPE, LINK, LOCATION, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS,
+};
+use axum::http::{HeaderValue, Method};
+use axum::Router;
+use tokio::time::Duration;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::set_header::SetResponseHeaderLayer;
+use tower_http::timeout::TimeoutLayer;
+use crate::config::AppConfig;
+use crate::idempotency::{IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED};
+use crate::read_replica::READ_PRIMARY;
+use crate::versioning::{DEPRECATION, SUNSET};
+
+/// Wrap the whole application in the layers configured in `AppConfig`, outermost first:
+/// CORS, security headers, compression, request timeout and the default body limit
+pub fn apply(router: Router, config: &AppConfig) -> Result<Router> {
+    // Layers added later wrap the ones added before them
+    let mut router = router
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
+        .layer(TimeoutLayer::new(config.request_timeout))
+        .layer(compression(config.compression_min_bytes));
+
+    router = router
+        .layer(SetResponseHeaderLayer::if_not_present(
+            X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            REFERRER_POLICY,
+            HeaderValue::try_from(config.referrer_policy.as_str())
+                .with_context(|| format!("Invalid REFERRER_POLICY: {}", config.referrer_policy))?,
+        ));
+    if config.hsts_max_age > 0 {
+        let hsts = format!("max-age={}; includeSubDomains", config.hsts_max_age);
+        router = router.layer(SetResponseHeaderLayer::if_not_present(
+            STRICT_TRANSPORT_SECURITY,
+            HeaderValue::try_from(hsts)?,
+        ));
+    }
+
+    if let Some(cors) = cors(&config.cors_allowed_origins)? {
+        router = router.layer(cors);
+    }
+    Ok(router)
+}
+
+/// gzip, brotli or zstd as the client accepts, skipping gRPC, images and small responses
+fn compression(min_bytes: u16) -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(DefaultPredicate::new().and(SizeAbove::new(min_bytes)))
+}
+
+fn cors(origins: &[String]) -> Result<Option<CorsLayer>> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| HeaderValue::try_from(origin.as_str()).with_context(|| format!("Invalid CORS origin: {}", origin)))
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+            .allow_headers([CONTENT_TYPE, AUTHORIZATION, IDEMPOTENCY_KEY, READ_PRIMARY])
+            .expose_headers([LOCATION, LINK, DEPRECATION, SUNSET, IDEMPOTENT_REPLAYED])
+            .max_age(Duration::from_secs(60 * 60)),
+    ))
+}
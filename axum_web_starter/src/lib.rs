@@ -0,0 +1,56 @@
+pub mod models;
+pub mod errors;
+
+// Everything else serves the API. Clients build without the `server` feature for the models
+// and errors above alone.
+#[cfg(feature = "server")]
+pub mod daos;
+#[cfg(feature = "server")]
+pub mod routes;
+#[cfg(feature = "server")]
+pub mod handlers;
+#[cfg(feature = "server")]
+pub mod state;
+#[cfg(feature = "server")]
+pub mod api_doc;
+#[cfg(feature = "server")]
+mod utils;
+#[cfg(feature = "server")]
+pub mod transfer;
+#[cfg(feature = "server")]
+pub mod cli;
+#[cfg(feature = "server")]
+pub mod config;
+#[cfg(feature = "server")]
+pub mod worker;
+#[cfg(feature = "server")]
+pub mod auth;
+#[cfg(feature = "server")]
+pub mod idempotency;
+#[cfg(feature = "server")]
+pub mod graphql;
+#[cfg(feature = "server")]
+pub mod grpc;
+#[cfg(feature = "server")]
+pub mod versioning;
+#[cfg(feature = "server")]
+pub mod layers;
+#[cfg(feature = "server")]
+pub mod tls;
+#[cfg(feature = "server")]
+pub mod repositories;
+#[cfg(feature = "server")]
+pub mod unit_of_work;
+#[cfg(feature = "server")]
+pub mod read_replica;
+#[cfg(feature = "server")]
+pub mod database;
+#[cfg(feature = "server")]
+pub mod cache;
+#[cfg(feature = "server")]
+pub mod cache_codec;
+#[cfg(feature = "server")]
+mod server;
+
+#[cfg(feature = "server")]
+pub use server::{app, router};
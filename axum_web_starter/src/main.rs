@@ -1,20 +1,11 @@
-use axum::{
-    routing::get,
-    Router,
-};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use dotenv::dotenv;
-use utoipa::OpenApi;
 
-mod models;
-mod daos;
-mod routes;
-mod handlers;
-mod state;
-mod api_doc;
-
-use crate::state::AppState;
-use api_doc::ApiDoc;
+use axum_web_starter::config::AppConfig;
+use axum_web_starter::state::AppState;
+use axum_web_starter::tls::{self, TlsSettings};
+use axum_web_starter::{cli, grpc, idempotency, worker};
 use tracing_subscriber::FmtSubscriber;
 use tracing_subscriber::EnvFilter;
 
@@ -22,16 +13,25 @@ use tracing_subscriber::EnvFilter;
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    // Get the DATABASE_URL from the environment
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set in the .env file");
+    let config = AppConfig::from_env().unwrap();
 
-    let redis_url = std::env::var("REDIS_URL")
-        .expect("REDIS_URL must be set in the .env file");
-    // Shared state
-    let app_state = Arc::new(AppState::new(&database_url, &redis_url).await.unwrap());
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|command| command == "import") {
+        if let Err(e) = cli::import(&config, &args[1..]).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().is_some_and(|command| command == "search-indexes") {
+        if let Err(e) = cli::search_indexes(&config).await {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    //init the logging
+    //init the logging, before connecting so the retries are reported
     let subscriber = FmtSubscriber::builder()
         .with_env_filter(
             EnvFilter::try_from_default_env() // Tries to read RUST_LOG
@@ -40,21 +40,31 @@ async fn main() {
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
-    // Define routes
-    let app = Router::new()
-        .merge(routes::customer_route::customer_routes(app_state.clone()))
-        .merge(routes::seller_route::seller_routes(app_state.clone()))
-        .route("/", get(|| async { "Axum CRUD API Starter!" }))
-        .route("/openapi.json", get(openapi_json));
-
-    println!("Server running on http://localhost:3000");
-    // run our app with hyper, listening globally on port 3000
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
-}
+    // Shared state
+    let app_state = Arc::new(AppState::from_config(&config).await.unwrap());
+
+    // Background jobs share the state with the HTTP handlers
+    worker::spawn_workers(app_state.clone(), config.job_workers, config.job_poll_interval);
+    idempotency::spawn_purge(app_state.db_pool.clone());
+
+    // gRPC gets a server of its own when GRPC_PORT is set, otherwise the router serves it
+    if config.grpc_port != 0 {
+        grpc::spawn_server(grpc::grpc_routes(app_state.clone()), config.grpc_port);
+    }
+    let app = axum_web_starter::router(app_state, &config).unwrap();
 
-// Handler for the OpenAPI JSON route
-async fn openapi_json() -> impl axum::response::IntoResponse {
-    let api_doc = ApiDoc::openapi(); // Generate OpenAPI JSON using utoipa
-    axum::Json(api_doc) // Return as JSON
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    match TlsSettings::from_config(&config).unwrap() {
+        Some(settings) => {
+            println!("Server running on https://localhost:{}", config.port);
+            tls::serve(app, addr, settings).await.unwrap();
+        }
+        None => {
+            // run our app with hyper, listening globally on the configured port
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            // PORT=0 picks a free port, so report the one actually bound
+            println!("Server running on http://localhost:{}", listener.local_addr().unwrap().port());
+            axum::serve(listener, app).await.unwrap();
+        }
+    }
 }
@@ -0,0 +1,91 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+/// ISO 3166-1 alpha-2 country codes
+const COUNTRY_CODES: &[&str] = &[
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type, ToSchema, Enum)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AddressKind {
+    Shipping,
+    Billing,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema, SimpleObject)]
+pub struct CustomerAddress {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Uuid,
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub customer_id: Uuid,
+    pub kind: AddressKind,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub city: String,
+    pub region: Option<String>,
+    pub postal_code: String,
+    #[schema(example = "US")]
+    pub country_code: String,
+    #[schema(example = "+14155550123")]
+    pub phone: Option<String>,
+    pub is_default: bool,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CustomerAddressPayload {
+    pub kind: AddressKind,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub city: String,
+    pub region: Option<String>,
+    pub postal_code: String,
+    #[schema(example = "US")]
+    pub country_code: String,
+    /// E.164 formatted phone number
+    #[schema(example = "+14155550123")]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+impl CustomerAddressPayload {
+    /// Checks the payload and normalizes the country code to upper case.
+    pub fn validate(&mut self) -> Result<(), String> {
+        if self.line1.trim().is_empty() || self.city.trim().is_empty() || self.postal_code.trim().is_empty() {
+            return Err("line1, city and postal_code must not be empty".to_string());
+        }
+        self.country_code = self.country_code.trim().to_ascii_uppercase();
+        if !COUNTRY_CODES.contains(&self.country_code.as_str()) {
+            return Err(format!("Unknown country code: {}", self.country_code));
+        }
+        if let Some(phone) = &self.phone {
+            let digits = phone.strip_prefix('+').unwrap_or_default();
+            if !(7..=15).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("Phone number must be in E.164 format: {}", phone));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use std::future::Future;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+use crate::errors::{AppError, ErrorBody};
+
+/// Upper bound on the number of operations accepted by a single batch request
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    /// Every operation is applied in one transaction, or none is
+    #[default]
+    Atomic,
+    /// Operations are applied one by one and fail independently
+    BestEffort,
+}
+
+/// Outcome of one operation of a batch, in request order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchItemResult {
+    pub index: usize,
+    /// HTTP status the operation would have produced as a standalone request
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorBody>,
+}
+
+impl BatchItemResult {
+    pub fn success(index: usize, status: StatusCode, id: Uuid) -> Self {
+        BatchItemResult { index, status: status.as_u16(), id: Some(id), error: None }
+    }
+
+    pub fn failure(index: usize, error: AppError) -> Self {
+        BatchItemResult { index, status: error.status().as_u16(), id: None, error: Some(error.into_body()) }
+    }
+
+    fn not_applied(index: usize) -> Self {
+        BatchItemResult {
+            index,
+            status: StatusCode::FAILED_DEPENDENCY.as_u16(),
+            id: None,
+            error: Some(ErrorBody {
+                error: "Not applied because another operation in the batch failed".to_string(),
+                existing_id: None,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchResponse {
+    pub mode: BatchMode,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+}
+
+impl BatchResponse {
+    pub fn new(mode: BatchMode, results: Vec<BatchItemResult>) -> Self {
+        let failed = results.iter().filter(|result| result.error.is_some()).count();
+        BatchResponse { mode, succeeded: results.len() - failed, failed, results }
+    }
+
+    /// Response for an atomic batch that was not applied. Operations without a failure of
+    /// their own are reported as 424, and the response takes the status of the first failure.
+    pub fn aborted(failures: Vec<Option<AppError>>) -> (StatusCode, Self) {
+        let status = failures
+            .iter()
+            .flatten()
+            .next()
+            .map(AppError::status)
+            .unwrap_or(StatusCode::CONFLICT);
+        let results = failures
+            .into_iter()
+            .enumerate()
+            .map(|(index, failure)| match failure {
+                Some(error) => BatchItemResult::failure(index, error),
+                None => BatchItemResult::not_applied(index),
+            })
+            .collect();
+        (status, BatchResponse::new(BatchMode::Atomic, results))
+    }
+}
+
+/// What one operation of an atomic batch writes
+pub enum Planned<R> {
+    Create(R),
+    Update(R),
+    Delete(Uuid),
+}
+
+/// The writes of an atomic batch grouped for the repository, and the status and ID each
+/// operation reports, in request order
+pub struct BatchPlan<R> {
+    pub creates: Vec<R>,
+    pub updates: Vec<R>,
+    pub deletes: Vec<Uuid>,
+    planned: Vec<(StatusCode, Uuid)>,
+}
+
+impl<R> BatchPlan<R> {
+    /// `plan` turns each operation into its write; created records get their ID up front, and
+    /// `id` reads it back from any record
+    pub fn new<O>(operations: Vec<O>, plan: impl Fn(O) -> Planned<R>, id: impl Fn(&R) -> Uuid) -> Self {
+        let mut batch = BatchPlan {
+            creates: Vec::new(),
+            updates: Vec::new(),
+            deletes: Vec::new(),
+            planned: Vec::with_capacity(operations.len()),
+        };
+        for operation in operations {
+            match plan(operation) {
+                Planned::Create(record) => {
+                    batch.planned.push((StatusCode::CREATED, id(&record)));
+                    batch.creates.push(record);
+                }
+                Planned::Update(record) => {
+                    batch.planned.push((StatusCode::OK, id(&record)));
+                    batch.updates.push(record);
+                }
+                Planned::Delete(deleted) => {
+                    batch.planned.push((StatusCode::OK, deleted));
+                    batch.deletes.push(deleted);
+                }
+            }
+        }
+        batch
+    }
+
+    /// Response for a batch that was not applied, with the operations `failed` returns an error
+    /// for as failures. Every ID appears once per batch, so failures are matched back by ID.
+    pub fn abort(&self, failed: impl Fn(Uuid) -> Option<AppError>) -> (StatusCode, BatchResponse) {
+        BatchResponse::aborted(self.planned.iter().map(|(_, id)| failed(*id)).collect())
+    }
+
+    /// Response for a batch that was committed
+    pub fn applied(&self) -> (StatusCode, BatchResponse) {
+        let results = self
+            .planned
+            .iter()
+            .enumerate()
+            .map(|(index, (status, id))| BatchItemResult::success(index, *status, *id))
+            .collect();
+        (StatusCode::OK, BatchResponse::new(BatchMode::Atomic, results))
+    }
+}
+
+/// Run a batch in its mode, given the validation error of each operation. An atomic batch is
+/// rejected whole if any operation is invalid, and otherwise handed to `apply_atomic`; a
+/// best-effort batch applies its valid operations one by one with `apply_one`.
+pub async fn run_batch<O, A, B>(
+    mode: BatchMode,
+    operations: Vec<O>,
+    invalid: Vec<Option<String>>,
+    apply_atomic: impl FnOnce(Vec<O>) -> A,
+    mut apply_one: impl FnMut(O) -> B,
+) -> Result<(StatusCode, BatchResponse), AppError>
+where
+    A: Future<Output = Result<(StatusCode, BatchResponse), AppError>>,
+    B: Future<Output = Result<(StatusCode, Uuid), AppError>>,
+{
+    match mode {
+        BatchMode::Atomic if invalid.iter().any(Option::is_some) => Ok(BatchResponse::aborted(
+            invalid.into_iter().map(|message| message.map(AppError::BadRequest)).collect(),
+        )),
+        BatchMode::Atomic => apply_atomic(operations).await,
+        BatchMode::BestEffort => {
+            let mut results = Vec::with_capacity(operations.len());
+            for (index, (operation, message)) in operations.into_iter().zip(invalid).enumerate() {
+                let result = match message {
+                    Some(message) => Err(AppError::BadRequest(message)),
+                    None => apply_one(operation).await,
+                };
+                results.push(match result {
+                    Ok((status, id)) => BatchItemResult::success(index, status, id),
+                    Err(e) => BatchItemResult::failure(index, e),
+                });
+            }
+            Ok((StatusCode::OK, BatchResponse::new(BatchMode::BestEffort, results)))
+        }
+    }
+}
+
+pub fn check_batch_size(len: usize) -> Result<(), AppError> {
+    if len == 0 {
+        return Err(AppError::BadRequest("A batch must contain at least one operation".to_string()));
+    }
+    if len > MAX_BATCH_SIZE {
+        return Err(AppError::PayloadTooLarge(format!(
+            "A batch may contain at most {} operations, got {}",
+            MAX_BATCH_SIZE, len
+        )));
+    }
+    Ok(())
+}
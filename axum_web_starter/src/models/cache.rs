@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use crate::cache::CacheHealth;
+
+/// What the cache holds, each under `<entity>:<id>` keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheEntity {
+    Customer,
+    Seller,
+    Product,
+}
+
+impl CacheEntity {
+    pub const ALL: [CacheEntity; 3] = [CacheEntity::Customer, CacheEntity::Seller, CacheEntity::Product];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CacheEntity::Customer => "customer",
+            CacheEntity::Seller => "seller",
+            CacheEntity::Product => "product",
+        }
+    }
+
+    /// Shared by every key of the entity, including derived ones such as `customer:{id}:addresses`
+    pub fn prefix(self) -> String {
+        format!("{}:", self.name())
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WarmCacheRequest {
+    /// `customer` or `seller`
+    pub entity: CacheEntity,
+    /// Only these rows; every row when left out
+    #[serde(default)]
+    pub ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CachePrefixParams {
+    /// e.g. `customer:` to drop every cached customer
+    pub prefix: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheDeletion {
+    pub deleted: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheStats {
+    /// Keys of each entity, or `null` when the backend cannot list its keys
+    #[schema(value_type = Option<BTreeMap<String, usize>>)]
+    pub keys: Option<BTreeMap<&'static str, usize>>,
+    #[serde(flatten)]
+    pub cache: CacheHealth,
+}
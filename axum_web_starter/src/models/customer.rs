@@ -1,17 +1,133 @@
+#[cfg(feature = "server")]
+use std::collections::HashSet;
+#[cfg(feature = "server")]
+use async_graphql::SimpleObject;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use utoipa::ToSchema;
+#[cfg(feature = "server")]
+use utoipa::{IntoParams, ToSchema};
+#[cfg(feature = "server")]
+use crate::models::address::CustomerAddress;
+#[cfg(feature = "server")]
+use crate::models::batch::BatchMode;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow, ToSchema, SimpleObject), graphql(complex))]
 pub struct Customer {
-    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    #[cfg_attr(feature = "server", schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851"))]
     pub id: Uuid,
     pub name: String,
     pub email: String,    
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct CustomerPayload {
     pub name: String,
     pub email: String,
 }
+
+impl CustomerPayload {
+    /// Checks the payload and trims surrounding whitespace from the email.
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.email = self.email.trim().to_string();
+        match self.email.split_once('@') {
+            Some((local, domain)) if !local.is_empty() && !domain.is_empty() => Ok(()),
+            _ => Err(format!("Invalid email address: {}", self.email)),
+        }
+    }
+}
+
+/// A customer with the related resources requested through `?expand=`
+#[cfg(feature = "server")]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CustomerDetails {
+    #[serde(flatten)]
+    pub customer: Customer,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub addresses: Option<Vec<CustomerAddress>>,
+}
+
+#[cfg(feature = "server")]
+#[derive(Deserialize, IntoParams)]
+pub struct CustomerQueryParams {
+    /// Comma separated related resources to inline, currently only `addresses`
+    pub expand: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl CustomerQueryParams {
+    pub fn expand_addresses(&self) -> Result<bool, String> {
+        let mut addresses = false;
+        for field in self.expand.iter().flat_map(|expand| expand.split(',')).map(str::trim) {
+            match field {
+                "" => {}
+                "addresses" => addresses = true,
+                other => return Err(format!("Unknown expand value: {}", other)),
+            }
+        }
+        Ok(addresses)
+    }
+}
+
+#[cfg(feature = "server")]
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum CustomerBatchOperation {
+    Create {
+        #[serde(flatten)]
+        payload: CustomerPayload,
+    },
+    Update {
+        #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+        id: Uuid,
+        #[serde(flatten)]
+        payload: CustomerPayload,
+    },
+    Delete {
+        #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+        id: Uuid,
+    },
+}
+
+#[cfg(feature = "server")]
+#[derive(Deserialize, ToSchema)]
+pub struct CustomerBatchRequest {
+    #[serde(default)]
+    pub mode: BatchMode,
+    pub operations: Vec<CustomerBatchOperation>,
+}
+
+#[cfg(feature = "server")]
+impl CustomerBatchRequest {
+    /// Validates every operation, returning the error of each one in request order.
+    /// A customer or an email may only be touched once per batch.
+    pub fn validate(&mut self) -> Vec<Option<String>> {
+        let mut ids = HashSet::new();
+        let mut emails = HashSet::new();
+        self.operations
+            .iter_mut()
+            .map(|operation| {
+                let (id, payload) = match operation {
+                    CustomerBatchOperation::Create { payload } => (None, Some(payload)),
+                    CustomerBatchOperation::Update { id, payload } => (Some(*id), Some(payload)),
+                    CustomerBatchOperation::Delete { id } => (Some(*id), None),
+                };
+                if let Some(id) = id {
+                    if !ids.insert(id) {
+                        return Some(format!("Customer {} appears more than once in the batch", id));
+                    }
+                }
+                if let Some(payload) = payload {
+                    if let Err(message) = payload.validate() {
+                        return Some(message);
+                    }
+                    if !emails.insert(payload.email.to_lowercase()) {
+                        return Some(format!("Email {} appears more than once in the batch", payload.email));
+                    }
+                }
+                None
+            })
+            .collect()
+    }
+}
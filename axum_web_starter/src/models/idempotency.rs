@@ -0,0 +1,26 @@
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum IdempotencyStatus {
+    InFlight,
+    Completed,
+}
+
+/// A request already seen with the same principal and Idempotency-Key
+#[derive(Debug, sqlx::FromRow)]
+pub struct IdempotencyRecord {
+    /// SHA-256 of the method, URI and body of the first request
+    pub request_hash: Vec<u8>,
+    pub status: IdempotencyStatus,
+    pub response_status: Option<i32>,
+    /// Replayed response headers as a JSON object of name to value
+    pub response_headers: Option<Value>,
+    pub response_body: Option<Vec<u8>>,
+}
+
+pub enum IdempotencyClaim {
+    /// The key is new; the caller runs the request and stores its response
+    Claimed,
+    Existing(IdempotencyRecord),
+}
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::models::batch::BatchMode;
+use crate::models::transfer::DataFormat;
+
+/// queued -> running -> succeeded, or back to queued after a failed attempt.
+/// Jobs that used up their attempts are dead-lettered and wait for a manual retry.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Dead,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Payload is an `ImportJobPayload`
+    ImportCustomers,
+    /// Payload is an `ImportJobPayload`
+    ImportSellers,
+    /// Load every customer into the cache. Payload is an optional `CacheJobPayload`.
+    WarmCustomerCache,
+    /// Evict every customer from the cache. Payload is an optional `CacheJobPayload`.
+    PurgeCustomerCache,
+    /// Load every seller into the cache. Payload is an optional `CacheJobPayload`.
+    WarmSellerCache,
+}
+
+impl JobKind {
+    /// Cache jobs act on whichever customers or sellers they name, so only admins queue them
+    pub fn requires_admin(self) -> bool {
+        matches!(self, JobKind::WarmCustomerCache | JobKind::PurgeCustomerCache | JobKind::WarmSellerCache)
+    }
+
+    /// Reject payloads the worker would not be able to run
+    pub fn validate_payload(self, payload: &Value) -> Result<(), String> {
+        match self {
+            JobKind::ImportCustomers | JobKind::ImportSellers => {
+                let payload = serde_json::from_value::<ImportJobPayload>(payload.clone())
+                    .map_err(|e| format!("Invalid import payload: {}", e))?;
+                if payload.data.len() > ImportJobPayload::MAX_DATA_BYTES {
+                    return Err(format!("Import data must not exceed {} bytes", ImportJobPayload::MAX_DATA_BYTES));
+                }
+                Ok(())
+            }
+            JobKind::WarmCustomerCache | JobKind::PurgeCustomerCache | JobKind::WarmSellerCache => {
+                CacheJobPayload::from_job(payload).map(|_| ())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema)]
+pub struct Job {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Only read by the workers, never sent back: imports carry the whole upload
+    #[serde(skip_serializing, default)]
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    /// Percentage reported by the worker, 0 to 100
+    pub progress: i32,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub timeout_seconds: i32,
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<Value>,
+    pub last_error: Option<String>,
+    /// When the job becomes runnable, pushed back after each failed attempt
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub run_at: DateTime<Utc>,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub created_at: DateTime<Utc>,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct JobPayload {
+    pub kind: JobKind,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    /// Defaults to 3
+    pub max_attempts: Option<i32>,
+    /// Defaults to 300
+    pub timeout_seconds: Option<i32>,
+}
+
+impl JobPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts.is_some_and(|attempts| !(1..=20).contains(&attempts)) {
+            return Err("max_attempts must be between 1 and 20".to_string());
+        }
+        if self.timeout_seconds.is_some_and(|timeout| !(1..=3600).contains(&timeout)) {
+            return Err("timeout_seconds must be between 1 and 3600".to_string());
+        }
+        self.kind.validate_payload(&self.payload)
+    }
+}
+
+/// Payload of `import_customers` and `import_sellers` jobs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportJobPayload {
+    pub format: DataFormat,
+    #[serde(default)]
+    pub mode: BatchMode,
+    /// The uploaded file
+    pub data: String,
+}
+
+impl ImportJobPayload {
+    /// The payload is stored in a single row, which CockroachDB bounds by its raft command size,
+    /// so background uploads get a smaller limit than imports run in the request
+    pub const MAX_DATA_BYTES: usize = 8 * 1024 * 1024;
+
+    /// Jobs store their payload as JSON, so the upload has to be UTF-8
+    pub fn new(format: DataFormat, mode: BatchMode, data: &[u8]) -> Result<Self, String> {
+        let data = String::from_utf8(data.to_vec()).map_err(|_| "Upload must be UTF-8 encoded".to_string())?;
+        Ok(ImportJobPayload { format, mode, data })
+    }
+}
+
+/// Payload of the cache jobs, which cover every row without one
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheJobPayload {
+    /// Only these rows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<Uuid>>,
+}
+
+impl CacheJobPayload {
+    pub fn from_job(payload: &Value) -> Result<Self, String> {
+        serde_json::from_value::<Option<Self>>(payload.clone())
+            .map(Option::unwrap_or_default)
+            .map_err(|e| format!("Invalid cache payload: {}", e))
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct JobQueryParams {
+    /// Only list jobs in this status, e.g. `dead` for the dead-letter queue
+    #[param(value_type = Option<String>)]
+    pub status: Option<JobStatus>,
+}
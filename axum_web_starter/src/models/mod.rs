@@ -1,2 +1,21 @@
 pub mod seller;
-pub mod customer;
\ No newline at end of file
+pub mod customer;
+pub mod page;
+#[cfg(feature = "server")]
+pub mod order;
+#[cfg(feature = "server")]
+pub mod product;
+#[cfg(feature = "server")]
+pub mod address;
+#[cfg(feature = "server")]
+pub mod batch;
+#[cfg(feature = "server")]
+pub mod transfer;
+#[cfg(feature = "server")]
+pub mod job;
+#[cfg(feature = "server")]
+pub mod idempotency;
+#[cfg(feature = "server")]
+pub mod search;
+#[cfg(feature = "server")]
+pub mod cache;
@@ -0,0 +1,187 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+/// Lifecycle status of an order, stored as lowercase text in the `orders` table.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type, ToSchema, Enum)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    Shipped,
+    Delivered,
+    Cancelled,
+    Refunded,
+}
+
+impl OrderStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Paid => "paid",
+            OrderStatus::Shipped => "shipped",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Refunded => "refunded",
+        }
+    }
+
+    /// Orders that still need work from the customer or seller.
+    pub fn is_open(self) -> bool {
+        matches!(self, OrderStatus::Pending | OrderStatus::Paid | OrderStatus::Shipped)
+    }
+
+    /// Orders whose reserved catalog stock goes back to the products: refunded goods are
+    /// returned, so they are sold again like those of cancelled orders.
+    pub fn releases_stock(self) -> bool {
+        matches!(self, OrderStatus::Cancelled | OrderStatus::Refunded)
+    }
+
+    /// The status reached by applying `action`, or `None` if the transition is not allowed.
+    ///
+    /// pending -> paid -> shipped -> delivered, with cancellation before shipping
+    /// and refunds once the order has been paid.
+    pub fn apply(self, action: OrderAction) -> Option<OrderStatus> {
+        use OrderAction::*;
+        use OrderStatus::*;
+        match (self, action) {
+            (Pending, Pay) => Some(Paid),
+            (Paid, Ship) => Some(Shipped),
+            (Shipped, Deliver) => Some(Delivered),
+            (Pending | Paid, Cancel) => Some(Cancelled),
+            (Paid | Shipped | Delivered, Refund) => Some(Refunded),
+            _ => None,
+        }
+    }
+}
+
+/// Transitions exposed as `POST /orders/{id}/{action}`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderAction {
+    Pay,
+    Ship,
+    Deliver,
+    Cancel,
+    Refund,
+}
+
+impl OrderAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderAction::Pay => "pay",
+            OrderAction::Ship => "ship",
+            OrderAction::Deliver => "deliver",
+            OrderAction::Cancel => "cancel",
+            OrderAction::Refund => "refund",
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct OrderTransitionPayload {
+    /// Who performed the transition, recorded in the status history. Required for anonymous
+    /// requests; authenticated callers are recorded as their principal instead.
+    #[schema(example = "support@example.com")]
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema)]
+pub struct OrderStatusChange {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Uuid,
+    pub from_status: OrderStatus,
+    pub to_status: OrderStatus,
+    pub actor: String,
+    pub reason: Option<String>,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Amounts are integer minor units (e.g. cents) of `currency`.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, SimpleObject)]
+#[graphql(complex)]
+pub struct Order {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Uuid,
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub customer_id: Uuid,
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub seller_id: Uuid,
+    pub status: OrderStatus,
+    #[schema(example = "USD")]
+    pub currency: String,
+    #[schema(example = 2598)]
+    pub total_amount: i64,
+    pub items: Vec<OrderItem>,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub created_at: DateTime<Utc>,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema, SimpleObject)]
+pub struct OrderItem {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Uuid,
+    #[schema(value_type = Option<String>, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub product_id: Option<Uuid>,
+    pub sku: String,
+    pub description: String,
+    pub quantity: i32,
+    pub unit_price: i64,
+    pub line_total: i64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct OrderPayload {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub customer_id: Uuid,
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub seller_id: Uuid,
+    #[schema(example = "USD")]
+    pub currency: String,
+    pub items: Vec<OrderItemPayload>,
+}
+
+/// Either a catalog product (`product_id`), whose SKU, title and price are taken
+/// from the seller's catalog and whose stock is reserved, or a free-form line.
+#[derive(Deserialize, ToSchema, Clone)]
+pub struct OrderItemPayload {
+    #[schema(value_type = Option<String>, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub product_id: Option<Uuid>,
+    #[serde(default)]
+    pub sku: String,
+    #[serde(default)]
+    pub description: String,
+    pub quantity: i32,
+    #[serde(default)]
+    pub unit_price: i64,
+}
+
+impl OrderPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(format!("Invalid currency code: {}", self.currency));
+        }
+        if self.items.is_empty() {
+            return Err("Order must contain at least one item".to_string());
+        }
+        for item in &self.items {
+            if item.product_id.is_none() && item.sku.trim().is_empty() {
+                return Err("Items without a product_id must have a SKU".to_string());
+            }
+            if item.quantity <= 0 {
+                return Err(format!("Quantity for {} must be positive", item.sku));
+            }
+            if item.unit_price < 0 {
+                return Err(format!("Unit price for {} must not be negative", item.sku));
+            }
+        }
+        Ok(())
+    }
+}
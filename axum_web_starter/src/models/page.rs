@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use utoipa::IntoParams;
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+/// `?limit=&offset=` paging of a list endpoint. Without either, the whole list is returned.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(IntoParams))]
+pub struct Page {
+    /// Page size, 20 by default and at most 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+}
+
+impl Page {
+    pub fn new(limit: i64, offset: i64) -> Self {
+        Page { limit: Some(limit), offset: Some(offset) }
+    }
+
+    /// The checked limit and offset, or `None` when the whole list was asked for
+    pub fn bounds(&self) -> Result<Option<(i64, i64)>, String> {
+        if self.limit.is_none() && self.offset.is_none() {
+            return Ok(None);
+        }
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(format!("limit must be between 1 and {}", MAX_LIMIT));
+        }
+        let offset = self.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err("offset must not be negative".to_string());
+        }
+        Ok(Some((limit, offset)))
+    }
+}
@@ -0,0 +1,64 @@
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+
+/// `price` is in integer minor units (e.g. cents) of `currency`.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema, SimpleObject)]
+pub struct Product {
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub id: Uuid,
+    #[schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851")]
+    pub seller_id: Uuid,
+    #[schema(example = "WIDGET-001")]
+    pub sku: String,
+    pub title: String,
+    #[schema(example = 1299)]
+    pub price: i64,
+    #[schema(example = "USD")]
+    pub currency: String,
+    pub stock_quantity: i32,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub created_at: DateTime<Utc>,
+    #[schema(value_type = String, example = "2025-03-01T12:00:00Z")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ProductPayload {
+    pub sku: String,
+    pub title: String,
+    pub price: i64,
+    pub currency: String,
+    pub stock_quantity: i32,
+}
+
+impl ProductPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sku.trim().is_empty() {
+            return Err("SKU must not be empty".to_string());
+        }
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(format!("Invalid currency code: {}", self.currency));
+        }
+        if self.price < 0 {
+            return Err("Price must not be negative".to_string());
+        }
+        if self.stock_quantity < 0 {
+            return Err("Stock quantity must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ProductSearchParams {
+    /// Case-insensitive match against title or SKU
+    pub q: Option<String>,
+    #[param(value_type = Option<String>)]
+    pub seller_id: Option<Uuid>,
+    /// Only return products with stock left
+    pub in_stock: Option<bool>,
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use crate::models::customer::Customer;
+use crate::models::seller::Seller;
+
+#[derive(Deserialize, IntoParams)]
+pub struct SearchParams {
+    /// Free text; every word has to appear in a searched field, or the whole text has to be
+    /// similar enough to one to tolerate typos
+    pub q: String,
+    /// Page size, 20 by default and at most 100
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// The record a search hit points at, tagged with its `type`
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(tag = "type", content = "record", rename_all = "lowercase")]
+pub enum SearchRecord {
+    Customer(Customer),
+    Seller(Seller),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub record: SearchRecord,
+    /// Trigram similarity between the query and the best matching field, 0 to 1
+    pub score: f32,
+    /// Matched fields with every occurrence of a query word wrapped in `<mark>` tags
+    pub highlights: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchResponse {
+    pub query: String,
+    /// Number of matches across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub results: Vec<SearchResult>,
+}
+
+/// Wrap every case-insensitive occurrence of a term in `<mark>` tags, escaping the rest for HTML.
+/// Returns `None` when no term occurs in the value.
+pub fn highlight(value: &str, terms: &[String]) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets identical to the original value
+    let lowered = value.to_ascii_lowercase();
+    let mut marked = vec![false; value.len()];
+    for term in terms {
+        let term = term.to_ascii_lowercase();
+        for (start, found) in lowered.match_indices(term.as_str()) {
+            marked[start..start + found.len()].iter_mut().for_each(|byte| *byte = true);
+        }
+    }
+    if !marked.contains(&true) {
+        return None;
+    }
+
+    let mut highlighted = String::with_capacity(value.len() + 16);
+    let mut open = false;
+    for (index, c) in value.char_indices() {
+        if marked[index] != open {
+            highlighted.push_str(if open { "</mark>" } else { "<mark>" });
+            open = marked[index];
+        }
+        match c {
+            '&' => highlighted.push_str("&amp;"),
+            '<' => highlighted.push_str("&lt;"),
+            '>' => highlighted.push_str("&gt;"),
+            '"' => highlighted.push_str("&quot;"),
+            c => highlighted.push(c),
+        }
+    }
+    if open {
+        highlighted.push_str("</mark>");
+    }
+    Some(highlighted)
+}
@@ -1,9 +1,91 @@
+#[cfg(feature = "server")]
+use std::collections::HashSet;
+#[cfg(feature = "server")]
+use async_graphql::SimpleObject;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+#[cfg(feature = "server")]
+use utoipa::ToSchema;
+#[cfg(feature = "server")]
+use crate::models::batch::BatchMode;
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "server", derive(ToSchema, SimpleObject), graphql(complex))]
 pub struct Seller {
+    #[cfg_attr(feature = "server", schema(value_type = String, example = "d290f1ee-6c54-4b01-90e6-d701748f0851"))]
     pub id: Uuid,
     pub name: String,
     pub company_name: String,
 }
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct SellerPayload {
+    pub name: String,
+    pub company_name: String,
+}
+
+impl SellerPayload {
+    /// Checks the payload and trims surrounding whitespace from both names.
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.name = self.name.trim().to_string();
+        self.company_name = self.company_name.trim().to_string();
+        if self.name.is_empty() {
+            return Err("Seller name must not be blank".to_string());
+        }
+        if self.company_name.is_empty() {
+            return Err("Seller company_name must not be blank".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum SellerBatchOperation {
+    Create {
+        #[serde(flatten)]
+        payload: SellerPayload,
+    },
+    Update {
+        id: Uuid,
+        #[serde(flatten)]
+        payload: SellerPayload,
+    },
+    Delete {
+        id: Uuid,
+    },
+}
+
+#[cfg(feature = "server")]
+#[derive(Deserialize)]
+pub struct SellerBatchRequest {
+    #[serde(default)]
+    pub mode: BatchMode,
+    pub operations: Vec<SellerBatchOperation>,
+}
+
+#[cfg(feature = "server")]
+impl SellerBatchRequest {
+    /// Validates every operation, returning the error of each one in request order
+    pub fn validate(&mut self) -> Vec<Option<String>> {
+        let mut ids = HashSet::new();
+        self.operations
+            .iter_mut()
+            .map(|operation| {
+                let (id, payload) = match operation {
+                    SellerBatchOperation::Create { payload } => (None, Some(payload)),
+                    SellerBatchOperation::Update { id, payload } => (Some(*id), Some(payload)),
+                    SellerBatchOperation::Delete { id } => (Some(*id), None),
+                };
+                if let Some(id) = id {
+                    if !ids.insert(id) {
+                        return Some(format!("Seller {} appears more than once in the batch", id));
+                    }
+                }
+                payload.and_then(|payload| payload.validate().err())
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,79 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use crate::models::batch::BatchMode;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Csv,
+    /// One JSON document per line
+    Ndjson,
+}
+
+impl DataFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            DataFormat::Csv => "text/csv; charset=utf-8",
+            DataFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next().unwrap_or_default().trim() {
+            "text/csv" => Some(DataFormat::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Some(DataFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(DataFormat::Csv),
+            "ndjson" | "jsonl" => Some(DataFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ExportParams {
+    /// `csv` or `ndjson`, defaults to `csv`
+    pub format: Option<DataFormat>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ImportParams {
+    /// `csv` or `ndjson`; taken from the Content-Type header when omitted
+    pub format: Option<DataFormat>,
+    /// `atomic` imports nothing if any line is invalid, `best_effort` imports the valid lines
+    pub mode: Option<BatchMode>,
+    /// Run the import as a background job and answer 202 with the job to poll
+    pub background: Option<bool>,
+}
+
+/// A line of an import that was rejected
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LineError {
+    /// 1-based line number in the uploaded file
+    pub line: u64,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ImportReport {
+    pub mode: BatchMode,
+    pub imported: usize,
+    pub errors: Vec<LineError>,
+}
+
+impl ImportReport {
+    /// 422 when an atomic import was rejected, 200 otherwise
+    pub fn status(&self) -> StatusCode {
+        if self.mode == BatchMode::Atomic && !self.errors.is_empty() {
+            StatusCode::UNPROCESSABLE_ENTITY
+        } else {
+            StatusCode::OK
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::HeaderName;
+use axum::middleware::Next;
+use axum::response::Response;
+use sqlx::PgPool;
+use tokio::time::{interval, timeout, Duration, Instant};
+use tracing::{info, warn};
+use crate::auth::Principal;
+
+/// Sending `X-Read-Primary: true` makes every read of the request go to the primary
+pub const READ_PRIMARY: HeaderName = HeaderName::from_static("x-read-primary");
+
+tokio::task_local! {
+    /// Set for requests that may read stale data. Reads outside of one, such as those of the
+    /// job workers or of gRPC calls, always go to the primary.
+    static REPLICA_ALLOWED: bool;
+}
+
+/// Where the reads of a request go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadTarget {
+    Primary,
+    Replica,
+}
+
+struct Replica {
+    pool: PgPool,
+    healthy: AtomicBool,
+}
+
+/// Picks the pool for `list_*` and `get_*` queries: the read replica when one is configured and
+/// healthy, the primary when there is none or the request has to read its own writes
+#[derive(Clone)]
+pub struct ReadRouter {
+    primary: PgPool,
+    replica: Option<Arc<Replica>>,
+    /// When each principal last wrote, to keep their reads on the primary for a while after
+    recent_writes: Arc<Mutex<HashMap<String, Instant>>>,
+    read_your_writes: Duration,
+}
+
+impl ReadRouter {
+    /// Every read goes to `primary`
+    pub fn primary_only(primary: PgPool) -> Self {
+        Self {
+            primary,
+            replica: None,
+            recent_writes: Arc::default(),
+            read_your_writes: Duration::ZERO,
+        }
+    }
+
+    /// Reads go to `replica` once a health check has succeeded. Principals who wrote in the
+    /// last `read_your_writes` keep reading from the primary.
+    pub fn with_replica(primary: PgPool, replica: PgPool, read_your_writes: Duration) -> Self {
+        Self {
+            primary,
+            replica: Some(Arc::new(Replica { pool: replica, healthy: AtomicBool::new(false) })),
+            recent_writes: Arc::default(),
+            read_your_writes,
+        }
+    }
+
+    /// Where the reads of the current request go
+    pub fn target(&self) -> ReadTarget {
+        let allowed = REPLICA_ALLOWED.try_with(|allowed| *allowed).unwrap_or(false);
+        match &self.replica {
+            Some(replica) if allowed && replica.healthy.load(Ordering::Relaxed) => ReadTarget::Replica,
+            _ => ReadTarget::Primary,
+        }
+    }
+
+    /// The pool to read from in the current request
+    pub fn pool(&self) -> &PgPool {
+        match (&self.replica, self.target()) {
+            (Some(replica), ReadTarget::Replica) => &replica.pool,
+            _ => &self.primary,
+        }
+    }
+
+    /// Anonymous callers share one principal, so they are never tracked and use `X-Read-Primary`
+    /// to read what they wrote instead
+    fn wrote_recently(&self, principal: &Principal) -> bool {
+        if *principal == Principal::anonymous() {
+            return false;
+        }
+        let recent_writes = self.recent_writes.lock().unwrap();
+        recent_writes.get(&principal.0).is_some_and(|at| at.elapsed() < self.read_your_writes)
+    }
+
+    fn record_write(&self, principal: Principal) {
+        if principal == Principal::anonymous() {
+            return;
+        }
+        let mut recent_writes = self.recent_writes.lock().unwrap();
+        recent_writes.retain(|_, at| at.elapsed() < self.read_your_writes);
+        recent_writes.insert(principal.0, Instant::now());
+    }
+
+    /// Check the replica every `every` and fail reads over to the primary while it does not
+    /// answer. Stops once the router is dropped.
+    pub fn spawn_health_check(&self, every: Duration) {
+        let Some(replica) = &self.replica else { return };
+        let replica: Weak<Replica> = Arc::downgrade(replica);
+        tokio::spawn(async move {
+            let mut ticks = interval(every);
+            loop {
+                ticks.tick().await;
+                let Some(replica) = replica.upgrade() else { return };
+                let ping = sqlx::query("SELECT 1").execute(&replica.pool);
+                let healthy = matches!(timeout(every, ping).await, Ok(Ok(_)));
+                if replica.healthy.swap(healthy, Ordering::Relaxed) != healthy {
+                    if healthy {
+                        info!("Read replica is healthy, serving reads from it");
+                    } else {
+                        warn!("Read replica is unhealthy, serving reads from the primary");
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Middleware sending the reads of a request to the primary when it writes, asks for it with
+/// `X-Read-Primary: true`, or comes from an authenticated principal that wrote within the
+/// read-your-writes window
+pub async fn route_reads(State(router): State<ReadRouter>, request: Request, next: Next) -> Response {
+    if router.replica.is_none() {
+        return next.run(request).await;
+    }
+    let principal = request.extensions().get::<Principal>().cloned().unwrap_or_else(Principal::anonymous);
+    let writes = !request.method().is_safe() && !is_graphql(&request);
+    let asked = request
+        .headers()
+        .get(&READ_PRIMARY)
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"true"));
+    let allowed = !(writes || asked || router.wrote_recently(&principal));
+
+    let response = REPLICA_ALLOWED.scope(allowed, next.run(request)).await;
+    if writes && response.status().is_success() {
+        router.record_write(principal);
+    }
+    response
+}
+
+/// The GraphQL schema has no mutations, so its POST requests only read
+fn is_graphql(request: &Request) -> bool {
+    request
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|path| path.as_str().ends_with("/graphql"))
+}
@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::RwLock;
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::daos::customer_dao::CustomerDAO;
+use crate::daos::order_dao::OrderDAO;
+use crate::models::customer::{Customer, CustomerDetails};
+use crate::read_replica::ReadRouter;
+use crate::unit_of_work::UnitOfWork;
+use crate::utils::unique_violation;
+
+/// Where the handlers read and write customers, so they can run against Postgres or memory
+#[async_trait]
+pub trait CustomerRepository: Send + Sync {
+    async fn create_customer(&self, name: String, email: String) -> Result<Customer, sqlx::Error>;
+
+    async fn list_customers(&self) -> Result<Vec<Customer>, sqlx::Error>;
+
+    /// `limit` customers ordered by ID, skipping the first `offset`
+    async fn list_customers_page(&self, limit: i64, offset: i64) -> Result<Vec<Customer>, sqlx::Error>;
+
+    /// Every customer ordered by ID, without loading them all at once
+    fn stream_customers(&self) -> BoxStream<'_, Result<Customer, sqlx::Error>>;
+
+    async fn count_customers(&self) -> Result<i64, sqlx::Error>;
+
+    async fn get_customer(&self, id: Uuid) -> Result<Customer, sqlx::Error>;
+
+    /// Case-insensitive lookup by email
+    async fn get_customer_by_email(&self, email: &str) -> Result<Customer, sqlx::Error>;
+
+    /// Create the customer with this email, or rename the existing one. Returns whether it was created.
+    async fn upsert_customer_by_email(&self, name: String, email: String) -> Result<(Customer, bool), sqlx::Error>;
+
+    async fn get_customer_with_addresses(&self, id: Uuid) -> Result<CustomerDetails, sqlx::Error>;
+
+    async fn list_customers_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Customer>, sqlx::Error>;
+
+    /// Case-insensitive lookup of the customers owning any of these emails
+    async fn list_customers_by_emails(&self, emails: &[String]) -> Result<Vec<Customer>, sqlx::Error>;
+
+    /// Apply every change or none. Returns the updated or deleted IDs that do not exist,
+    /// in which case nothing was changed.
+    async fn apply_batch(
+        &self,
+        creates: &[Customer],
+        updates: &[Customer],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error>;
+
+    async fn update_customer(&self, id: Uuid, name: String, email: String) -> Result<Customer, sqlx::Error>;
+
+    /// Delete inside `uow`, so it commits together with the checks made before it
+    async fn delete_customer(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<u64, sqlx::Error>;
+
+    /// Orders not yet delivered, cancelled or refunded; a customer with any cannot be deleted
+    async fn count_open_orders(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<i64, sqlx::Error>;
+
+    /// Those of the given customers that have placed any order
+    async fn customers_with_orders(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error>;
+}
+
+/// Customers stored in the database through `CustomerDAO`
+pub struct PgCustomerRepository {
+    pool: PgPool,
+    reads: ReadRouter,
+}
+
+impl PgCustomerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_reads(pool.clone(), ReadRouter::primary_only(pool))
+    }
+
+    /// Writes go to `pool`, `list_*` and `get_*` queries to the pool `reads` picks
+    pub fn with_reads(pool: PgPool, reads: ReadRouter) -> Self {
+        Self { pool, reads }
+    }
+}
+
+#[async_trait]
+impl CustomerRepository for PgCustomerRepository {
+    async fn create_customer(&self, name: String, email: String) -> Result<Customer, sqlx::Error> {
+        CustomerDAO::create_customer(&self.pool, name, email).await
+    }
+
+    async fn list_customers(&self) -> Result<Vec<Customer>, sqlx::Error> {
+        CustomerDAO::list_customers(self.reads.pool()).await
+    }
+
+    async fn list_customers_page(&self, limit: i64, offset: i64) -> Result<Vec<Customer>, sqlx::Error> {
+        CustomerDAO::list_customers_page(self.reads.pool(), limit, offset).await
+    }
+
+    fn stream_customers(&self) -> BoxStream<'_, Result<Customer, sqlx::Error>> {
+        CustomerDAO::stream_customers(self.reads.pool())
+    }
+
+    async fn count_customers(&self) -> Result<i64, sqlx::Error> {
+        CustomerDAO::count_customers(self.reads.pool()).await
+    }
+
+    async fn get_customer(&self, id: Uuid) -> Result<Customer, sqlx::Error> {
+        CustomerDAO::get_customer(self.reads.pool(), id).await
+    }
+
+    async fn get_customer_by_email(&self, email: &str) -> Result<Customer, sqlx::Error> {
+        CustomerDAO::get_customer_by_email(self.reads.pool(), email).await
+    }
+
+    async fn upsert_customer_by_email(&self, name: String, email: String) -> Result<(Customer, bool), sqlx::Error> {
+        CustomerDAO::upsert_customer_by_email(&self.pool, name, email).await
+    }
+
+    async fn get_customer_with_addresses(&self, id: Uuid) -> Result<CustomerDetails, sqlx::Error> {
+        CustomerDAO::get_customer_with_addresses(self.reads.pool(), id).await
+    }
+
+    async fn list_customers_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Customer>, sqlx::Error> {
+        CustomerDAO::list_customers_by_ids(self.reads.pool(), ids).await
+    }
+
+    async fn list_customers_by_emails(&self, emails: &[String]) -> Result<Vec<Customer>, sqlx::Error> {
+        CustomerDAO::list_customers_by_emails(self.reads.pool(), emails).await
+    }
+
+    async fn apply_batch(
+        &self,
+        creates: &[Customer],
+        updates: &[Customer],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        CustomerDAO::apply_batch(&self.pool, creates, updates, deletes).await
+    }
+
+    async fn update_customer(&self, id: Uuid, name: String, email: String) -> Result<Customer, sqlx::Error> {
+        CustomerDAO::update_customer(&self.pool, id, name, email).await
+    }
+
+    async fn delete_customer(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<u64, sqlx::Error> {
+        CustomerDAO::delete_customer(uow.connection().await?, id).await
+    }
+
+    async fn count_open_orders(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<i64, sqlx::Error> {
+        OrderDAO::count_open_orders_by_customer(uow.connection().await?, id).await
+    }
+
+    async fn customers_with_orders(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        OrderDAO::customers_with_orders(&self.pool, ids).await
+    }
+}
+
+/// Customers kept in a map, for running the handlers without a database.
+/// Emails are unique regardless of case, like the `customers_email_lower_idx` index;
+/// orders and addresses live elsewhere, so these customers never have any.
+#[derive(Default)]
+pub struct InMemoryCustomerRepository {
+    customers: RwLock<BTreeMap<Uuid, Customer>>,
+}
+
+impl InMemoryCustomerRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Fail like the unique index when another customer already has this email
+fn check_email(customers: &BTreeMap<Uuid, Customer>, id: Uuid, email: &str) -> Result<(), sqlx::Error> {
+    let email = email.to_lowercase();
+    if customers.values().any(|customer| customer.id != id && customer.email.to_lowercase() == email) {
+        return Err(unique_violation(format!(
+            "duplicate key value violates unique constraint \"customers_email_lower_idx\": {}",
+            email
+        )));
+    }
+    Ok(())
+}
+
+fn insert(customers: &mut BTreeMap<Uuid, Customer>, customer: Customer) -> Result<Customer, sqlx::Error> {
+    check_email(customers, customer.id, &customer.email)?;
+    customers.insert(customer.id, customer.clone());
+    Ok(customer)
+}
+
+#[async_trait]
+impl CustomerRepository for InMemoryCustomerRepository {
+    async fn create_customer(&self, name: String, email: String) -> Result<Customer, sqlx::Error> {
+        let mut customers = self.customers.write().unwrap();
+        insert(&mut customers, Customer { id: Uuid::new_v4(), name, email })
+    }
+
+    async fn list_customers(&self) -> Result<Vec<Customer>, sqlx::Error> {
+        Ok(self.customers.read().unwrap().values().cloned().collect())
+    }
+
+    async fn list_customers_page(&self, limit: i64, offset: i64) -> Result<Vec<Customer>, sqlx::Error> {
+        let customers = self.customers.read().unwrap();
+        Ok(customers.values().skip(offset as usize).take(limit as usize).cloned().collect())
+    }
+
+    fn stream_customers(&self) -> BoxStream<'_, Result<Customer, sqlx::Error>> {
+        let customers: Vec<Customer> = self.customers.read().unwrap().values().cloned().collect();
+        stream::iter(customers.into_iter().map(Ok)).boxed()
+    }
+
+    async fn count_customers(&self) -> Result<i64, sqlx::Error> {
+        Ok(self.customers.read().unwrap().len() as i64)
+    }
+
+    async fn get_customer(&self, id: Uuid) -> Result<Customer, sqlx::Error> {
+        self.customers.read().unwrap().get(&id).cloned().ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_customer_by_email(&self, email: &str) -> Result<Customer, sqlx::Error> {
+        let email = email.to_lowercase();
+        self.customers
+            .read()
+            .unwrap()
+            .values()
+            .find(|customer| customer.email.to_lowercase() == email)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn upsert_customer_by_email(&self, name: String, email: String) -> Result<(Customer, bool), sqlx::Error> {
+        let mut customers = self.customers.write().unwrap();
+        let lower = email.to_lowercase();
+        let existing = customers
+            .values()
+            .find(|customer| customer.email.to_lowercase() == lower)
+            .map(|customer| customer.id);
+        match existing {
+            Some(id) => Ok((insert(&mut customers, Customer { id, name, email })?, false)),
+            None => Ok((insert(&mut customers, Customer { id: Uuid::new_v4(), name, email })?, true)),
+        }
+    }
+
+    async fn get_customer_with_addresses(&self, id: Uuid) -> Result<CustomerDetails, sqlx::Error> {
+        let customer = self.get_customer(id).await?;
+        Ok(CustomerDetails { customer, addresses: Some(Vec::new()) })
+    }
+
+    async fn list_customers_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Customer>, sqlx::Error> {
+        let customers = self.customers.read().unwrap();
+        Ok(ids.iter().filter_map(|id| customers.get(id).cloned()).collect())
+    }
+
+    async fn list_customers_by_emails(&self, emails: &[String]) -> Result<Vec<Customer>, sqlx::Error> {
+        let emails: HashSet<String> = emails.iter().map(|email| email.to_lowercase()).collect();
+        Ok(self
+            .customers
+            .read()
+            .unwrap()
+            .values()
+            .filter(|customer| emails.contains(&customer.email.to_lowercase()))
+            .cloned()
+            .collect())
+    }
+
+    async fn apply_batch(
+        &self,
+        creates: &[Customer],
+        updates: &[Customer],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut customers = self.customers.write().unwrap();
+        // Work on a copy so a failure leaves the stored customers untouched, like a rolled back transaction
+        let mut pending = customers.clone();
+        let mut missing = Vec::new();
+        for customer in creates {
+            insert(&mut pending, customer.clone())?;
+        }
+        for customer in updates {
+            if pending.contains_key(&customer.id) {
+                insert(&mut pending, customer.clone())?;
+            } else {
+                missing.push(customer.id);
+            }
+        }
+        for id in deletes {
+            if pending.remove(id).is_none() {
+                missing.push(*id);
+            }
+        }
+        if missing.is_empty() {
+            *customers = pending;
+        }
+        Ok(missing)
+    }
+
+    async fn update_customer(&self, id: Uuid, name: String, email: String) -> Result<Customer, sqlx::Error> {
+        let mut customers = self.customers.write().unwrap();
+        if !customers.contains_key(&id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        insert(&mut customers, Customer { id, name, email })
+    }
+
+    async fn delete_customer(&self, _uow: &mut UnitOfWork, id: Uuid) -> Result<u64, sqlx::Error> {
+        Ok(self.customers.write().unwrap().remove(&id).map_or(0, |_| 1))
+    }
+
+    async fn count_open_orders(&self, _uow: &mut UnitOfWork, _id: Uuid) -> Result<i64, sqlx::Error> {
+        Ok(0)
+    }
+
+    async fn customers_with_orders(&self, _ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        Ok(Vec::new())
+    }
+}
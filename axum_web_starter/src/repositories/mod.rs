@@ -0,0 +1,2 @@
+pub mod customer_repository;
+pub mod seller_repository;
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::daos::order_dao::OrderDAO;
+use crate::daos::product_dao::ProductDAO;
+use crate::daos::seller_dao::SellerDAO;
+use crate::read_replica::ReadRouter;
+use crate::models::seller::Seller;
+use crate::unit_of_work::UnitOfWork;
+
+/// Where the handlers read and write sellers, so they can run against Postgres or memory
+#[async_trait]
+pub trait SellerRepository: Send + Sync {
+    async fn create_seller(&self, name: String, company_name: String) -> Result<Seller, sqlx::Error>;
+
+    async fn list_sellers(&self) -> Result<Vec<Seller>, sqlx::Error>;
+
+    /// `limit` sellers ordered by ID, skipping the first `offset`
+    async fn list_sellers_page(&self, limit: i64, offset: i64) -> Result<Vec<Seller>, sqlx::Error>;
+
+    /// Every seller ordered by ID, without loading them all at once
+    fn stream_sellers(&self) -> BoxStream<'_, Result<Seller, sqlx::Error>>;
+
+    async fn count_sellers(&self) -> Result<i64, sqlx::Error>;
+
+    async fn get_seller(&self, id: Uuid) -> Result<Seller, sqlx::Error>;
+
+    async fn list_sellers_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Seller>, sqlx::Error>;
+
+    async fn update_seller(&self, id: Uuid, name: String, company_name: String) -> Result<Seller, sqlx::Error>;
+
+    /// Apply every change or none. Returns the updated or deleted IDs that do not exist,
+    /// in which case nothing was changed.
+    async fn apply_batch(
+        &self,
+        creates: &[Seller],
+        updates: &[Seller],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error>;
+
+    /// Delete inside `uow`, so it commits together with the checks made before it
+    async fn delete_seller(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<u64, sqlx::Error>;
+
+    /// Orders not yet delivered, cancelled or refunded; a seller with any cannot be deleted
+    async fn count_open_orders(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<i64, sqlx::Error>;
+
+    /// Those of the given sellers that have received any order
+    async fn sellers_with_orders(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error>;
+
+    /// IDs of the products that are deleted along with these sellers
+    async fn list_product_ids(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error>;
+}
+
+/// Sellers stored in the database through `SellerDAO`
+pub struct PgSellerRepository {
+    pool: PgPool,
+    reads: ReadRouter,
+}
+
+impl PgSellerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_reads(pool.clone(), ReadRouter::primary_only(pool))
+    }
+
+    /// Writes go to `pool`, `list_*` and `get_*` queries to the pool `reads` picks
+    pub fn with_reads(pool: PgPool, reads: ReadRouter) -> Self {
+        Self { pool, reads }
+    }
+}
+
+#[async_trait]
+impl SellerRepository for PgSellerRepository {
+    async fn create_seller(&self, name: String, company_name: String) -> Result<Seller, sqlx::Error> {
+        SellerDAO::create_seller(&self.pool, name, company_name).await
+    }
+
+    async fn list_sellers(&self) -> Result<Vec<Seller>, sqlx::Error> {
+        SellerDAO::list_sellers(self.reads.pool()).await
+    }
+
+    async fn list_sellers_page(&self, limit: i64, offset: i64) -> Result<Vec<Seller>, sqlx::Error> {
+        SellerDAO::list_sellers_page(self.reads.pool(), limit, offset).await
+    }
+
+    fn stream_sellers(&self) -> BoxStream<'_, Result<Seller, sqlx::Error>> {
+        SellerDAO::stream_sellers(self.reads.pool())
+    }
+
+    async fn count_sellers(&self) -> Result<i64, sqlx::Error> {
+        SellerDAO::count_sellers(self.reads.pool()).await
+    }
+
+    async fn get_seller(&self, id: Uuid) -> Result<Seller, sqlx::Error> {
+        SellerDAO::get_seller(self.reads.pool(), id).await
+    }
+
+    async fn list_sellers_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Seller>, sqlx::Error> {
+        SellerDAO::list_sellers_by_ids(self.reads.pool(), ids).await
+    }
+
+    async fn update_seller(&self, id: Uuid, name: String, company_name: String) -> Result<Seller, sqlx::Error> {
+        SellerDAO::update_seller(&self.pool, id, name, company_name).await
+    }
+
+    async fn apply_batch(
+        &self,
+        creates: &[Seller],
+        updates: &[Seller],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        SellerDAO::apply_batch(&self.pool, creates, updates, deletes).await
+    }
+
+    async fn delete_seller(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<u64, sqlx::Error> {
+        SellerDAO::delete_seller(uow.connection().await?, id).await
+    }
+
+    async fn count_open_orders(&self, uow: &mut UnitOfWork, id: Uuid) -> Result<i64, sqlx::Error> {
+        OrderDAO::count_open_orders_by_seller(uow.connection().await?, id).await
+    }
+
+    async fn sellers_with_orders(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        OrderDAO::sellers_with_orders(&self.pool, ids).await
+    }
+
+    async fn list_product_ids(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        ProductDAO::list_product_ids_by_sellers(&self.pool, ids).await
+    }
+}
+
+/// Sellers kept in a map, for running the handlers without a database.
+/// Orders and products live elsewhere, so these sellers never have any.
+#[derive(Default)]
+pub struct InMemorySellerRepository {
+    sellers: RwLock<BTreeMap<Uuid, Seller>>,
+}
+
+impl InMemorySellerRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SellerRepository for InMemorySellerRepository {
+    async fn create_seller(&self, name: String, company_name: String) -> Result<Seller, sqlx::Error> {
+        let seller = Seller { id: Uuid::new_v4(), name, company_name };
+        self.sellers.write().unwrap().insert(seller.id, seller.clone());
+        Ok(seller)
+    }
+
+    async fn list_sellers(&self) -> Result<Vec<Seller>, sqlx::Error> {
+        Ok(self.sellers.read().unwrap().values().cloned().collect())
+    }
+
+    async fn list_sellers_page(&self, limit: i64, offset: i64) -> Result<Vec<Seller>, sqlx::Error> {
+        let sellers = self.sellers.read().unwrap();
+        Ok(sellers.values().skip(offset as usize).take(limit as usize).cloned().collect())
+    }
+
+    fn stream_sellers(&self) -> BoxStream<'_, Result<Seller, sqlx::Error>> {
+        let sellers: Vec<Seller> = self.sellers.read().unwrap().values().cloned().collect();
+        stream::iter(sellers.into_iter().map(Ok)).boxed()
+    }
+
+    async fn count_sellers(&self) -> Result<i64, sqlx::Error> {
+        Ok(self.sellers.read().unwrap().len() as i64)
+    }
+
+    async fn get_seller(&self, id: Uuid) -> Result<Seller, sqlx::Error> {
+        self.sellers.read().unwrap().get(&id).cloned().ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn list_sellers_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Seller>, sqlx::Error> {
+        let sellers = self.sellers.read().unwrap();
+        Ok(ids.iter().filter_map(|id| sellers.get(id).cloned()).collect())
+    }
+
+    async fn update_seller(&self, id: Uuid, name: String, company_name: String) -> Result<Seller, sqlx::Error> {
+        let mut sellers = self.sellers.write().unwrap();
+        let seller = sellers.get_mut(&id).ok_or(sqlx::Error::RowNotFound)?;
+        seller.name = name;
+        seller.company_name = company_name;
+        Ok(seller.clone())
+    }
+
+    async fn apply_batch(
+        &self,
+        creates: &[Seller],
+        updates: &[Seller],
+        deletes: &[Uuid],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut sellers = self.sellers.write().unwrap();
+        let missing: Vec<Uuid> = updates
+            .iter()
+            .map(|seller| seller.id)
+            .chain(deletes.iter().copied())
+            .filter(|id| !sellers.contains_key(id))
+            .collect();
+        if !missing.is_empty() {
+            return Ok(missing);
+        }
+        for seller in creates.iter().chain(updates) {
+            sellers.insert(seller.id, seller.clone());
+        }
+        for id in deletes {
+            sellers.remove(id);
+        }
+        Ok(missing)
+    }
+
+    async fn delete_seller(&self, _uow: &mut UnitOfWork, id: Uuid) -> Result<u64, sqlx::Error> {
+        Ok(self.sellers.write().unwrap().remove(&id).map_or(0, |_| 1))
+    }
+
+    async fn count_open_orders(&self, _uow: &mut UnitOfWork, _id: Uuid) -> Result<i64, sqlx::Error> {
+        Ok(0)
+    }
+
+    async fn sellers_with_orders(&self, _ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn list_product_ids(&self, _ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        Ok(Vec::new())
+    }
+}
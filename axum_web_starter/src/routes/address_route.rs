@@ -0,0 +1,19 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+use crate::handlers::address_handler::AddressHandler;
+use crate::state::AppState;
+
+pub fn address_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/customers/{id}/addresses", 
+            get(AddressHandler::list_addresses)
+            .post(AddressHandler::create_address))
+        .route("/customers/{id}/addresses/{address_id}", 
+            get(AddressHandler::get_address)
+            .put(AddressHandler::update_address)
+            .delete(AddressHandler::delete_address))
+        .with_state(app_state)
+}
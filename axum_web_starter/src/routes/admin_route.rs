@@ -0,0 +1,24 @@
+use axum::{
+    middleware,
+    routing::{delete, get, post},
+    Router,
+};
+use std::sync::Arc;
+use crate::auth::{require_admin, AdminAuth};
+use crate::handlers::admin_handler;
+use crate::state::AppState;
+
+/// Served at the root only, outside the versioned API, and to admins only
+pub fn admin_routes(app_state: Arc<AppState>, admin: AdminAuth) -> Router {
+    Router::new()
+        .route("/admin/cache",
+            delete(admin_handler::delete_cache_prefix))
+        .route("/admin/cache/warm",
+            post(admin_handler::warm_cache))
+        .route("/admin/cache/stats",
+            get(admin_handler::cache_stats))
+        .route("/admin/cache/{entity}/{id}",
+            delete(admin_handler::evict_cache_entry))
+        .route_layer(middleware::from_fn_with_state(admin, require_admin))
+        .with_state(app_state)
+}
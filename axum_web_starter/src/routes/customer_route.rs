@@ -1,16 +1,28 @@
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
 use std::sync::Arc;
 use crate::handlers::customer_handler::CustomerHandler;
 use crate::state::AppState;
+use crate::transfer::MAX_IMPORT_BYTES;
 
 pub fn customer_routes(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/customers", 
             post(CustomerHandler::create_customer)
-            .get(CustomerHandler::list_customers))
+            .get(CustomerHandler::list_customers)
+            .put(CustomerHandler::upsert_customer))
+        .route("/customers:batch", 
+            post(CustomerHandler::batch_customers))
+        .route("/customers/export", 
+            get(CustomerHandler::export_customers))
+        .route("/customers/import", 
+            post(CustomerHandler::import_customers)
+            .layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)))
+        .route("/customers/by-email/{email}", 
+            get(CustomerHandler::get_customer_by_email))
         .route("/customers/{id}", 
             get(CustomerHandler::get_customer)
             .put(CustomerHandler::update_customer)
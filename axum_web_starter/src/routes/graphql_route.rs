@@ -0,0 +1,22 @@
+use axum::{
+    routing::{get, post},
+    Extension, Router,
+};
+use std::sync::Arc;
+use crate::config::AppConfig;
+use crate::graphql::build_schema;
+use crate::handlers::graphql_handler::GraphQLHandler;
+use crate::state::AppState;
+
+pub fn graphql_routes(app_state: Arc<AppState>, config: &AppConfig) -> Router {
+    let schema = build_schema(config.graphql_max_depth, config.graphql_max_complexity);
+    let graphql = if config.is_development() {
+        get(GraphQLHandler::graphiql).post(GraphQLHandler::execute)
+    } else {
+        post(GraphQLHandler::execute)
+    };
+    Router::new()
+        .route("/graphql", graphql)
+        .layer(Extension(schema))
+        .with_state(app_state)
+}
@@ -0,0 +1,17 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+use crate::handlers::health_handler;
+use crate::state::AppState;
+
+/// Served at the root only, outside the versioned API
+pub fn health_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/health",
+            get(health_handler::health))
+        .route("/metrics",
+            get(health_handler::metrics))
+        .with_state(app_state)
+}
@@ -0,0 +1,25 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Extension, Router,
+};
+use std::sync::Arc;
+use crate::auth::{require_admin, AdminAuth};
+use crate::handlers::job_handler::JobHandler;
+use crate::state::AppState;
+
+/// Anyone may queue a job, cache jobs aside, and poll the ones they submitted; listing and
+/// retrying jobs is for admins
+pub fn job_routes(app_state: Arc<AppState>, admin: AdminAuth) -> Router {
+    let admin_only = middleware::from_fn_with_state(admin.clone(), require_admin);
+    Router::new()
+        .route("/jobs", 
+            post(JobHandler::create_job)
+            .merge(get(JobHandler::list_jobs).route_layer(admin_only.clone())))
+        .route("/jobs/{id}", 
+            get(JobHandler::get_job))
+        .route("/jobs/{id}/retry", 
+            post(JobHandler::retry_job).route_layer(admin_only))
+        .layer(Extension(admin))
+        .with_state(app_state)
+}
@@ -1,2 +1,10 @@
 pub mod customer_route;
-pub mod seller_route;
\ No newline at end of file
+pub mod seller_route;
+pub mod order_route;
+pub mod product_route;
+pub mod address_route;
+pub mod job_route;
+pub mod search_route;
+pub mod graphql_route;
+pub mod health_route;
+pub mod admin_route;
\ No newline at end of file
@@ -0,0 +1,25 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+use crate::handlers::order_handler::OrderHandler;
+use crate::state::AppState;
+
+pub fn order_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/orders", 
+            post(OrderHandler::create_order)
+            .get(OrderHandler::list_orders))
+        .route("/orders/{id}", 
+            get(OrderHandler::get_order))
+        .route("/orders/{id}/history", 
+            get(OrderHandler::list_order_history))
+        .route("/orders/{id}/{action}", 
+            post(OrderHandler::transition_order))
+        .route("/customers/{id}/orders", 
+            get(OrderHandler::list_customer_orders))
+        .route("/sellers/{id}/orders", 
+            get(OrderHandler::list_seller_orders))
+        .with_state(app_state)
+}
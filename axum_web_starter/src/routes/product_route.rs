@@ -0,0 +1,21 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+use crate::handlers::product_handler::ProductHandler;
+use crate::state::AppState;
+
+pub fn product_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/sellers/{id}/products", 
+            get(ProductHandler::list_seller_products)
+            .post(ProductHandler::create_product))
+        .route("/products", 
+            get(ProductHandler::search_products))
+        .route("/products/{id}", 
+            get(ProductHandler::get_product)
+            .put(ProductHandler::update_product)
+            .delete(ProductHandler::delete_product))
+        .with_state(app_state)
+}
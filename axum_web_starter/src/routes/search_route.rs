@@ -0,0 +1,14 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+use crate::handlers::search_handler::SearchHandler;
+use crate::state::AppState;
+
+pub fn search_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/search", 
+            get(SearchHandler::search))
+        .with_state(app_state)
+}
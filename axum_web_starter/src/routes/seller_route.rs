@@ -1,16 +1,25 @@
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
 use crate::handlers::seller_handler::SellerHandler;
 use std::sync::Arc;
 use crate::state::AppState;
+use crate::transfer::MAX_IMPORT_BYTES;
 
 pub fn seller_routes(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sellers", 
             post(SellerHandler::create_seller)
             .get(SellerHandler::list_sellers))
+        .route("/sellers:batch", 
+            post(SellerHandler::batch_sellers))
+        .route("/sellers/export", 
+            get(SellerHandler::export_sellers))
+        .route("/sellers/import", 
+            post(SellerHandler::import_sellers)
+            .layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)))
         .route("/sellers/{id}", 
             get(SellerHandler::get_seller)
             .put(SellerHandler::update_seller)
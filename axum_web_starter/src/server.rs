@@ -0,0 +1,64 @@
+use axum::{
+    middleware,
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+use anyhow::Result;
+use crate::{grpc, idempotency, layers, read_replica, routes, versioning};
+use crate::auth::AdminAuth;
+use crate::config::AppConfig;
+use crate::idempotency::Idempotency;
+use crate::state::AppState;
+use crate::versioning::{ApiVersion, Deprecation};
+
+/// The whole application for `config`: the state, every route and the middleware around them.
+/// Background job workers are not started; see `worker::spawn_workers`.
+pub async fn app(config: &AppConfig) -> Result<Router> {
+    let app_state = Arc::new(AppState::from_config(config).await?);
+    router(app_state, config)
+}
+
+/// Every route over an existing state, ready to be served or nested into another router:
+/// the REST API under each version prefix and as deprecated root aliases, GraphQL, gRPC when it
+/// shares the HTTP port, and the layers configured in `config`
+pub fn router(app_state: Arc<AppState>, config: &AppConfig) -> Result<Router> {
+    // Define routes: every version under its prefix, and v1 at the root as deprecated aliases
+    let admin = AdminAuth::from_config(config);
+    let mut app = Router::new();
+    for version in ApiVersion::ALL {
+        let version_routes = versioning::api_routes(app_state.clone(), admin.clone())
+            .route("/openapi.json", get(move || openapi_json(version)));
+        app = app.nest(version.prefix(), version_routes);
+    }
+    let legacy_routes = versioning::api_routes(app_state.clone(), admin.clone());
+    let mut app = app
+        .merge(versioning::deprecate(legacy_routes, Deprecation::legacy(config)))
+        .merge(routes::graphql_route::graphql_routes(app_state.clone(), config))
+        .merge(routes::health_route::health_routes(app_state.clone()))
+        .merge(routes::admin_route::admin_routes(app_state.clone(), admin))
+        .layer(middleware::from_fn_with_state(
+            Idempotency::new(app_state.clone(), config),
+            idempotency::idempotency,
+        ))
+        .layer(middleware::from_fn_with_state(app_state.reads.clone(), read_replica::route_reads))
+        .route("/", get(|| async { "Axum CRUD API Starter!" }))
+        .route("/openapi.json", get(legacy_openapi_json));
+
+    // gRPC shares the HTTP port unless GRPC_PORT names its own, which `grpc::spawn_server` serves
+    if config.grpc_port == 0 {
+        app = app.merge(grpc::grpc_routes(app_state).into_axum_router());
+    }
+    // CORS, compression, security headers, timeout and body limit wrap every route
+    layers::apply(app, config)
+}
+
+// Handler for the OpenAPI JSON route of one version
+async fn openapi_json(version: ApiVersion) -> impl axum::response::IntoResponse {
+    axum::Json(versioning::openapi(version)) // Generate OpenAPI JSON using utoipa
+}
+
+// The unprefixed routes are documented as deprecated aliases of v1
+async fn legacy_openapi_json() -> impl axum::response::IntoResponse {
+    axum::Json(versioning::legacy_openapi())
+}
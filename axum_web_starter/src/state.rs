@@ -1,36 +1,101 @@
 use sqlx::PgPool;
 use std::sync::Arc;
-// use opendal::services::Moka;
+use opendal::services::Moka;
 use opendal::services::Redis;
 use tokio::time::Duration;
 use opendal::Operator;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use crate::cache::{Cache, CacheSettings};
+use crate::config::AppConfig;
+use crate::database;
+use crate::read_replica::ReadRouter;
+use crate::repositories::customer_repository::{CustomerRepository, InMemoryCustomerRepository, PgCustomerRepository};
+use crate::repositories::seller_repository::{InMemorySellerRepository, PgSellerRepository, SellerRepository};
 
 
 pub struct AppState {
     pub db_pool: PgPool,
-    pub cache: Arc<Operator>, //OpenDAL Operator
+    /// Where `list_*` and `get_*` queries go: the read replica when configured and healthy
+    pub reads: ReadRouter,
+    /// The OpenDAL operator behind a circuit breaker
+    pub cache: Arc<Cache>,
+    pub customers: Arc<dyn CustomerRepository>,
+    pub sellers: Arc<dyn SellerRepository>,
 }
 
 impl AppState {
-    pub async fn new(database_url: &str, redis_url: &str) -> Result<Self> {
-        let db_pool = PgPool::connect(database_url).await?;
-        
-        // Initialize Moka as OpenDAL backend
-        // let builder = Moka::default()
-        //  .max_capacity(10_000)
-        //  .time_to_live(Duration::from_secs(300));
-
-        // Initialize Redis as OpenDAL backend
-        let builder = Redis::default()
-            .endpoint(redis_url)
-            .default_ttl(Duration::from_secs(300));
-        
-        let op = Operator::new(builder)?.finish();
-        
-        Ok(Self { 
+    /// Build the state for the backends named in `STORAGE_BACKEND` and `CACHE_BACKEND`
+    pub async fn from_config(config: &AppConfig) -> Result<Self> {
+        let operator = match config.cache_backend.as_str() {
+            "redis" => redis_cache(&config.redis_url)?,
+            "memory" => memory_cache()?,
+            other => bail!("Unknown CACHE_BACKEND: {}", other),
+        };
+        let cache = Cache::new(operator, CacheSettings::from_config(config));
+        match config.storage_backend.as_str() {
+            "postgres" => {
+                let db_pool = database::connect(config, &config.database_url).await?;
+                let reads = match &config.database_read_url {
+                    // A replica that is down only sends reads to the primary until it is back
+                    Some(read_url) => ReadRouter::with_replica(
+                        db_pool.clone(),
+                        database::pool_options(config).connect_lazy(read_url)?,
+                        config.read_your_writes,
+                    ),
+                    None => ReadRouter::primary_only(db_pool.clone()),
+                };
+                reads.spawn_health_check(config.replica_health_check_interval);
+                Ok(Self::with_reads(db_pool, reads, cache))
+            }
+            "memory" => {
+                let db_pool = database::pool_options(config).connect_lazy(&config.database_url)?;
+                Ok(Self::in_memory(db_pool, cache))
+            }
+            other => bail!("Unknown STORAGE_BACKEND: {}", other),
+        }
+    }
+
+    /// Customers and sellers stored in the database behind the pool
+    pub fn new(db_pool: PgPool, cache: Cache) -> Self {
+        Self::with_reads(db_pool.clone(), ReadRouter::primary_only(db_pool), cache)
+    }
+
+    /// Writes go to `db_pool`, reads to the pool `reads` picks for the request
+    pub fn with_reads(db_pool: PgPool, reads: ReadRouter, cache: Cache) -> Self {
+        Self {
+            customers: Arc::new(PgCustomerRepository::with_reads(db_pool.clone(), reads.clone())),
+            sellers: Arc::new(PgSellerRepository::with_reads(db_pool.clone(), reads.clone())),
             db_pool,
-            cache: Arc::new(op),
-        })
+            reads,
+            cache: Arc::new(cache),
+        }
     }
+
+    /// Customers and sellers live in memory. With a lazy pool, nothing connects until a route
+    /// backed by another table is called, so the customer and seller API needs no database.
+    pub fn in_memory(db_pool: PgPool, cache: Cache) -> Self {
+        Self {
+            reads: ReadRouter::primary_only(db_pool.clone()),
+            db_pool,
+            cache: Arc::new(cache),
+            customers: Arc::new(InMemoryCustomerRepository::new()),
+            sellers: Arc::new(InMemorySellerRepository::new()),
+        }
+    }
+}
+
+fn redis_cache(redis_url: &str) -> Result<Operator> {
+    // Initialize Redis as OpenDAL backend
+    let builder = Redis::default()
+        .endpoint(redis_url)
+        .default_ttl(Duration::from_secs(300));
+    Ok(Operator::new(builder)?.finish())
+}
+
+fn memory_cache() -> Result<Operator> {
+    // Initialize Moka as OpenDAL backend
+    let builder = Moka::default()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(300));
+    Ok(Operator::new(builder)?.finish())
 }
@@ -0,0 +1,174 @@
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use anyhow::{bail, Context, Result};
+use axum::Router;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use futures::future::BoxFuture;
+use rustls::crypto::aws_lc_rs;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use tokio_rustls::server::TlsStream;
+use tower_http::add_extension::AddExtension;
+use tracing::{error, info};
+use x509_parser::prelude::{FromDer, X509Certificate};
+use crate::auth::Principal;
+use crate::config::AppConfig;
+
+/// Where the server certificate and the optional client CA live
+#[derive(Debug, Clone)]
+pub struct TlsSettings {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Enables mutual TLS: client certificates must be issued by one of these CAs
+    pub client_ca_path: Option<PathBuf>,
+    /// Accept clients without a certificate; they stay anonymous
+    pub client_auth_optional: bool,
+    /// How often the files are checked for changes
+    pub reload_interval: Duration,
+}
+
+impl TlsSettings {
+    /// `None` when no certificate is configured and the server should speak plain HTTP
+    pub fn from_config(config: &AppConfig) -> Result<Option<Self>> {
+        match (&config.tls_cert_path, &config.tls_key_path) {
+            (None, None) => Ok(None),
+            (Some(cert_path), Some(key_path)) => Ok(Some(TlsSettings {
+                cert_path: cert_path.clone(),
+                key_path: key_path.clone(),
+                client_ca_path: config.tls_client_ca_path.clone(),
+                client_auth_optional: config.tls_client_auth_optional,
+                reload_interval: config.tls_reload_interval,
+            })),
+            _ => bail!("TLS_CERT_PATH and TLS_KEY_PATH must be set together"),
+        }
+    }
+
+    fn watched_paths(&self) -> Vec<&Path> {
+        let mut paths = vec![self.cert_path.as_path(), self.key_path.as_path()];
+        paths.extend(self.client_ca_path.as_deref());
+        paths
+    }
+
+    /// Read the PEM files into a rustls config offering HTTP/2 and HTTP/1.1 over ALPN
+    fn server_config(&self) -> Result<ServerConfig> {
+        let provider = Arc::new(aws_lc_rs::default_provider());
+        let certificates = read_certificates(&self.cert_path)?;
+        let key = PrivateKeyDer::from_pem_file(&self.key_path)
+            .with_context(|| format!("Failed to read private key {}", self.key_path.display()))?;
+
+        let builder = ServerConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions()?;
+        let builder = match &self.client_ca_path {
+            None => builder.with_no_client_auth(),
+            Some(client_ca_path) => {
+                let mut roots = RootCertStore::empty();
+                for certificate in read_certificates(client_ca_path)? {
+                    roots.add(certificate)?;
+                }
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                let verifier = if self.client_auth_optional {
+                    verifier.allow_unauthenticated().build()?
+                } else {
+                    verifier.build()?
+                };
+                builder.with_client_cert_verifier(verifier)
+            }
+        };
+        let mut config = builder.with_single_cert(certificates, key)?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    CertificateDer::pem_file_iter(path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read certificates {}", path.display()))
+}
+
+/// The common name of the certificate subject, or the whole subject when it has none
+fn principal_from_certificate(certificate: &CertificateDer<'_>) -> Option<Principal> {
+    let (_, certificate) = X509Certificate::from_der(certificate.as_ref()).ok()?;
+    let subject = certificate.subject();
+    let common_name = subject.iter_common_name().next().and_then(|name| name.as_str().ok());
+    Some(Principal(common_name.map_or_else(|| subject.to_string(), str::to_string)))
+}
+
+/// Terminates TLS and hands the client certificate's subject to every request on the connection
+#[derive(Clone)]
+struct ClientCertAcceptor(RustlsAcceptor);
+
+impl<I, S> Accept<I, S> for ClientCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = TlsStream<I>;
+    type Service = AddExtension<S, Principal>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let acceptor = self.0.clone();
+        Box::pin(async move {
+            let (stream, service) = acceptor.accept(stream, service).await?;
+            let principal = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certificates| certificates.first())
+                .and_then(principal_from_certificate)
+                .unwrap_or_else(Principal::anonymous);
+            Ok((stream, AddExtension::new(service, principal)))
+        })
+    }
+}
+
+fn modified_times(paths: &[&Path]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+        .collect()
+}
+
+/// Swap in the new certificates whenever one of the files changes. New connections use them
+/// right away; a broken file is logged and the previous certificates stay in use.
+fn spawn_reload(rustls_config: RustlsConfig, settings: TlsSettings) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(settings.reload_interval);
+        let mut last_modified = modified_times(&settings.watched_paths());
+        loop {
+            ticker.tick().await;
+            let modified = modified_times(&settings.watched_paths());
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            match settings.server_config() {
+                Ok(server_config) => {
+                    rustls_config.reload_from_config(Arc::new(server_config));
+                    info!("Reloaded TLS certificates from {}", settings.cert_path.display());
+                }
+                Err(e) => error!("Failed to reload TLS certificates: {:#}", e),
+            }
+        }
+    })
+}
+
+/// Serve HTTPS, with HTTP/2 negotiated over ALPN, until the server fails
+pub async fn serve(app: Router, addr: SocketAddr, settings: TlsSettings) -> Result<()> {
+    let rustls_config = RustlsConfig::from_config(Arc::new(settings.server_config()?));
+    spawn_reload(rustls_config.clone(), settings);
+    axum_server::bind(addr)
+        .acceptor(ClientCertAcceptor(RustlsAcceptor::new(rustls_config)))
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
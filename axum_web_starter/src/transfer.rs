@@ -0,0 +1,272 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use async_stream::try_stream;
+use axum::body::Body;
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use axum::BoxError;
+use futures::stream::BoxStream;
+use futures::{Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::error;
+use uuid::Uuid;
+use crate::errors::AppError;
+use crate::models::batch::BatchMode;
+use crate::models::customer::{Customer, CustomerPayload};
+use crate::models::seller::{Seller, SellerPayload};
+use crate::models::transfer::{DataFormat, ImportReport, LineError};
+use crate::repositories::customer_repository::CustomerRepository;
+use crate::repositories::seller_repository::SellerRepository;
+use crate::utils::is_unique_violation;
+
+/// Uploads are parsed in memory, so imports get a larger but still bounded body limit
+pub const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+const CUSTOMER_COLUMNS: &[&str] = &["id", "name", "email"];
+const SELLER_COLUMNS: &[&str] = &["id", "name", "company_name"];
+
+struct ImportRow<T> {
+    line: u64,
+    value: T,
+}
+
+/// The format named in the query string wins over the Content-Type of the upload
+pub fn import_format(format: Option<DataFormat>, headers: &HeaderMap) -> Result<DataFormat, AppError> {
+    format
+        .or_else(|| {
+            headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(DataFormat::from_content_type)
+        })
+        .ok_or_else(|| {
+            AppError::BadRequest(
+                "Upload must be text/csv or application/x-ndjson, or name a format with ?format=".to_string(),
+            )
+        })
+}
+
+/// Decode every line of an upload, collecting a line-numbered error for each one that fails
+fn parse_rows<T: DeserializeOwned>(format: DataFormat, data: &[u8]) -> (Vec<ImportRow<T>>, Vec<LineError>) {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    match format {
+        DataFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data);
+            let headers = match reader.headers() {
+                Ok(headers) => headers.clone(),
+                Err(e) => {
+                    errors.push(LineError { line: 1, error: e.to_string() });
+                    return (rows, errors);
+                }
+            };
+            for record in reader.records() {
+                let result = record.and_then(|record| {
+                    let line = record.position().map_or(0, |position| position.line());
+                    record.deserialize(Some(&headers)).map(|value| ImportRow { line, value })
+                });
+                match result {
+                    Ok(row) => rows.push(row),
+                    Err(e) => errors.push(LineError {
+                        line: e.position().map_or(0, |position| position.line()),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        }
+        DataFormat::Ndjson => {
+            for (index, line) in data.split(|byte| *byte == b'\n').enumerate() {
+                let line_number = index as u64 + 1;
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+                match serde_json::from_slice(line) {
+                    Ok(value) => rows.push(ImportRow { line: line_number, value }),
+                    Err(e) => errors.push(LineError { line: line_number, error: e.to_string() }),
+                }
+            }
+        }
+    }
+    (rows, errors)
+}
+
+fn encode_row<T: Serialize>(format: DataFormat, row: &T) -> Result<Vec<u8>, BoxError> {
+    match format {
+        DataFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+            writer.serialize(row)?;
+            Ok(writer.into_inner().map_err(|e| e.into_error())?)
+        }
+        DataFormat::Ndjson => {
+            let mut line = serde_json::to_vec(row)?;
+            line.push(b'\n');
+            Ok(line)
+        }
+    }
+}
+
+/// Stream rows straight from the repository (a database cursor for Postgres) into the response body
+fn export_response<R, T>(
+    format: DataFormat,
+    name: &str,
+    columns: &'static [&'static str],
+    repository: Arc<R>,
+    fetch: for<'a> fn(&'a R) -> BoxStream<'a, Result<T, sqlx::Error>>,
+) -> Response
+where
+    R: ?Sized + Send + Sync + 'static,
+    T: Serialize + Send + 'static,
+{
+    let body: BoxStream<'static, Result<Vec<u8>, BoxError>> = Box::pin(try_stream! {
+        if format == DataFormat::Csv {
+            yield format!("{}\n", columns.join(",")).into_bytes();
+        }
+        let mut rows = fetch(&repository);
+        while let Some(row) = rows.try_next().await? {
+            yield encode_row(format, &row)?;
+        }
+    });
+    let body = body.inspect_err(|e| error!("Export failed: {}", e));
+    let extension = match format {
+        DataFormat::Csv => "csv",
+        DataFormat::Ndjson => "ndjson",
+    };
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.{}\"", name, extension)),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+pub fn export_customers(customers: Arc<dyn CustomerRepository>, format: DataFormat) -> Response {
+    export_response(format, "customers", CUSTOMER_COLUMNS, customers, |customers| customers.stream_customers())
+}
+
+pub fn export_sellers(sellers: Arc<dyn SellerRepository>, format: DataFormat) -> Response {
+    export_response(format, "sellers", SELLER_COLUMNS, sellers, |sellers| sellers.stream_sellers())
+}
+
+/// Validate an upload of customers and insert the valid rows in a single statement.
+/// Emails must be unique within the file and must not belong to an existing customer, even
+/// one created while the import runs. In atomic mode nothing is inserted when any line is rejected.
+pub async fn import_customers(
+    repository: &dyn CustomerRepository,
+    format: DataFormat,
+    mode: BatchMode,
+    data: &[u8],
+) -> Result<ImportReport, sqlx::Error> {
+    let (rows, mut errors) = parse_rows::<CustomerPayload>(format, data);
+
+    let mut first_lines: HashMap<String, u64> = HashMap::new();
+    let mut customers = Vec::with_capacity(rows.len());
+    for ImportRow { line, value: mut payload } in rows {
+        if let Err(error) = payload.validate() {
+            errors.push(LineError { line, error });
+            continue;
+        }
+        match first_lines.entry(payload.email.to_lowercase()) {
+            Entry::Occupied(first) => {
+                errors.push(LineError {
+                    line,
+                    error: format!("Email {} already appears on line {}", payload.email, first.get()),
+                });
+                continue;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(line);
+            }
+        }
+        customers.push((line, Customer { id: Uuid::new_v4(), name: payload.name, email: payload.email }));
+    }
+
+    reject_taken_emails(repository, &mut customers, &mut errors).await?;
+    loop {
+        let batch: Vec<Customer> = customers.iter().map(|(_, customer)| customer.clone()).collect();
+        let result = finish_import(mode, errors.clone(), batch.len(), || repository.apply_batch(&batch, &[], &[])).await;
+        match result {
+            // Another request took an email since the check: reject its line too and insert the rest again
+            Err(e) if is_unique_violation(&e) => {
+                if reject_taken_emails(repository, &mut customers, &mut errors).await? == 0 {
+                    return Err(e);
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Move the rows whose email belongs to an existing customer to `errors`, returning how many
+async fn reject_taken_emails(
+    repository: &dyn CustomerRepository,
+    customers: &mut Vec<(u64, Customer)>,
+    errors: &mut Vec<LineError>,
+) -> Result<usize, sqlx::Error> {
+    let emails: Vec<String> = customers.iter().map(|(_, customer)| customer.email.clone()).collect();
+    let taken: HashSet<String> = repository.list_customers_by_emails(&emails)
+        .await?
+        .into_iter()
+        .map(|customer| customer.email.to_lowercase())
+        .collect();
+    let before = customers.len();
+    customers.retain(|(line, customer)| {
+        if taken.contains(&customer.email.to_lowercase()) {
+            errors.push(LineError {
+                line: *line,
+                error: format!("A customer with email {} already exists", customer.email),
+            });
+            return false;
+        }
+        true
+    });
+    Ok(before - customers.len())
+}
+
+/// Validate an upload of sellers and insert the valid rows in a single statement.
+/// In atomic mode nothing is inserted when any line is rejected.
+pub async fn import_sellers(
+    repository: &dyn SellerRepository,
+    format: DataFormat,
+    mode: BatchMode,
+    data: &[u8],
+) -> Result<ImportReport, sqlx::Error> {
+    let (rows, mut errors) = parse_rows::<SellerPayload>(format, data);
+
+    let mut sellers = Vec::with_capacity(rows.len());
+    for ImportRow { line, value: mut payload } in rows {
+        if let Err(error) = payload.validate() {
+            errors.push(LineError { line, error });
+            continue;
+        }
+        sellers.push(Seller {
+            id: Uuid::new_v4(),
+            name: payload.name,
+            company_name: payload.company_name,
+        });
+    }
+    finish_import(mode, errors, sellers.len(), || repository.apply_batch(&sellers, &[], &[])).await
+}
+
+async fn finish_import<F, Fut>(
+    mode: BatchMode,
+    mut errors: Vec<LineError>,
+    valid: usize,
+    insert: F,
+) -> Result<ImportReport, sqlx::Error>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<Uuid>, sqlx::Error>>,
+{
+    errors.sort_by_key(|error| error.line);
+    let imported = if valid == 0 || (mode == BatchMode::Atomic && !errors.is_empty()) {
+        0
+    } else {
+        insert().await?;
+        valid
+    };
+    Ok(ImportReport { mode, imported, errors })
+}
@@ -0,0 +1,141 @@
+use futures::future::BoxFuture;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use tokio::time::{sleep, Duration};
+use tracing::{error, warn};
+use crate::cache_codec::CacheCodec;
+use crate::state::AppState;
+use crate::utils::is_serialization_failure;
+
+/// Attempts of a unit of work before a serialization failure is given up on
+const MAX_ATTEMPTS: u32 = 5;
+/// Wait before the second attempt, doubled for every attempt after it
+const INITIAL_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Errors a unit of work can fail with, so a CockroachDB retry error can be told apart from
+/// the others
+pub trait TransactionError: From<sqlx::Error> {
+    fn database_error(&self) -> Option<&sqlx::Error>;
+}
+
+impl TransactionError for sqlx::Error {
+    fn database_error(&self) -> Option<&sqlx::Error> {
+        Some(self)
+    }
+}
+
+enum CacheOperation {
+    /// An encoded entry
+    Write(String, Vec<u8>),
+    Delete(String),
+}
+
+/// One database transaction shared by several DAO or repository calls, plus the cache changes
+/// that only apply once it has committed. The transaction begins on first use, so a unit of work
+/// over repositories that keep their data in memory never needs the database.
+pub struct UnitOfWork {
+    pool: PgPool,
+    tx: Option<Transaction<'static, Postgres>>,
+    cache: Vec<CacheOperation>,
+    codec: CacheCodec,
+}
+
+impl UnitOfWork {
+    /// The connection to pass to the DAOs. Those opening a transaction of their own get a
+    /// savepoint inside this one.
+    pub async fn connection(&mut self) -> Result<&mut PgConnection, sqlx::Error> {
+        let tx = match self.tx.take() {
+            Some(tx) => tx,
+            None => self.pool.begin().await?,
+        };
+        Ok(&mut **self.tx.insert(tx))
+    }
+
+    /// Write to the cache after the commit; nothing is written if the transaction rolls back
+    pub fn cache_write<T: Serialize>(&mut self, key: String, value: &T) {
+        match self.codec.encode(value) {
+            Ok(entry) => self.cache.push(CacheOperation::Write(key, entry)),
+            Err(e) => error!("Cache write error for {}: {}", key, e),
+        }
+    }
+
+    /// Delete from the cache after the commit
+    pub fn cache_delete(&mut self, key: String) {
+        self.cache.push(CacheOperation::Delete(key));
+    }
+
+    async fn apply_cache(cache: Vec<CacheOperation>, app_state: &AppState) {
+        for operation in cache {
+            match operation {
+                CacheOperation::Write(key, value) => {
+                    if let Err(e) = app_state.cache.write(&key, value).await {
+                        error!("Cache write error: {}", e);
+                    }
+                }
+                CacheOperation::Delete(key) => {
+                    if let Err(e) = app_state.cache.delete(&key).await {
+                        error!("Cache delete error: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run `work` in one transaction and commit it if it succeeds. The whole of `work` runs again
+/// on a fresh transaction when CockroachDB reports a serialization failure (SQLSTATE 40001),
+/// so it must not have effects outside the transaction other than the deferred cache changes.
+///
+/// ```ignore
+/// let product = unit_of_work::run(&app_state, |uow| {
+///     let payload = payload.clone();
+///     Box::pin(async move {
+///         let seller = SellerDAO::create_seller(uow.connection().await?, payload.name, payload.company_name).await?;
+///         let product = ProductDAO::create_product(uow.connection().await?, seller.id, /* ... */).await?;
+///         uow.cache_write(product_cache_key(product.id), &product);
+///         Ok(product)
+///     })
+/// })
+/// .await?;
+/// ```
+pub async fn run<T, E, F>(app_state: &AppState, mut work: F) -> Result<T, E>
+where
+    E: TransactionError,
+    F: for<'u> FnMut(&'u mut UnitOfWork) -> BoxFuture<'u, Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match run_once(app_state, &mut work).await {
+            Ok((value, cache)) => {
+                UnitOfWork::apply_cache(cache, app_state).await;
+                return Ok(value);
+            }
+            Err(e) if attempt < MAX_ATTEMPTS && e.database_error().is_some_and(is_serialization_failure) => {
+                warn!("Transaction conflicted with another one, retrying (attempt {})", attempt);
+                sleep(INITIAL_BACKOFF * 2u32.pow(attempt - 1)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn run_once<T, E, F>(app_state: &AppState, work: &mut F) -> Result<(T, Vec<CacheOperation>), E>
+where
+    E: TransactionError,
+    F: for<'u> FnMut(&'u mut UnitOfWork) -> BoxFuture<'u, Result<T, E>>,
+{
+    let mut uow = UnitOfWork {
+        pool: app_state.db_pool.clone(),
+        tx: None,
+        cache: Vec::new(),
+        codec: app_state.cache.codec(),
+    };
+    // An error drops the transaction, which rolls it back
+    let value = work(&mut uow).await?;
+    let UnitOfWork { tx, cache, .. } = uow;
+    if let Some(tx) = tx {
+        tx.commit().await?;
+    }
+    Ok((value, cache))
+}
@@ -0,0 +1,90 @@
+/// SQLSTATE raised when a row is still referenced by (or references a missing) foreign key
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+/// SQLSTATE raised when an insert or update would duplicate a unique key
+const UNIQUE_VIOLATION: &str = "23505";
+/// SQLSTATE raised when a function or operator, e.g. one from a missing extension, does not exist
+const UNDEFINED_FUNCTION: &str = "42883";
+/// SQLSTATE CockroachDB raises when a transaction conflicted with another one and has to be retried
+const SERIALIZATION_FAILURE: &str = "40001";
+/// SQLSTATEs of a server shutting down, out of connections, or cancelling a statement that ran
+/// past `statement_timeout`
+const UNAVAILABLE: [&str; 5] = ["57P01", "57P02", "57P03", "53300", "57014"];
+
+fn has_sqlstate(error: &sqlx::Error, sqlstate: &str) -> bool {
+    error
+        .as_database_error()
+        .and_then(|db_error| db_error.code())
+        .is_some_and(|code| code == sqlstate)
+}
+
+pub fn is_foreign_key_violation(error: &sqlx::Error) -> bool {
+    has_sqlstate(error, FOREIGN_KEY_VIOLATION)
+}
+
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    has_sqlstate(error, UNIQUE_VIOLATION)
+}
+
+pub fn is_undefined_function(error: &sqlx::Error) -> bool {
+    has_sqlstate(error, UNDEFINED_FUNCTION)
+}
+
+pub fn is_serialization_failure(error: &sqlx::Error) -> bool {
+    has_sqlstate(error, SERIALIZATION_FAILURE)
+}
+
+/// Errors that say nothing about the request and may well not happen on a retry: the database
+/// cannot be reached, the pool has no connection to spare, or the database is overloaded
+pub fn is_transient(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(db_error) => db_error.code().is_some_and(|code| {
+            // Class 08 is a connection exception
+            code.starts_with("08") || code == SERIALIZATION_FAILURE || UNAVAILABLE.contains(&code.as_ref())
+        }),
+        _ => false,
+    }
+}
+
+/// Stands in for the error Postgres raises on a unique key, for stores that are not a database
+#[derive(Debug)]
+struct UniqueViolation(String);
+
+impl std::fmt::Display for UniqueViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UniqueViolation {}
+
+impl sqlx::error::DatabaseError for UniqueViolation {
+    fn message(&self) -> &str {
+        &self.0
+    }
+
+    fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+        Some(UNIQUE_VIOLATION.into())
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::UniqueViolation
+    }
+}
+
+/// An error that `is_unique_violation` recognizes, like the one a unique index raises
+pub fn unique_violation(message: impl Into<String>) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(UniqueViolation(message.into())))
+}
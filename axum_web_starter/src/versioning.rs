@@ -0,0 +1,146 @@
+use std::sync::Arc;
+use axum::extract::{OriginalUri, Request, State};
+use axum::http::header::LINK;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use utoipa::openapi::path::Operation;
+use utoipa::openapi::{Deprecated, OpenApi as OpenApiDoc};
+use utoipa::OpenApi;
+use crate::api_doc::ApiDoc;
+use crate::auth::AdminAuth;
+use crate::config::AppConfig;
+use crate::routes;
+use crate::state::AppState;
+
+pub const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+pub const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    /// Starts out identical to v1; resources get their own v2 routes as their shape changes
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/v1",
+            ApiVersion::V2 => "/v2",
+        }
+    }
+}
+
+/// When routes stopped being recommended, when they go away, and where clients should move to
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub since: DateTime<Utc>,
+    pub sunset: Option<DateTime<Utc>>,
+    /// Prefix of the replacement routes, advertised as the `successor-version` link
+    pub successor: Option<&'static str>,
+}
+
+impl Deprecation {
+    /// The unprefixed aliases of the v1 routes, on the dates set by `LEGACY_API_DEPRECATED_AT`
+    /// and `LEGACY_API_SUNSET`
+    pub fn legacy(config: &AppConfig) -> Self {
+        Deprecation {
+            since: config.legacy_api_deprecated_at,
+            sunset: config.legacy_api_sunset,
+            successor: Some(ApiVersion::V1.prefix()),
+        }
+    }
+}
+
+/// The REST resources, mounted once per version and once more at the root as legacy aliases
+pub fn api_routes(app_state: Arc<AppState>, admin: AdminAuth) -> Router {
+    Router::new()
+        .merge(routes::customer_route::customer_routes(app_state.clone()))
+        .merge(routes::seller_route::seller_routes(app_state.clone()))
+        .merge(routes::order_route::order_routes(app_state.clone()))
+        .merge(routes::product_route::product_routes(app_state.clone()))
+        .merge(routes::address_route::address_routes(app_state.clone()))
+        .merge(routes::job_route::job_routes(app_state.clone(), admin))
+        .merge(routes::search_route::search_routes(app_state))
+}
+
+/// Answer every request to the router with `Deprecation` (RFC 9745) and `Sunset` (RFC 8594) headers
+pub fn deprecate(router: Router, deprecation: Deprecation) -> Router {
+    router.layer(middleware::from_fn_with_state(Arc::new(deprecation), add_deprecation_headers))
+}
+
+async fn add_deprecation_headers(
+    State(deprecation): State<Arc<Deprecation>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let successor = deprecation.successor.map(|prefix| {
+        let (mount, path) = mount_point(&request);
+        format!("<{}{}{}>; rel=\"successor-version\"", mount, prefix, path)
+    });
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::try_from(format!("@{}", deprecation.since.timestamp())) {
+        headers.insert(DEPRECATION, value);
+    }
+    if let Some(sunset) = deprecation.sunset {
+        if let Ok(value) = HeaderValue::try_from(sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string()) {
+            headers.insert(SUNSET, value);
+        }
+    }
+    if let Some(Ok(value)) = successor.map(HeaderValue::try_from) {
+        headers.append(LINK, value);
+    }
+    response
+}
+
+/// Split the path the client requested into where the deprecated router is mounted, such as the
+/// prefix the whole app is nested under, and the path within it
+fn mount_point(request: &Request) -> (&str, &str) {
+    let path = request.uri().path();
+    let original = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or(path, |original| original.path());
+    (original.strip_suffix(path).unwrap_or_default(), path)
+}
+
+/// Paths served at the root only, outside the versioned API
+const UNVERSIONED_PREFIX: &str = "/admin";
+
+/// The OpenAPI document of one version, with its paths under the version prefix
+pub fn openapi(version: ApiVersion) -> OpenApiDoc {
+    let mut doc = ApiDoc::openapi();
+    doc.info.version = version.prefix().trim_start_matches('/').to_string();
+    doc.paths.paths = std::mem::take(&mut doc.paths.paths)
+        .into_iter()
+        .map(|(path, item)| {
+            if path.starts_with(UNVERSIONED_PREFIX) {
+                (path, item)
+            } else {
+                (format!("{}{}", version.prefix(), path), item)
+            }
+        })
+        .collect();
+    doc
+}
+
+/// The OpenAPI document of the unprefixed aliases, with every operation marked deprecated
+/// except those of the unversioned paths
+pub fn legacy_openapi() -> OpenApiDoc {
+    let mut doc = ApiDoc::openapi();
+    let aliases = doc.paths.paths.iter_mut().filter(|(path, _)| !path.starts_with(UNVERSIONED_PREFIX));
+    for (_, item) in aliases {
+        let operations: [&mut Option<Operation>; 5] =
+            [&mut item.get, &mut item.post, &mut item.put, &mut item.patch, &mut item.delete];
+        for operation in operations.into_iter().flatten() {
+            operation.deprecated = Some(Deprecated::True);
+        }
+    }
+    doc
+}
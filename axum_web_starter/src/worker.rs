@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout, Duration};
+use tracing::{error, info, warn};
+use crate::daos::job_dao::JobDAO;
+use crate::handlers::customer_handler::{cache_customer, evict_customer};
+use crate::handlers::seller_handler::cache_seller;
+use crate::models::job::{CacheJobPayload, ImportJobPayload, Job, JobKind, JobStatus};
+use crate::state::AppState;
+use crate::transfer;
+
+/// Longest delay between two attempts of a failing job
+const MAX_BACKOFF_SECONDS: i32 = 600;
+/// Rows processed between two progress updates of the cache jobs
+const PROGRESS_EVERY: i64 = 500;
+
+/// Why an attempt failed; permanent failures are dead-lettered without further retries
+struct JobError {
+    message: String,
+    permanent: bool,
+}
+
+impl JobError {
+    fn permanent(message: impl Into<String>) -> Self {
+        JobError { message: message.into(), permanent: true }
+    }
+}
+
+impl From<sqlx::Error> for JobError {
+    fn from(e: sqlx::Error) -> Self {
+        JobError { message: e.to_string(), permanent: false }
+    }
+}
+
+/// Spawn `count` workers that poll the `jobs` table until the process exits
+pub fn spawn_workers(app_state: Arc<AppState>, count: usize, poll_interval: Duration) -> Vec<JoinHandle<()>> {
+    (0..count)
+        .map(|worker| tokio::spawn(run_worker(app_state.clone(), worker, poll_interval)))
+        .collect()
+}
+
+async fn run_worker(app_state: Arc<AppState>, worker: usize, poll_interval: Duration) {
+    info!("Job worker {} started", worker);
+    loop {
+        match JobDAO::claim_next(&app_state.db_pool).await {
+            Ok(Some(job)) => execute(&app_state, job).await,
+            Ok(None) => sleep(poll_interval).await,
+            Err(e) => {
+                error!("Job worker {} failed to claim a job: {}", worker, e);
+                sleep(poll_interval).await;
+            }
+        }
+    }
+}
+
+async fn execute(app_state: &AppState, job: Job) {
+    info!("Running job {} ({:?}), attempt {}/{}", job.id, job.kind, job.attempts, job.max_attempts);
+    let outcome = if job.attempts > job.max_attempts {
+        // The lease of the last attempt expired, most likely because its worker died
+        Err(JobError::permanent("The worker running the last attempt stopped responding"))
+    } else {
+        match timeout(Duration::from_secs(job.timeout_seconds as u64), run(app_state, &job)).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(JobError {
+                message: format!("Timed out after {} seconds", job.timeout_seconds),
+                permanent: false,
+            }),
+        }
+    };
+
+    let pool = &app_state.db_pool;
+    let recorded = match &outcome {
+        Ok(result) => JobDAO::complete(pool, job.id, job.attempts, result.clone())
+            .await
+            .map(|owned| owned.then_some(JobStatus::Succeeded)),
+        Err(e) => {
+            let backoff = 2i32.saturating_pow(job.attempts.max(0) as u32).min(MAX_BACKOFF_SECONDS);
+            JobDAO::fail(pool, job.id, job.attempts, &e.message, backoff, e.permanent).await
+        }
+    };
+    let message = outcome.err().map(|e| e.message).unwrap_or_default();
+    match recorded {
+        Ok(Some(JobStatus::Dead)) => warn!("Job {} dead-lettered: {}", job.id, message),
+        Ok(Some(JobStatus::Queued)) => warn!("Job {} will be retried: {}", job.id, message),
+        Ok(Some(status)) => info!("Job {} is now {:?}", job.id, status),
+        Ok(None) => warn!("Job {} lost its lease before finishing", job.id),
+        Err(e) => error!("Failed to record the outcome of job {}: {}", job.id, e),
+    }
+}
+
+async fn run(app_state: &AppState, job: &Job) -> Result<Value, JobError> {
+    match job.kind {
+        JobKind::ImportCustomers | JobKind::ImportSellers => {
+            let payload: ImportJobPayload = serde_json::from_value(job.payload.clone())
+                .map_err(|e| JobError::permanent(format!("Invalid import payload: {}", e)))?;
+            let data = payload.data.as_bytes();
+            let report = if job.kind == JobKind::ImportCustomers {
+                transfer::import_customers(app_state.customers.as_ref(), payload.format, payload.mode, data).await?
+            } else {
+                transfer::import_sellers(app_state.sellers.as_ref(), payload.format, payload.mode, data).await?
+            };
+            Ok(json!(report))
+        }
+        JobKind::WarmCustomerCache | JobKind::PurgeCustomerCache => {
+            let customers = &app_state.customers;
+            let (total, mut rows) = match CacheJobPayload::from_job(&job.payload).map_err(JobError::permanent)?.ids {
+                Some(ids) => (ids.len() as i64, listed(customers.list_customers_by_ids(&ids).await?)),
+                None => (customers.count_customers().await?, customers.stream_customers()),
+            };
+            let mut processed = 0;
+            while let Some(customer) = rows.try_next().await? {
+                if job.kind == JobKind::WarmCustomerCache {
+                    cache_customer(app_state, &customer).await;
+                } else {
+                    evict_customer(app_state, customer.id).await;
+                }
+                processed += 1;
+                report_progress(app_state, job, processed, total).await?;
+            }
+            Ok(json!({ "customers": processed }))
+        }
+        JobKind::WarmSellerCache => {
+            let sellers = &app_state.sellers;
+            let (total, mut rows) = match CacheJobPayload::from_job(&job.payload).map_err(JobError::permanent)?.ids {
+                Some(ids) => (ids.len() as i64, listed(sellers.list_sellers_by_ids(&ids).await?)),
+                None => (sellers.count_sellers().await?, sellers.stream_sellers()),
+            };
+            let mut processed = 0;
+            while let Some(seller) = rows.try_next().await? {
+                cache_seller(app_state, &seller).await;
+                processed += 1;
+                report_progress(app_state, job, processed, total).await?;
+            }
+            Ok(json!({ "sellers": processed }))
+        }
+    }
+}
+
+/// Rows already loaded, streamed like those of a whole table
+fn listed<T: Send + 'static>(rows: Vec<T>) -> BoxStream<'static, Result<T, sqlx::Error>> {
+    stream::iter(rows.into_iter().map(Ok)).boxed()
+}
+
+/// Record the progress of a cache job every `PROGRESS_EVERY` rows
+async fn report_progress(app_state: &AppState, job: &Job, processed: i64, total: i64) -> Result<(), sqlx::Error> {
+    if processed % PROGRESS_EVERY == 0 {
+        let progress = (processed * 100 / total.max(1)).min(100) as i32;
+        JobDAO::update_progress(&app_state.db_pool, job.id, job.attempts, progress).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use serde_json::json;
+use super::harness::{unique_email, TestApp};
+
+async fn setup_customer(app: &TestApp) -> String {
+    let customer = app.create_customer_with("Address User", &unique_email("address.user")).await;
+    customer["id"].as_str().unwrap().to_string()
+}
+
+fn address(line1: &str, country_code: &str) -> serde_json::Value {
+    json!({
+        "kind": "shipping",
+        "line1": line1,
+        "city": "Austin",
+        "region": "TX",
+        "postal_code": "78701",
+        "country_code": country_code,
+        "phone": "+15125550100",
+        "is_default": true
+    })
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_address_crud_and_default_flag() {
+    let app = TestApp::spawn_with_database().await;
+    let customer_id = setup_customer(&app).await;
+    let base = app.url(&format!("/customers/{}/addresses", customer_id));
+
+    let response = app.client.post(&base).json(&address("1 Main St", "XX")).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = app.client.post(&base).json(&address("1 Main St", "us")).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let first: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(first["country_code"], "US");
+
+    // A second default of the same kind demotes the first one
+    let response = app.client.post(&base).json(&address("2 Main St", "US")).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(&format!("{}/{}", base, first["id"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["is_default"], false);
+
+    let response = app.client.delete(&format!("{}/{}", base, first["id"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(&base).send().await.unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["line1"], "2 Main St");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_get_customer_with_expanded_addresses() {
+    let app = TestApp::spawn_with_database().await;
+    let customer_id = setup_customer(&app).await;
+    let url = app.url(&format!("/customers/{}", customer_id));
+
+    // Warm both cached representations before the address exists
+    let body: serde_json::Value = app.client.get(&url).send().await.unwrap().json().await.unwrap();
+    assert!(body.get("addresses").is_none());
+    let body: serde_json::Value = app.client.get(&format!("{}?expand=addresses", url))
+        .send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["addresses"].as_array().unwrap().len(), 0);
+
+    let response = app.client.post(&format!("{}/addresses", url))
+        .json(&address("3 Main St", "US"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(&format!("{}?expand=addresses", url)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["id"], customer_id);
+    assert_eq!(body["addresses"].as_array().unwrap().len(), 1);
+
+    let response = app.client.get(&format!("{}?expand=orders", url)).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,164 @@
+use serde_json::{json, Value};
+use super::harness::{test_config, TestApp, ADMIN_TOKEN};
+
+async fn cache_stats(app: &TestApp) -> Value {
+    let response = app.client.get(app.url("/admin/cache/stats")).bearer_auth(ADMIN_TOKEN).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_admin_credentials() {
+    let app = TestApp::spawn().await;
+
+    let response = app.client.get(app.url("/admin/cache/stats")).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+
+    let response = app.client.get(app.url("/admin/cache/stats")).bearer_auth("wrong").send().await.unwrap();
+    assert_eq!(response.status(), 403);
+
+    // Without ADMIN_TOKEN or ADMIN_PRINCIPALS nobody gets in
+    let mut config = test_config(None);
+    config.admin_token = None;
+    let closed = TestApp::serve(axum_web_starter::app(&config).await.unwrap()).await;
+    let response = closed.client.get(closed.url("/admin/cache/stats")).bearer_auth(ADMIN_TOKEN).send().await.unwrap();
+    assert_eq!(response.status(), 403);
+}
+
+#[tokio::test]
+async fn test_evict_cached_customer() {
+    let app = TestApp::spawn().await;
+    let customer = app.create_customer().await;
+    let id = customer["id"].as_str().unwrap();
+
+    // Creating a customer caches it, so the read is a hit
+    let before = cache_stats(&app).await;
+    app.client.get(app.url(&format!("/customers/{}", id))).send().await.unwrap();
+    let after = cache_stats(&app).await;
+    assert_eq!(after["hits"].as_u64().unwrap(), before["hits"].as_u64().unwrap() + 1);
+
+    let response = app.client.delete(app.url(&format!("/admin/cache/customer/{}", id)))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+
+    app.client.get(app.url(&format!("/customers/{}", id))).send().await.unwrap();
+    let evicted = cache_stats(&app).await;
+    assert_eq!(evicted["misses"].as_u64().unwrap(), after["misses"].as_u64().unwrap() + 1);
+}
+
+#[tokio::test]
+async fn test_delete_cache_prefix() {
+    let app = TestApp::spawn().await;
+    app.create_customer().await;
+    app.create_customer().await;
+    let seller = app.create_seller().await;
+    app.client.get(app.url(&format!("/sellers/{}", seller["id"].as_str().unwrap()))).send().await.unwrap();
+
+    // Test servers cache in memory, which can list its keys
+    let stats = cache_stats(&app).await;
+    assert_eq!(stats["keys"]["customer"], 2);
+    assert_eq!(stats["keys"]["seller"], 1);
+
+    let response = app.client.delete(app.url("/admin/cache?prefix=customer:"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let deletion: Value = response.json().await.unwrap();
+    assert_eq!(deletion["deleted"], 2);
+
+    let stats = cache_stats(&app).await;
+    assert_eq!(stats["keys"]["customer"], 0);
+    assert_eq!(stats["keys"]["seller"], 1);
+}
+
+#[tokio::test]
+async fn test_warm_cache_rejects_invalid_requests() {
+    let app = TestApp::spawn().await;
+    for body in [json!({ "entity": "product" }), json!({ "entity": "customer", "ids": [] })] {
+        let response = app.client.post(app.url("/admin/cache/warm"))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400, "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn test_delete_cache_prefix_without_listing() {
+    // Redis cannot list its keys, so the keys of the stored rows are deleted instead
+    let app = TestApp::spawn_configured(|config| config.cache_backend = "redis".to_string()).await;
+    assert!(cache_stats(&app).await["keys"].is_null());
+
+    let response = app.client.delete(app.url("/admin/cache?prefix=seller:"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let deletion: Value = response.json().await.unwrap();
+    assert_eq!(deletion["deleted"], 0);
+
+    // With a seller stored, its key is deleted from a Redis nobody listens on
+    app.create_seller().await;
+    let response = app.client.delete(app.url("/admin/cache?prefix=seller:"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 500);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_warm_cache_queues_job() {
+    let app = TestApp::spawn_with_worker().await;
+    let seller = app.create_seller().await;
+    let id = seller["id"].as_str().unwrap();
+    let cache_key = format!("seller:{}", id);
+
+    // Creating the seller cached it, so start from an empty cache
+    let response = app.client.delete(app.url(&format!("/admin/cache/seller/{}", id)))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+    assert!(app.state().cache.get::<Value>(&cache_key).await.is_none());
+
+    let response = app.client.post(app.url("/admin/cache/warm"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({ "entity": "seller", "ids": [seller["id"]] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let job: Value = response.json().await.unwrap();
+    assert_eq!(location, format!("/jobs/{}", job["id"].as_str().unwrap()));
+    assert_eq!(job["kind"], "warm_seller_cache");
+
+    let job = app.wait_for_job(job["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["result"]["sellers"], 1);
+    assert_eq!(app.state().cache.get::<Value>(&cache_key).await, Some(seller));
+}
+
+#[tokio::test]
+async fn test_admin_routes_are_documented_at_the_root() {
+    let app = TestApp::spawn().await;
+    for path in ["/openapi.json", "/v1/openapi.json"] {
+        let doc: Value = app.client.get(app.url(path)).send().await.unwrap().json().await.unwrap();
+        let stats = &doc["paths"]["/admin/cache/stats"]["get"];
+        assert!(stats.is_object(), "{}", path);
+        assert!(stats.get("deprecated").is_none());
+        assert!(doc["paths"].get("/v1/admin/cache/stats").is_none());
+    }
+}
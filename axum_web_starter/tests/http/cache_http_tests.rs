@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum_web_starter::cache::CircuitState;
+use axum_web_starter::cache_codec::{CacheCodec, CacheFormat, CodecError};
+use axum_web_starter::models::customer::Customer;
+use axum_web_starter::state::AppState;
+use serde_json::{json, Value};
+use super::harness::{test_config, TestApp, ADMIN_TOKEN};
+
+/// A server with in-memory customers, and its state to reach the cache directly
+async fn spawn_with_format(format: CacheFormat, compress_above: Option<usize>) -> (TestApp, Arc<AppState>) {
+    let mut config = test_config(None);
+    config.cache_format = format;
+    config.cache_compress_above = compress_above;
+    let app_state = Arc::new(AppState::from_config(&config).await.unwrap());
+    let app = TestApp::serve(axum_web_starter::router(app_state.clone(), &config).unwrap()).await;
+    (app, app_state)
+}
+
+async fn hits(app: &TestApp) -> u64 {
+    let response = app.client.get(app.url("/admin/cache/stats")).bearer_auth(ADMIN_TOKEN).send().await.unwrap();
+    let stats: Value = response.json().await.unwrap();
+    stats["hits"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_every_format_serves_cache_hits() {
+    for format in CacheFormat::ALL {
+        for compress_above in [None, Some(0)] {
+            let (app, _) = spawn_with_format(format, compress_above).await;
+            let customer = app.create_customer().await;
+            let id = customer["id"].as_str().unwrap();
+            // The in-memory store has no addresses, but the expanded representation is cached on its own
+            for path in [format!("/customers/{}", id), format!("/customers/{}?expand=addresses", id)] {
+                let first: Value = app.client.get(app.url(&path)).send().await.unwrap().json().await.unwrap();
+                let before = hits(&app).await;
+                let second: Value = app.client.get(app.url(&path)).send().await.unwrap().json().await.unwrap();
+                assert_eq!(hits(&app).await, before + 1, "{:?} {:?} {}", format, compress_above, path);
+                assert_eq!(first, second, "{:?} {:?} {}", format, compress_above, path);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_stale_entries_are_refreshed() {
+    let (app, app_state) = spawn_with_format(CacheFormat::MessagePack, None).await;
+    let customer = app.create_customer().await;
+    let id = customer["id"].as_str().unwrap();
+
+    // An entry written as plain JSON, before entries had a header byte
+    let legacy = serde_json::json!({ "id": id, "name": "Legacy Name", "email": customer["email"] });
+    app_state.cache.write(&format!("customer:{}", id), legacy.to_string()).await.unwrap();
+
+    let response = app.client.get(app.url(&format!("/customers/{}", id))).send().await.unwrap();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["name"], customer["name"]);
+
+    let entry = app_state.cache.read(&format!("customer:{}", id)).await.unwrap().to_vec();
+    let cached: Customer = app_state.cache.codec().decode(&entry).unwrap();
+    assert_eq!(cached.name, customer["name"].as_str().unwrap());
+}
+
+#[tokio::test]
+async fn test_updates_while_the_circuit_is_open_are_not_served_stale() {
+    let mut config = test_config(None);
+    config.cache_failure_threshold = 1;
+    config.cache_open_duration = Duration::from_millis(200);
+    let app_state = Arc::new(AppState::from_config(&config).await.unwrap());
+    let app = TestApp::serve(axum_web_starter::router(app_state.clone(), &config).unwrap()).await;
+    let customer = app.create_customer().await;
+    let url = app.url(&format!("/customers/{}", customer["id"].as_str().unwrap()));
+    assert_eq!(app.client.get(&url).send().await.unwrap().status(), 200);
+
+    // Reading a directory fails, which opens the circuit
+    assert!(app_state.cache.read("not-a-key/").await.is_err());
+    assert_eq!(app_state.cache.health().state, CircuitState::Open);
+
+    // The update cannot reach the cache, which still holds the old customer
+    let response = app.client.put(&url)
+        .json(&json!({ "name": "Renamed While Open", "email": customer["email"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(app_state.cache.health().pending_deletes > 0);
+
+    // The probe once the circuit may close replays the delete instead of serving the old customer
+    tokio::time::sleep(config.cache_open_duration).await;
+    let body: Value = app.client.get(&url).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["name"], "Renamed While Open");
+    let health = app_state.cache.health();
+    assert_eq!(health.state, CircuitState::Closed);
+    assert_eq!(health.pending_deletes, 0);
+
+    let body: Value = app.client.get(&url).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["name"], "Renamed While Open");
+}
+
+#[test]
+fn test_decompression_is_bounded() {
+    let codec = CacheCodec { format: CacheFormat::Json, compress_above: Some(1024), zstd_level: 3 };
+    let entry = codec.encode(&"a".repeat(1024 * 1024)).unwrap();
+    assert_eq!(codec.decode::<String>(&entry).unwrap().len(), 1024 * 1024);
+
+    // A few kilobytes that would expand past the limit are refused instead of decompressed
+    let entry = codec.encode(&"a".repeat(32 * 1024 * 1024)).unwrap();
+    assert!(entry.len() < 64 * 1024);
+    assert!(matches!(codec.decode::<String>(&entry), Err(CodecError::Decode(_))));
+}
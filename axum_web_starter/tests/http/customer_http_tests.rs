@@ -1,25 +1,19 @@
-use reqwest::Client;
+use std::sync::Arc;
+use axum::routing::post;
+use axum::{middleware, Router};
+use axum_web_starter::idempotency::{self, Idempotency};
 use serde_json::json;
+use tokio::sync::Notify;
+use uuid::Uuid;
+use super::harness::{test_config, unique_email, TestApp};
 
-async fn setup_customer() -> String {
-    let client = Client::new();
-    let response = client.post("http://localhost:3000/customers")
-        .json(&json!({
-            "name": "Setup User",
-            "email": "setup.user@example.com"
-        }))
-        .send()
-        .await
-        .unwrap();
-
-    assert_eq!(response.status(), 200);
-    let body: serde_json::Value = response.json().await.unwrap();
-    body["id"].as_str().unwrap().to_string() // Return the created UUID
+async fn setup_customer(app: &TestApp) -> String {
+    let customer = app.create_customer().await;
+    customer["id"].as_str().unwrap().to_string() // Return the created UUID
 }
 
-async fn teardown_customer(customer_id: &str) {
-    let client = Client::new();
-    let response = client.delete(&format!("http://localhost:3000/customers/{}", customer_id))
+async fn teardown_customer(app: &TestApp, customer_id: &str) {
+    let response = app.client.delete(app.url(&format!("/customers/{}", customer_id)))
         .send()
         .await
         .unwrap();
@@ -29,14 +23,15 @@ async fn teardown_customer(customer_id: &str) {
 
 #[tokio::test]
 async fn test_crud_operations() {
-    let customer_id = setup_customer().await;
+    let app = TestApp::spawn().await;
+    let customer_id = setup_customer(&app).await;
 
     // Test Update
-    let client = Client::new();
-    let response = client.put(&format!("http://localhost:3000/customers/{}", customer_id))
+    let updated_email = unique_email("updated.user");
+    let response = app.client.put(app.url(&format!("/customers/{}", customer_id)))
         .json(&json!({
             "name": "Updated User",
-            "email": "updated.user@example.com"
+            "email": updated_email
         }))
         .send()
         .await
@@ -45,10 +40,10 @@ async fn test_crud_operations() {
     assert_eq!(response.status(), 200);
     let body: serde_json::Value = response.json().await.unwrap();
     assert_eq!(body["name"], "Updated User");
-    assert_eq!(body["email"], "updated.user@example.com");
+    assert_eq!(body["email"], updated_email);
 
     // Test Get
-    let response = client.get(&format!("http://localhost:3000/customers/{}", customer_id))
+    let response = app.client.get(app.url(&format!("/customers/{}", customer_id)))
         .send()
         .await
         .unwrap();
@@ -58,10 +53,10 @@ async fn test_crud_operations() {
     assert_eq!(body["id"], customer_id);
 
     // Teardown
-    teardown_customer(&customer_id).await;
+    teardown_customer(&app, &customer_id).await;
 
     // Verify Teardown
-    let response = client.get(&format!("http://localhost:3000/customers/{}", customer_id))
+    let response = app.client.get(app.url(&format!("/customers/{}", customer_id)))
         .send()
         .await
         .unwrap();
@@ -71,17 +66,420 @@ async fn test_crud_operations() {
 
 #[tokio::test]
 async fn test_list_customers() {
-    let customer_id = setup_customer().await;
+    let app = TestApp::spawn().await;
+    let customer_id = setup_customer(&app).await;
+
+    let response = app.client.get(app.url("/customers"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    // Every test has the server to itself, so the list holds only this customer
+    let customers = body.as_array().unwrap();
+    assert_eq!(customers.len(), 1);
+    assert_eq!(customers[0]["id"], customer_id);
+}
+
+#[tokio::test]
+async fn test_email_is_unique_case_insensitively() {
+    let app = TestApp::spawn().await;
+    let email = unique_email("unique.user");
+    let created = app.create_customer_with("Unique User", &email).await;
+
+    // Duplicate create with different casing
+    let response = app.client.post(app.url("/customers"))
+        .json(&json!({ "name": "Other User", "email": email.to_uppercase() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["existing_id"], created["id"]);
+
+    // Lookup by email
+    let response = app.client.get(app.url(&format!("/customers/by-email/{}", email.to_uppercase())))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["id"], created["id"]);
+
+    // Upsert by email updates instead of duplicating
+    let response = app.client.put(app.url("/customers"))
+        .json(&json!({ "name": "Imported User", "email": email }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["id"], created["id"]);
+    assert_eq!(body["name"], "Imported User");
+
+    let response = app.client.put(app.url("/customers"))
+        .json(&json!({ "name": "New Import", "email": unique_email("new.import") }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_ne!(body["id"], created["id"]);
+}
+
+#[tokio::test]
+async fn test_batch_is_atomic() {
+    let app = TestApp::spawn().await;
+    let existing_id = setup_customer(&app).await;
+    let first_email = unique_email("batch.first");
+    let second_email = unique_email("batch.second");
+
+    // One bad operation rolls back the whole batch
+    let missing_id = Uuid::new_v4().to_string();
+    let response = app.client.post(app.url("/customers:batch"))
+        .json(&json!({
+            "operations": [
+                { "op": "create", "name": "Batch First", "email": first_email },
+                { "op": "delete", "id": missing_id }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["failed"], 2);
+    assert_eq!(body["results"][0]["status"], 424);
+    assert_eq!(body["results"][1]["status"], 404);
 
-    let client = Client::new();
-    let response = client.get("http://localhost:3000/customers")
+    let response = app.client.get(app.url(&format!("/customers/by-email/{}", first_email)))
         .send()
         .await
         .unwrap();
+    assert_eq!(response.status(), 404);
 
+    // A valid batch applies every operation
+    let response = app.client.post(app.url("/customers:batch"))
+        .json(&json!({
+            "mode": "atomic",
+            "operations": [
+                { "op": "create", "name": "Batch First", "email": first_email },
+                { "op": "create", "name": "Batch Second", "email": second_email },
+                { "op": "update", "id": existing_id, "name": "Batch Updated", "email": unique_email("batch.updated") }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
     assert_eq!(response.status(), 200);
     let body: serde_json::Value = response.json().await.unwrap();
-    assert!(body.as_array().unwrap().iter().any(|c| c["id"] == customer_id));
+    assert_eq!(body["succeeded"], 3);
+    assert_eq!(body["results"][0]["status"], 201);
+    assert_eq!(body["results"][2]["status"], 200);
+    let first_id = body["results"][0]["id"].as_str().unwrap().to_string();
+    let second_id = body["results"][1]["id"].as_str().unwrap().to_string();
+
+    let response = app.client.get(app.url(&format!("/customers/{}", existing_id)))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["name"], "Batch Updated");
+
+    // Emails already in use are reported against the operation that reused them
+    let response = app.client.post(app.url("/customers:batch"))
+        .json(&json!({
+            "operations": [
+                { "op": "create", "name": "Duplicate", "email": first_email.to_uppercase() }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["results"][0]["error"]["existing_id"], first_id.as_str());
+
+    let response = app.client.post(app.url("/customers:batch"))
+        .json(&json!({
+            "operations": [
+                { "op": "delete", "id": first_id },
+                { "op": "delete", "id": second_id },
+                { "op": "delete", "id": existing_id }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(app.url(&format!("/customers/{}", first_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+}
 
-    teardown_customer(&customer_id).await;
+#[tokio::test]
+async fn test_batch_best_effort_and_limits() {
+    let app = TestApp::spawn().await;
+    let email = unique_email("batch.best");
+
+    let response = app.client.post(app.url("/customers:batch"))
+        .json(&json!({
+            "mode": "best_effort",
+            "operations": [
+                { "op": "create", "name": "Best Effort", "email": email },
+                { "op": "create", "name": "No Email", "email": "not-an-email" },
+                { "op": "delete", "id": Uuid::new_v4() }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["succeeded"], 1);
+    assert_eq!(body["failed"], 2);
+    assert_eq!(body["results"][0]["status"], 201);
+    assert_eq!(body["results"][1]["status"], 400);
+    assert_eq!(body["results"][2]["status"], 404);
+
+    let operations: Vec<serde_json::Value> = (0..1001)
+        .map(|_| json!({ "op": "delete", "id": Uuid::new_v4() }))
+        .collect();
+    let response = app.client.post(app.url("/customers:batch"))
+        .json(&json!({ "operations": operations }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 413);
+}
+
+#[tokio::test]
+async fn test_import_and_export() {
+    let app = TestApp::spawn().await;
+    let first_email = unique_email("import.first");
+    let second_email = unique_email("import.second");
+
+    // A bad line rejects the whole atomic import
+    let csv = format!("name,email\nImport First,{}\nImport Broken,not-an-email\n", first_email);
+    let response = app.client.post(app.url("/customers/import"))
+        .header("Content-Type", "text/csv")
+        .body(csv.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 422);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 0);
+    assert_eq!(body["errors"][0]["line"], 3);
+
+    // Best effort imports the valid lines
+    let response = app.client.post(app.url("/customers/import?mode=best_effort"))
+        .header("Content-Type", "text/csv")
+        .body(csv)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 1);
+
+    let ndjson = format!(
+        "{}\n\n{}\n",
+        json!({ "name": "Import Second", "email": second_email }),
+        json!({ "name": "Import Again", "email": first_email })
+    );
+    let response = app.client.post(app.url("/customers/import?format=ndjson&mode=best_effort"))
+        .body(ndjson)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 1);
+    assert_eq!(body["errors"][0]["line"], 3);
+
+    // Both customers show up in the export
+    let response = app.client.get(app.url("/customers/export?format=ndjson"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+    let export = response.text().await.unwrap();
+    let exported: Vec<serde_json::Value> = export
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .filter(|customer: &serde_json::Value| customer["email"] == first_email || customer["email"] == second_email)
+        .collect();
+    assert_eq!(exported.len(), 2);
+
+    let response = app.client.get(app.url("/customers/export"))
+        .send()
+        .await
+        .unwrap();
+    let export = response.text().await.unwrap();
+    assert!(export.starts_with("id,name,email\n"));
+    assert!(export.contains(&second_email));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_idempotent_create() {
+    let app = TestApp::spawn_with_database().await;
+    let key = Uuid::new_v4().to_string();
+    let payload = json!({ "name": "Idempotent User", "email": unique_email("idempotent.user") });
+
+    let response = app.client.post(app.url("/customers"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", &key)
+        .json(&payload)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("idempotent-replayed").is_none());
+    let created: serde_json::Value = response.json().await.unwrap();
+
+    // The retry replays the stored response instead of creating a duplicate
+    let response = app.client.post(app.url("/customers"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", &key)
+        .json(&payload)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["idempotent-replayed"], "true");
+    let replayed: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(replayed, created);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_idempotency_key_reused_for_another_request() {
+    let app = TestApp::spawn_with_database().await;
+    let key = Uuid::new_v4().to_string();
+    let response = app.client.post(app.url("/customers"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", &key)
+        .json(&json!({ "name": "First User", "email": unique_email("first.user") }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // The same key cannot be reused for another request
+    let response = app.client.post(app.url("/customers"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", &key)
+        .json(&json!({ "name": "Someone Else", "email": unique_email("someone.else") }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 422);
+    let error: serde_json::Value = response.json().await.unwrap();
+    assert!(error["error"].as_str().unwrap().contains("different request"));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_idempotent_retry_while_in_flight() {
+    let app = TestApp::spawn_with_database().await;
+
+    // A route that holds the first request until the test releases it
+    let entered = Arc::new(Notify::new());
+    let release = Arc::new(Notify::new());
+    let handler = {
+        let (entered, release) = (entered.clone(), release.clone());
+        move || {
+            let (entered, release) = (entered.clone(), release.clone());
+            async move {
+                entered.notify_one();
+                release.notified().await;
+                "done"
+            }
+        }
+    };
+    let slow = Router::new()
+        .route("/slow", post(handler))
+        .layer(middleware::from_fn_with_state(
+            Idempotency::new(app.state(), &test_config(Some(&std::env::var("TEST_DATABASE_URL").unwrap()))),
+            idempotency::idempotency,
+        ));
+    let slow = TestApp::serve(slow).await;
+
+    let first = tokio::spawn({
+        let request = slow.client.post(slow.url("/slow")).bearer_auth("idempotency-client").header("Idempotency-Key", "in-flight");
+        async move { request.send().await.unwrap() }
+    });
+    entered.notified().await;
+
+    // The retry races the first request, which still holds the key
+    let response = slow.client.post(slow.url("/slow"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", "in-flight")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+
+    release.notify_one();
+    let response = first.await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "done");
+
+    // Once finished, the response is replayed
+    let response = slow.client.post(slow.url("/slow"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", "in-flight")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["idempotent-replayed"], "true");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_idempotency_keys_are_not_shared() {
+    let app = TestApp::spawn_with_database().await;
+    let key = Uuid::new_v4().to_string();
+    let payload = json!({ "name": "Shared Key", "company_name": "Shared Key Company" });
+    let create = |token: Option<&str>| {
+        let request = app.client.post(app.url("/sellers")).header("Idempotency-Key", &key).json(&payload);
+        match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    };
+
+    let first: serde_json::Value = create(Some("first-client")).send().await.unwrap().json().await.unwrap();
+    // Another credential gets its own namespace, and callers without one get no replays at all
+    for token in [Some("second-client"), None, None] {
+        let response = create(token).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().get("idempotent-replayed").is_none());
+        let created: serde_json::Value = response.json().await.unwrap();
+        assert_ne!(created["id"], first["id"]);
+    }
+    let response = create(Some("first-client")).send().await.unwrap();
+    assert_eq!(response.headers()["idempotent-replayed"], "true");
+}
+
+#[tokio::test]
+async fn test_idempotency_key_on_every_storage_backend() {
+    // Without a database the key is ignored instead of failing the request
+    let app = TestApp::spawn().await;
+    let response = app.client.post(app.url("/sellers"))
+        .bearer_auth("idempotency-client")
+        .header("Idempotency-Key", Uuid::new_v4().to_string())
+        .json(&json!({ "name": "Keyed Seller", "company_name": "Keyed Company" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
 }
@@ -0,0 +1,18 @@
+use uuid::Uuid;
+use super::harness::{test_config, TestApp};
+
+#[tokio::test]
+async fn test_unreachable_database_returns_503() {
+    // Orders have no in-memory store, so they need the database this config never reaches
+    let app = TestApp::serve(axum_web_starter::app(&test_config(None)).await.unwrap()).await;
+
+    let response = app.client.get(app.url(&format!("/v1/orders/{}", Uuid::new_v4()))).send().await.unwrap();
+    assert_eq!(response.status(), 503);
+    assert_eq!(response.headers()["retry-after"], "1");
+    let body: serde_json::Value = response.json().await.unwrap();
+    // The sqlx error stays in the logs
+    assert_eq!(body["error"], "Service temporarily unavailable");
+
+    // The customer API keeps working without it
+    assert_eq!(app.create_customer().await["name"], "Test Customer");
+}
@@ -0,0 +1,62 @@
+use axum::routing::get;
+use axum::Router;
+use axum_web_starter::models::customer::Customer;
+use axum_web_starter::models::seller::Seller;
+use serde_json::json;
+use super::harness::{test_config, unique_email, TestApp};
+
+#[tokio::test]
+async fn test_app_nested_in_another_service() {
+    let api = axum_web_starter::app(&test_config(None)).await.unwrap();
+    let app = TestApp::serve(
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .nest("/sillycat", api),
+    )
+    .await;
+
+    let response = app.client.get(app.url("/health")).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "ok");
+
+    // The typed models decode the embedded API's responses
+    let email = unique_email("embedded");
+    let response = app.client.post(app.url("/sillycat/v1/customers"))
+        .json(&json!({ "name": "Embedded Customer", "email": email }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let customer: Customer = response.json().await.unwrap();
+    assert_eq!(customer.email, email);
+
+    let response = app.client.post(app.url("/sillycat/v1/sellers"))
+        .json(&json!({ "name": "Embedded Seller", "company_name": "Embedded Company" }))
+        .send()
+        .await
+        .unwrap();
+    let seller: Seller = response.json().await.unwrap();
+
+    let response = app.client.get(app.url(&format!("/sillycat/v1/sellers/{}", seller.id))).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let response = app.client.get(app.url("/sillycat/v1/openapi.json")).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    // The legacy aliases point at their successor under the same prefix
+    let response = app.client.get(app.url("/sillycat/sellers")).send().await.unwrap();
+    assert_eq!(
+        response.headers()["link"].to_str().unwrap(),
+        "</sillycat/v1/sellers>; rel=\"successor-version\""
+    );
+}
+
+#[tokio::test]
+async fn test_graphiql_posts_to_the_nested_endpoint() {
+    let mut config = test_config(None);
+    config.app_env = "development".to_string();
+    let api = axum_web_starter::app(&config).await.unwrap();
+    let app = TestApp::serve(Router::new().nest("/sillycat", api)).await;
+
+    let response = app.client.get(app.url("/sillycat/graphql")).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.text().await.unwrap().contains("/sillycat/graphql"));
+}
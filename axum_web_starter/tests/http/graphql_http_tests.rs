@@ -0,0 +1,89 @@
+use serde_json::json;
+use uuid::Uuid;
+use super::harness::{unique_email, TestApp};
+
+async fn graphql(app: &TestApp, query: &str) -> serde_json::Value {
+    let response = app.client.post(app.url("/graphql"))
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_seller_with_products_and_orders() {
+    let app = TestApp::spawn_with_database().await;
+    let seller = app.create_seller_with("GraphQL Seller", "GraphQL Company").await;
+    let seller_id = seller["id"].as_str().unwrap();
+
+    let response = app.client.post(app.url(&format!("/sellers/{}/products", seller_id)))
+        .json(&json!({ "sku": "GQL-1", "title": "Widget", "price": 1299, "currency": "USD", "stock_quantity": 5 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let customer = app.create_customer_with("GraphQL Customer", &unique_email("graphql")).await;
+
+    for _ in 0..2 {
+        let response = app.client.post(app.url("/orders"))
+            .json(&json!({
+                "customer_id": customer["id"],
+                "seller_id": seller_id,
+                "currency": "USD",
+                "items": [{ "sku": "GQL-1", "description": "Widget", "quantity": 1, "unit_price": 1299 }]
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    let body = graphql(&app, &format!(
+        r#"{{ seller(id: "{}") {{ name products {{ sku }} orders(last: 1) {{ totalAmount customer {{ name }} }} }} }}"#,
+        seller_id
+    )).await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    let seller = &body["data"]["seller"];
+    assert_eq!(seller["name"], "GraphQL Seller");
+    assert_eq!(seller["products"][0]["sku"], "GQL-1");
+    assert_eq!(seller["orders"].as_array().unwrap().len(), 1);
+    assert_eq!(seller["orders"][0]["customer"]["name"], "GraphQL Customer");
+
+    let body = graphql(&app, &format!(r#"{{ seller(id: "{}") {{ name }} }}"#, Uuid::new_v4())).await;
+    assert!(body["data"]["seller"].is_null());
+}
+
+#[tokio::test]
+async fn test_query_limits() {
+    // Rejected before anything is loaded
+    let app = TestApp::spawn().await;
+    let body = graphql(
+        &app,
+        "{ sellers { orders { seller { orders { seller { orders { seller { orders { id } } } } } } } } }",
+    ).await;
+    assert!(body["errors"].as_array().is_some_and(|errors| !errors.is_empty()));
+    assert!(body["data"].is_null());
+}
+
+#[tokio::test]
+async fn test_customers_are_paged() {
+    let app = TestApp::spawn().await;
+    for _ in 0..3 {
+        app.create_customer().await;
+    }
+
+    let body = graphql(&app, "{ customers(first: 2) { id } }").await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    assert_eq!(body["data"]["customers"].as_array().unwrap().len(), 2);
+
+    let body = graphql(&app, "{ customers(first: 2, offset: 2) { id } }").await;
+    assert!(body["data"]["customers"].as_array().is_some_and(|customers| !customers.is_empty()));
+
+    // Pages are capped like the REST list
+    let body = graphql(&app, "{ sellers(first: 1000) { id } }").await;
+    assert!(body["errors"].as_array().is_some_and(|errors| !errors.is_empty()));
+}
@@ -0,0 +1,71 @@
+use tonic::Code;
+use uuid::Uuid;
+use super::harness::TestApp;
+
+mod pb {
+    tonic::include_proto!("sillycat.v1");
+}
+
+use pb::customer_service_client::CustomerServiceClient;
+use pb::seller_service_client::SellerServiceClient;
+
+#[tokio::test]
+async fn test_customer_crud_over_grpc() {
+    // gRPC shares the HTTP port of test servers
+    let app = TestApp::spawn().await;
+    let mut client = CustomerServiceClient::connect(app.url("")).await.unwrap();
+    let email = format!("grpc.{}@example.com", Uuid::new_v4());
+
+    let customer = client.create_customer(pb::CreateCustomerRequest {
+        name: "gRPC Customer".to_string(),
+        email: email.clone(),
+    }).await.unwrap().into_inner();
+    assert_eq!(customer.email, email);
+
+    // The same customer is visible through REST
+    let response = app.client.get(app.url(&format!("/customers/{}", customer.id))).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let updated = client.update_customer(pb::UpdateCustomerRequest {
+        id: customer.id.clone(),
+        name: "Renamed".to_string(),
+        email: email.clone(),
+    }).await.unwrap().into_inner();
+    assert_eq!(updated.name, "Renamed");
+
+    // Duplicate emails map to ALREADY_EXISTS with the existing ID, like 409 over REST
+    let status = client.create_customer(pb::CreateCustomerRequest {
+        name: "Duplicate".to_string(),
+        email: email.to_uppercase(),
+    }).await.unwrap_err();
+    assert_eq!(status.code(), Code::AlreadyExists);
+    assert_eq!(status.metadata().get("existing-id").unwrap().to_str().unwrap(), customer.id);
+
+    client.delete_customer(pb::DeleteCustomerRequest { id: customer.id.clone() }).await.unwrap();
+    let status = client.get_customer(pb::GetCustomerRequest { id: customer.id }).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let status = client.get_customer(pb::GetCustomerRequest { id: "not-a-uuid".to_string() }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_seller_crud_over_grpc() {
+    let app = TestApp::spawn().await;
+    let mut client = SellerServiceClient::connect(app.url("")).await.unwrap();
+
+    let seller = client.create_seller(pb::CreateSellerRequest {
+        name: "gRPC Seller".to_string(),
+        company_name: "gRPC Company".to_string(),
+    }).await.unwrap().into_inner();
+
+    let fetched = client.get_seller(pb::GetSellerRequest { id: seller.id.clone() }).await.unwrap().into_inner();
+    assert_eq!(fetched, seller);
+
+    let sellers = client.list_sellers(pb::ListSellersRequest {}).await.unwrap().into_inner().sellers;
+    assert!(sellers.iter().any(|listed| listed.id == seller.id));
+
+    client.delete_seller(pb::DeleteSellerRequest { id: seller.id.clone() }).await.unwrap();
+    let status = client.get_seller(pb::GetSellerRequest { id: seller.id }).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
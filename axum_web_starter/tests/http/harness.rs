@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use axum::Router;
+use axum_web_starter::config::AppConfig;
+use axum_web_starter::state::AppState;
+use axum_web_starter::{database, worker};
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// A database created for one test and dropped with it
+struct TestDatabase {
+    admin_url: String,
+    name: String,
+    url: String,
+}
+
+impl TestDatabase {
+    async fn create(admin_url: &str) -> TestDatabase {
+        let name = format!("test_{}", Uuid::new_v4().simple());
+        let mut connection = PgConnection::connect(admin_url).await.unwrap();
+        sqlx::query(&format!("CREATE DATABASE {}", name)).execute(&mut connection).await.unwrap();
+
+        let mut url = Url::parse(admin_url).unwrap();
+        url.set_path(&name);
+        let pool = PgPool::connect(url.as_str()).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        database::create_search_indexes(&pool).await.unwrap();
+        pool.close().await;
+
+        TestDatabase { admin_url: admin_url.to_string(), name, url: url.to_string() }
+    }
+
+    /// Drop the database once the app has closed its pool. PostgreSQL refuses to drop a database
+    /// others are connected to, so FORCE ends any connection left; CockroachDB rejects FORCE and
+    /// needs CASCADE to drop a database that still has tables.
+    async fn drop_database(self) {
+        let result = async {
+            let mut connection = PgConnection::connect(&self.admin_url).await?;
+            let version: String = sqlx::query_scalar("SELECT version()").fetch_one(&mut connection).await?;
+            let statement = if version.contains("CockroachDB") {
+                format!("DROP DATABASE IF EXISTS {} CASCADE", self.name)
+            } else {
+                format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name)
+            };
+            sqlx::query(&statement).execute(&mut connection).await
+        }
+        .await;
+        if let Err(e) = result {
+            eprintln!("Failed to drop test database {}: {}", self.name, e);
+        }
+    }
+}
+
+/// A private copy of the application for one test, served in process on a free port and
+/// stopped when dropped, so tests run in parallel without a server started by hand
+pub struct TestApp {
+    pub client: Client,
+    port: u16,
+    server: JoinHandle<()>,
+    workers: Vec<JoinHandle<()>>,
+    state: Option<Arc<AppState>>,
+    database: Option<TestDatabase>,
+}
+
+/// How long a stopped test app waits for its connections before dropping its database anyway
+const POOL_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Bearer token of the `/admin` routes of test servers
+pub const ADMIN_TOKEN: &str = "test-admin-token";
+
+/// The settings of a test server: in-memory cache, no background servers, and in-memory
+/// customers and sellers unless a database URL is given
+pub fn test_config(database_url: Option<&str>) -> AppConfig {
+    let mut vars = HashMap::from([
+        ("CACHE_BACKEND", "memory"),
+        ("REDIS_URL", "redis://127.0.0.1:1"),
+        ("GRPC_PORT", "0"),
+        ("JOB_WORKERS", "0"),
+        ("ADMIN_TOKEN", ADMIN_TOKEN),
+    ]);
+    match database_url {
+        Some(database_url) => vars.extend([("STORAGE_BACKEND", "postgres"), ("DATABASE_URL", database_url)]),
+        // Nothing listens there, so a route that needs the database fails instead of hanging
+        None => vars.extend([
+            ("STORAGE_BACKEND", "memory"),
+            ("DATABASE_URL", "postgresql://root@127.0.0.1:1/unused"),
+            ("DB_ACQUIRE_TIMEOUT_SECS", "1"),
+        ]),
+    }
+    AppConfig::from_vars(|name| vars.get(name).map(|value| value.to_string())).unwrap()
+}
+
+impl TestApp {
+    /// A server backed by a fresh database when `TEST_DATABASE_URL` is set, otherwise by
+    /// in-memory customers and sellers
+    pub async fn spawn() -> TestApp {
+        Self::spawn_configured(|_| {}).await
+    }
+
+    /// Like `spawn`, with settings changed from `test_config`
+    pub async fn spawn_configured(configure: impl FnOnce(&mut AppConfig)) -> TestApp {
+        let database = match std::env::var("TEST_DATABASE_URL") {
+            Ok(admin_url) => Some(TestDatabase::create(&admin_url).await),
+            Err(_) => None,
+        };
+        Self::start(database, configure, |app| app).await
+    }
+
+    /// A server backed by a fresh database, for routes without an in-memory store. Tests calling
+    /// it are `#[ignore = "needs TEST_DATABASE_URL"]`, so plain `cargo test` reports them as
+    /// ignored; run them with `TEST_DATABASE_URL=... cargo test -- --include-ignored`.
+    pub async fn spawn_with_database() -> TestApp {
+        Self::spawn_with_database_configured(|_| {}).await
+    }
+
+    /// Like `spawn_with_database`, with settings changed from `test_config`
+    pub async fn spawn_with_database_configured(configure: impl FnOnce(&mut AppConfig)) -> TestApp {
+        let admin_url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set for this test");
+        Self::start(Some(TestDatabase::create(&admin_url).await), configure, |app| app).await
+    }
+
+    /// Like `spawn_with_database`, with the app wrapped by `wrap`, such as in a layer inserting
+    /// the `Principal` a client certificate would
+    pub async fn spawn_with_database_wrapped(wrap: impl FnOnce(Router) -> Router) -> TestApp {
+        let admin_url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set for this test");
+        Self::start(Some(TestDatabase::create(&admin_url).await), |_| {}, wrap).await
+    }
+
+    /// A database-backed server with a job worker polling often
+    pub async fn spawn_with_worker() -> TestApp {
+        Self::spawn_with_database_configured(|config| {
+            config.job_workers = 1;
+            config.job_poll_interval = Duration::from_millis(100);
+        })
+        .await
+    }
+
+    /// Serve the app, with `JOB_WORKERS` job workers when a test sets it
+    async fn start(
+        database: Option<TestDatabase>,
+        configure: impl FnOnce(&mut AppConfig),
+        wrap: impl FnOnce(Router) -> Router,
+    ) -> TestApp {
+        let mut config = test_config(database.as_ref().map(|database| database.url.as_str()));
+        configure(&mut config);
+        let app_state = Arc::new(AppState::from_config(&config).await.unwrap());
+        let workers = worker::spawn_workers(app_state.clone(), config.job_workers, config.job_poll_interval);
+        let app = axum_web_starter::router(app_state.clone(), &config).unwrap();
+        let mut test_app = Self::serve(wrap(app)).await;
+        test_app.workers = workers;
+        test_app.state = Some(app_state);
+        test_app.database = database;
+        test_app
+    }
+
+    /// Serve any router, such as the app nested into another one
+    pub async fn serve(app: Router) -> TestApp {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        TestApp { client: Client::new(), port, server, workers: Vec::new(), state: None, database: None }
+    }
+
+    /// The state behind the app, to serve extra routes over the same database and cache
+    pub fn state(&self) -> Arc<AppState> {
+        self.state.clone().expect("only apps started by TestApp::spawn* have a state")
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", self.port, path)
+    }
+
+    /// Poll the job until a worker has finished with it
+    pub async fn wait_for_job(&self, job_id: &str) -> Value {
+        for _ in 0..100 {
+            let response = self.client.get(self.url(&format!("/jobs/{}", job_id))).send().await.unwrap();
+            assert_eq!(response.status(), 200);
+            let job: Value = response.json().await.unwrap();
+            if job["status"] == "succeeded" || job["status"] == "dead" {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("Job {} did not finish in time", job_id);
+    }
+
+    /// Create a customer with a unique email and return it
+    pub async fn create_customer(&self) -> Value {
+        self.create_customer_with("Test Customer", &unique_email("customer")).await
+    }
+
+    pub async fn create_customer_with(&self, name: &str, email: &str) -> Value {
+        let response = self.client.post(self.url("/customers"))
+            .json(&json!({ "name": name, "email": email }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200, "creating customer {}", email);
+        response.json().await.unwrap()
+    }
+
+    /// Create a seller and return it
+    pub async fn create_seller(&self) -> Value {
+        self.create_seller_with("Test Seller", "Test Company").await
+    }
+
+    pub async fn create_seller_with(&self, name: &str, company_name: &str) -> Value {
+        let response = self.client.post(self.url("/sellers"))
+            .json(&json!({ "name": name, "company_name": company_name }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200, "creating seller {}", name);
+        response.json().await.unwrap()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        self.server.abort();
+        self.workers.iter().for_each(JoinHandle::abort);
+        let pool = self.state.take().map(|state| state.db_pool.clone());
+        if let Some(database) = self.database.take() {
+            // Drop cannot await, so the database is dropped on a runtime of its own. Connections
+            // still checked out by the aborted server are given a moment to come back.
+            let _ = std::thread::spawn(move || {
+                tokio::runtime::Runtime::new().unwrap().block_on(async move {
+                    if let Some(pool) = pool {
+                        if tokio::time::timeout(POOL_CLOSE_TIMEOUT, pool.close()).await.is_err() {
+                            eprintln!("Closing the pool of test database {} timed out", database.name);
+                        }
+                    }
+                    database.drop_database().await
+                })
+            })
+            .join();
+        }
+    }
+}
+
+pub fn unique_email(prefix: &str) -> String {
+    format!("{}.{}@example.com", prefix, Uuid::new_v4())
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use axum_web_starter::config::AppConfig;
+use serde_json::Value;
+use super::harness::TestApp;
+
+/// In-memory customers in front of a Redis nobody listens on
+async fn spawn_without_redis() -> TestApp {
+    let vars = HashMap::from([
+        ("CACHE_BACKEND", "redis"),
+        ("REDIS_URL", "redis://127.0.0.1:1"),
+        ("CACHE_FAILURE_THRESHOLD", "2"),
+        ("CACHE_OPEN_SECS", "60"),
+        ("STORAGE_BACKEND", "memory"),
+        ("DATABASE_URL", "postgresql://root@127.0.0.1:1/unused"),
+        ("GRPC_PORT", "0"),
+        ("JOB_WORKERS", "0"),
+    ]);
+    let config = AppConfig::from_vars(|name| vars.get(name).map(|value| value.to_string())).unwrap();
+    TestApp::serve(axum_web_starter::app(&config).await.unwrap()).await
+}
+
+#[tokio::test]
+async fn test_health_with_working_cache() {
+    let app = TestApp::spawn().await;
+    let response = app.client.get(app.url("/health")).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let health: Value = response.json().await.unwrap();
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["cache"]["state"], "closed");
+}
+
+#[tokio::test]
+async fn test_cache_circuit_opens_when_redis_is_down() {
+    let app = spawn_without_redis().await;
+    let customer = app.create_customer().await;
+    let url = app.url(&format!("/customers/{}", customer["id"].as_str().unwrap()));
+
+    // Reads keep working from the store while the cache fails, then skip it
+    for _ in 0..3 {
+        let response = app.client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    let health: Value = app.client.get(app.url("/health")).send().await.unwrap().json().await.unwrap();
+    assert_eq!(health["status"], "degraded");
+    assert_eq!(health["cache"]["state"], "open");
+    assert_eq!(health["cache"]["trips"], 1);
+    assert!(health["cache"]["skipped"].as_u64().unwrap() > 0);
+
+    // Skipped reads are not counted as misses
+    app.client.get(&url).send().await.unwrap();
+    let after: Value = app.client.get(app.url("/health")).send().await.unwrap().json().await.unwrap();
+    assert_eq!(after["cache"]["misses"], health["cache"]["misses"]);
+    assert!(after["cache"]["skipped"].as_u64().unwrap() > health["cache"]["skipped"].as_u64().unwrap());
+
+    let metrics = app.client.get(app.url("/metrics")).send().await.unwrap().text().await.unwrap();
+    assert!(metrics.contains("\ncache_circuit_state 2\n"), "{}", metrics);
+    assert!(metrics.contains("\ncache_circuit_trips_total 1\n"), "{}", metrics);
+}
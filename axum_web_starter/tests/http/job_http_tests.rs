@@ -0,0 +1,183 @@
+use axum::extract::Request;
+use axum::middleware::{self, Next};
+use axum_web_starter::auth::Principal;
+use serde_json::json;
+use uuid::Uuid;
+use super::harness::{TestApp, ADMIN_TOKEN};
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_submit_and_poll_job() {
+    let app = TestApp::spawn_with_worker().await;
+    let response = app.client.post(app.url("/jobs"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({ "kind": "warm_customer_cache" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let job: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(job["status"], "queued");
+    assert_eq!(location, format!("/jobs/{}", job["id"].as_str().unwrap()));
+
+    let job = app.wait_for_job(job["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["progress"], 100);
+    assert_eq!(job["attempts"], 1);
+
+    // Only dead-lettered jobs can be retried
+    let response = app.client.post(app.url(&format!("/jobs/{}/retry", job["id"].as_str().unwrap())))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_invalid_jobs_are_rejected() {
+    let app = TestApp::spawn_with_database().await;
+    let response = app.client.post(app.url("/jobs"))
+        .json(&json!({ "kind": "import_customers", "payload": { "format": "csv" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = app.client.post(app.url("/jobs"))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({ "kind": "purge_customer_cache", "timeout_seconds": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = app.client.get(app.url(&format!("/jobs/{}", Uuid::new_v4())))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_cache_jobs_are_for_admins() {
+    let app = TestApp::spawn().await;
+    for kind in ["warm_customer_cache", "warm_seller_cache", "purge_customer_cache"] {
+        let response = app.client.post(app.url("/jobs"))
+            .json(&json!({ "kind": kind }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401, "{}", kind);
+
+        let response = app.client.post(app.url("/jobs"))
+            .bearer_auth("not-the-admin-token")
+            .json(&json!({ "kind": kind }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403, "{}", kind);
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_background_import() {
+    let app = TestApp::spawn_with_worker().await;
+    let email = format!("background.import.{}@example.com", Uuid::new_v4());
+    let response = app.client.post(app.url("/customers/import?background=true"))
+        .header("Content-Type", "text/csv")
+        .body(format!("name,email\nBackground Import,{}\n", email))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    let job: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(job["kind"], "import_customers");
+
+    let job = app.wait_for_job(job["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["result"]["imported"], 1);
+
+    let response = app.client.get(app.url(&format!("/customers/by-email/{}", email)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let customer: serde_json::Value = response.json().await.unwrap();
+    let response = app.client.delete(app.url(&format!("/customers/{}", customer["id"].as_str().unwrap())))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_jobs_are_scoped_to_their_submitter() {
+    // Requests carrying X-Test-Principal are made on behalf of it, as with a client certificate
+    let app = TestApp::spawn_with_database_wrapped(|app| {
+        app.layer(middleware::from_fn(|mut request: Request, next: Next| async move {
+            let principal = request.headers().get("x-test-principal").and_then(|value| value.to_str().ok());
+            if let Some(principal) = principal.map(str::to_string) {
+                request.extensions_mut().insert(Principal(principal));
+            }
+            next.run(request).await
+        }))
+    })
+    .await;
+    let response = app.client.post(app.url("/customers/import?background=true"))
+        .header("Content-Type", "text/csv")
+        .header("X-Test-Principal", "importer")
+        .body("name,email\nScoped Import,scoped.import@example.com\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    let job: serde_json::Value = response.json().await.unwrap();
+    // The upload stays in the database
+    assert!(job.get("payload").is_none());
+    let job_url = app.url(&format!("/jobs/{}", job["id"].as_str().unwrap()));
+
+    let response = app.client.get(&job_url).header("X-Test-Principal", "importer").send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body.get("payload").is_none());
+
+    let response = app.client.get(&job_url).header("X-Test-Principal", "someone-else").send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = app.client.get(&job_url).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = app.client.get(&job_url).bearer_auth(ADMIN_TOKEN).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Listing and retrying are for admins
+    let response = app.client.get(app.url("/jobs")).header("X-Test-Principal", "importer").send().await.unwrap();
+    assert_eq!(response.status(), 403);
+    let response = app.client.get(app.url("/jobs")).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+    let response = app.client.get(app.url("/jobs")).bearer_auth(ADMIN_TOKEN).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let jobs: serde_json::Value = response.json().await.unwrap();
+    assert!(jobs.as_array().unwrap().iter().all(|job| job.get("payload").is_none()));
+    let response = app.client.post(format!("{}/retry", job_url)).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_background_imports_are_capped() {
+    let app = TestApp::spawn_with_database().await;
+    let row = "Large Import,large.import@example.com\n";
+    let body = format!("name,email\n{}", row.repeat(8 * 1024 * 1024 / row.len() + 1));
+
+    let response = app.client.post(app.url("/customers/import?background=true"))
+        .header("Content-Type", "text/csv")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 413);
+}
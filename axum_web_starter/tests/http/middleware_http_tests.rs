@@ -0,0 +1,56 @@
+use super::harness::TestApp;
+
+#[tokio::test]
+async fn test_security_headers() {
+    let app = TestApp::spawn().await;
+    let response = app.client.get(app.url("/v1/sellers"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["x-content-type-options"], "nosniff");
+    assert!(response.headers().contains_key("referrer-policy"));
+}
+
+#[tokio::test]
+async fn test_large_responses_are_compressed() {
+    let app = TestApp::spawn().await;
+    let response = app.client.get(app.url("/v1/openapi.json"))
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+
+    let response = app.client.get(app.url("/"))
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn test_cors_preflight() {
+    let app = TestApp::spawn_configured(|config| {
+        config.cors_allowed_origins = vec!["http://localhost:5173".to_string()];
+    })
+    .await;
+    let response = app.client.request(reqwest::Method::OPTIONS, app.url("/v1/customers"))
+        .header("origin", "http://localhost:5173")
+        .header("access-control-request-method", "POST")
+        .header("access-control-request-headers", "content-type, idempotency-key, x-read-primary")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["access-control-allow-origin"], "http://localhost:5173");
+
+    let response = app.client.get(app.url("/v1/customers"))
+        .header("origin", "https://evil.example.com")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+}
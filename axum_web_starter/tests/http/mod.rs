@@ -1,2 +1,20 @@
+mod harness;
 mod customer_http_tests;
 mod seller_http_tests;
+mod order_http_tests;
+mod product_http_tests;
+mod address_http_tests;
+mod job_http_tests;
+mod search_http_tests;
+mod graphql_http_tests;
+mod grpc_http_tests;
+mod versioning_http_tests;
+mod middleware_http_tests;
+mod tls_http_tests;
+mod embedding_http_tests;
+mod database_http_tests;
+mod health_http_tests;
+mod admin_http_tests;
+mod cache_http_tests;
+mod unit_of_work_http_tests;
+mod read_replica_http_tests;
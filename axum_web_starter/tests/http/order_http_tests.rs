@@ -0,0 +1,173 @@
+use axum::Extension;
+use axum_web_starter::auth::Principal;
+use serde_json::json;
+use uuid::Uuid;
+use super::harness::{unique_email, TestApp};
+
+async fn setup_customer(app: &TestApp) -> String {
+    let customer = app.create_customer_with("Order Customer", &unique_email("order.customer")).await;
+    customer["id"].as_str().unwrap().to_string()
+}
+
+async fn setup_seller(app: &TestApp) -> String {
+    let seller = app.create_seller_with("Order Seller", "Order Company").await;
+    seller["id"].as_str().unwrap().to_string()
+}
+
+async fn setup_order(app: &TestApp, customer_id: &str, seller_id: &str) -> serde_json::Value {
+    let response = app.client.post(app.url("/orders"))
+        .json(&json!({
+            "customer_id": customer_id,
+            "seller_id": seller_id,
+            "currency": "USD",
+            "items": [
+                { "sku": "SKU-1", "description": "Widget", "quantity": 2, "unit_price": 1299 },
+                { "sku": "SKU-2", "description": "Gadget", "quantity": 1, "unit_price": 500 }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_create_and_list_orders() {
+    let app = TestApp::spawn_with_database().await;
+    let customer_id = setup_customer(&app).await;
+    let seller_id = setup_seller(&app).await;
+
+    let order = setup_order(&app, &customer_id, &seller_id).await;
+    assert_eq!(order["status"], "pending");
+    assert_eq!(order["total_amount"], 3098);
+    assert_eq!(order["items"].as_array().unwrap().len(), 2);
+
+    // Test Get
+    let response = app.client.get(app.url(&format!("/orders/{}", order["id"].as_str().unwrap())))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Test nested lists
+    for path in [format!("customers/{}/orders", customer_id), format!("sellers/{}/orders", seller_id)] {
+        let response = app.client.get(app.url(&format!("/{}", path)))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert!(body.as_array().unwrap().iter().any(|o| o["id"] == order["id"]));
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_delete_with_open_orders_is_rejected() {
+    let app = TestApp::spawn_with_database().await;
+    let customer_id = setup_customer(&app).await;
+    let seller_id = setup_seller(&app).await;
+    setup_order(&app, &customer_id, &seller_id).await;
+
+    let response = app.client.delete(app.url(&format!("/customers/{}", customer_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+
+    let response = app.client.delete(app.url(&format!("/sellers/{}", seller_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_order_lifecycle_transitions() {
+    let app = TestApp::spawn_with_database().await;
+    let customer_id = setup_customer(&app).await;
+    let seller_id = setup_seller(&app).await;
+    let order = setup_order(&app, &customer_id, &seller_id).await;
+    let order_id = order["id"].as_str().unwrap();
+
+    for (action, status) in [("pay", "paid"), ("ship", "shipped")] {
+        let response = app.client.post(app.url(&format!("/orders/{}/{}", order_id, action)))
+            .json(&json!({ "actor": "test-suite" }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["status"], status);
+    }
+
+    // Shipped orders can no longer be cancelled
+    let response = app.client.post(app.url(&format!("/orders/{}/cancel", order_id)))
+        .json(&json!({ "actor": "test-suite" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+
+    let response = app.client.get(app.url(&format!("/orders/{}/history", order_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let history = body.as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["from_status"], "pending");
+    assert_eq!(history[1]["to_status"], "shipped");
+    assert_eq!(history[1]["actor"], "test-suite");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_transition_actor_is_the_authenticated_principal() {
+    let app = TestApp::spawn_with_database_wrapped(|app| app.layer(Extension(Principal("ops".to_string())))).await;
+    let customer_id = setup_customer(&app).await;
+    let seller_id = setup_seller(&app).await;
+    let order = setup_order(&app, &customer_id, &seller_id).await;
+    let order_id = order["id"].as_str().unwrap();
+
+    // An authenticated caller needs no actor and cannot record another one
+    let response = app.client.post(app.url(&format!("/orders/{}/pay", order_id)))
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let response = app.client.post(app.url(&format!("/orders/{}/ship", order_id)))
+        .json(&json!({ "actor": "someone-else" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(app.url(&format!("/orders/{}/history", order_id)))
+        .send()
+        .await
+        .unwrap();
+    let history: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(history[0]["actor"], "ops");
+    assert_eq!(history[1]["actor"], "ops");
+}
+
+#[tokio::test]
+async fn test_anonymous_transition_requires_an_actor() {
+    // Rejected before the order is loaded
+    let app = TestApp::spawn().await;
+    let response = app.client.post(app.url(&format!("/orders/{}/pay", Uuid::new_v4())))
+        .json(&json!({ "actor": "  " }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,168 @@
+use serde_json::json;
+use uuid::Uuid;
+use super::harness::{unique_email, TestApp};
+
+async fn setup_seller(app: &TestApp) -> String {
+    let seller = app.create_seller_with("Catalog Seller", "Catalog Company").await;
+    seller["id"].as_str().unwrap().to_string()
+}
+
+async fn setup_product(app: &TestApp, seller_id: &str, sku: &str, stock_quantity: i32) -> String {
+    let response = app.client.post(app.url(&format!("/sellers/{}/products", seller_id)))
+        .json(&json!({
+            "sku": sku,
+            "title": format!("Product {}", sku),
+            "price": 1299,
+            "currency": "USD",
+            "stock_quantity": stock_quantity
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_crud_and_search_operations() {
+    let app = TestApp::spawn_with_database().await;
+    let seller_id = setup_seller(&app).await;
+    let sku = format!("SKU-{}", Uuid::new_v4());
+    let product_id = setup_product(&app, &seller_id, &sku, 5).await;
+
+    // Duplicate SKU for the same seller
+    let response = app.client.post(app.url(&format!("/sellers/{}/products", seller_id)))
+        .json(&json!({ "sku": sku, "title": "Dup", "price": 1, "currency": "USD", "stock_quantity": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+
+    // Test Update, then Get must not serve the stale cached copy
+    let response = app.client.put(app.url(&format!("/products/{}", product_id)))
+        .json(&json!({ "sku": sku, "title": "Renamed", "price": 999, "currency": "USD", "stock_quantity": 7 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["title"], "Renamed");
+    assert_eq!(body["stock_quantity"], 7);
+
+    // Test Search
+    let response = app.client.get(app.url(&format!("/products?q={}&seller_id={}", sku, seller_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body.as_array().unwrap().iter().any(|p| p["id"] == product_id));
+
+    // Test Delete
+    let response = app.client.delete(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.get(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_orders_reserve_stock() {
+    let app = TestApp::spawn_with_database().await;
+    let seller_id = setup_seller(&app).await;
+    let product_id = setup_product(&app, &seller_id, &format!("SKU-{}", Uuid::new_v4()), 3).await;
+
+    let customer = app.create_customer_with("Stock Customer", &unique_email("stock.customer")).await;
+
+    let place_order = |quantity: i32| {
+        app.client.post(app.url("/orders"))
+            .json(&json!({
+                "customer_id": customer["id"],
+                "seller_id": seller_id,
+                "currency": "USD",
+                "items": [{ "product_id": product_id, "quantity": quantity }]
+            }))
+            .send()
+    };
+
+    let response = place_order(2).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let order: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(order["total_amount"], 2598);
+
+    let response = app.client.get(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["stock_quantity"], 1);
+
+    // Only one unit left
+    let response = place_order(2).await.unwrap();
+    assert_eq!(response.status(), 409);
+
+    // Refunded goods are returned to stock
+    for action in ["pay", "refund"] {
+        let response = app.client.post(app.url(&format!("/orders/{}/{}", order["id"].as_str().unwrap(), action)))
+            .json(&json!({ "actor": "test-suite" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+    let response = app.client.get(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["stock_quantity"], 3);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_deleting_the_seller_evicts_its_products() {
+    let app = TestApp::spawn_with_database().await;
+    let seller_id = setup_seller(&app).await;
+    let product_id = setup_product(&app, &seller_id, &format!("SKU-{}", Uuid::new_v4()), 3).await;
+
+    // Cache the product
+    let response = app.client.get(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.client.delete(app.url(&format!("/sellers/{}", seller_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // The product went with the seller, and the cached copy with the commit
+    let response = app.client.get(app.url(&format!("/products/{}", product_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let response = app.client.delete(app.url(&format!("/sellers/{}", seller_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+}
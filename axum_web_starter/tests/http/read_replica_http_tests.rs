@@ -0,0 +1,153 @@
+use axum::extract::{Request, State};
+use axum::middleware::{self, Next};
+use axum::routing::{get, post};
+use axum::Router;
+use axum_web_starter::auth::Principal;
+use axum_web_starter::handlers::seller_handler::seller_cache_key;
+use axum_web_starter::read_replica::{self, ReadRouter, ReadTarget};
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use tokio::time::{sleep, Duration};
+use uuid::Uuid;
+use super::harness::TestApp;
+
+/// Nothing listens there, so a replica at this URL never passes a health check
+const UNREACHABLE: &str = "postgresql://root@127.0.0.1:1/unused";
+
+/// Answer with where the reads of the request went
+async fn target(State(reads): State<ReadRouter>) -> String {
+    format!("{:?}", reads.target())
+}
+
+/// Authenticate requests as the principal named in `X-Test-Principal`, as a client certificate would
+async fn authenticate(mut request: Request, next: Next) -> axum::response::Response {
+    let principal = request.headers().get("x-test-principal").and_then(|value| value.to_str().ok());
+    if let Some(principal) = principal.map(|principal| Principal(principal.to_string())) {
+        request.extensions_mut().insert(principal);
+    }
+    next.run(request).await
+}
+
+async fn serve(reads: ReadRouter) -> TestApp {
+    let app = Router::new()
+        .route("/target", get(target).post(target))
+        .route("/graphql", post(target))
+        .layer(middleware::from_fn_with_state(reads.clone(), read_replica::route_reads))
+        .layer(middleware::from_fn(authenticate))
+        .with_state(reads);
+    TestApp::serve(app).await
+}
+
+async fn read_target(app: &TestApp, method: reqwest::Method, path: &str, headers: &[(&str, &str)]) -> String {
+    let mut request = app.client.request(method, app.url(path));
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    response.text().await.unwrap()
+}
+
+/// Serve a router whose replica passed its health check, with both pools on the test database
+async fn serve_healthy_replica() -> TestApp {
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set for this test");
+    let pool = PgPoolOptions::new().connect_lazy(&url).unwrap();
+    let reads = ReadRouter::with_replica(pool.clone(), pool, Duration::from_secs(60));
+    reads.spawn_health_check(Duration::from_millis(50));
+    let app = serve(reads).await;
+    wait_for_replica(&app).await;
+    app
+}
+
+async fn wait_for_replica(app: &TestApp) {
+    for _ in 0..100 {
+        if read_target(app, reqwest::Method::GET, "/target", &[]).await == "Replica" {
+            return;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    panic!("replica never became healthy");
+}
+
+#[tokio::test]
+async fn test_unhealthy_replica_reads_from_primary() {
+    let pool = PgPoolOptions::new().connect_lazy(UNREACHABLE).unwrap();
+    let replica = PgPoolOptions::new().connect_lazy(UNREACHABLE).unwrap();
+    let app = serve(ReadRouter::with_replica(pool, replica, Duration::from_secs(60))).await;
+
+    // No health check has passed yet
+    assert_eq!(read_target(&app, reqwest::Method::GET, "/target", &[]).await, "Primary");
+}
+
+#[tokio::test]
+async fn test_reads_outside_requests_use_primary() {
+    let pool = PgPoolOptions::new().connect_lazy(UNREACHABLE).unwrap();
+    let reads = ReadRouter::with_replica(pool.clone(), pool, Duration::from_secs(60));
+    assert_eq!(reads.target(), ReadTarget::Primary);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_reads_go_to_replica_unless_primary_is_needed() {
+    let app = serve_healthy_replica().await;
+    assert_eq!(read_target(&app, reqwest::Method::GET, "/target", &[]).await, "Replica");
+
+    // Writes and requests asking for it read from the primary
+    assert_eq!(read_target(&app, reqwest::Method::POST, "/target", &[]).await, "Primary");
+    assert_eq!(
+        read_target(&app, reqwest::Method::GET, "/target", &[("x-read-primary", "true")]).await,
+        "Primary"
+    );
+
+    // GraphQL only reads
+    assert_eq!(read_target(&app, reqwest::Method::POST, "/graphql", &[]).await, "Replica");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_principals_read_their_own_writes() {
+    let app = serve_healthy_replica().await;
+    let ops = [("x-test-principal", "ops")];
+    let billing = [("x-test-principal", "billing")];
+
+    read_target(&app, reqwest::Method::POST, "/target", &ops).await;
+    assert_eq!(read_target(&app, reqwest::Method::GET, "/target", &ops).await, "Primary");
+    // Other principals are not held back by it
+    assert_eq!(read_target(&app, reqwest::Method::GET, "/target", &billing).await, "Replica");
+
+    // Anonymous callers share a principal, so their writes pin nobody to the primary
+    read_target(&app, reqwest::Method::POST, "/target", &[]).await;
+    assert_eq!(read_target(&app, reqwest::Method::GET, "/target", &[]).await, "Replica");
+
+    // A GraphQL query is no write either
+    read_target(&app, reqwest::Method::POST, "/graphql", &billing).await;
+    assert_eq!(read_target(&app, reqwest::Method::GET, "/target", &billing).await, "Replica");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_replica_reads_are_not_cached() {
+    let app = TestApp::spawn_with_database_configured(|config| {
+        config.database_read_url = Some(config.database_url.clone());
+        config.replica_health_check_interval = Duration::from_millis(50);
+    })
+    .await;
+    wait_for_replica(&serve(app.state().reads.clone()).await).await;
+    let seller = app.create_seller().await;
+    let id: Uuid = seller["id"].as_str().unwrap().parse().unwrap();
+    let cache_key = seller_cache_key(id);
+    // Creating the seller cached it, so start from an empty cache
+    app.state().cache.delete(&cache_key).await.unwrap();
+
+    let response = app.client.get(app.url(&format!("/sellers/{}", id))).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(app.state().cache.get::<Value>(&cache_key).await.is_none());
+
+    let response = app.client.get(app.url(&format!("/sellers/{}", id)))
+        .header("x-read-primary", "true")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(app.state().cache.get::<Value>(&cache_key).await, Some(seller));
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+use uuid::Uuid;
+use super::harness::TestApp;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_search_customers_and_sellers() {
+    let app = TestApp::spawn_with_database().await;
+    let marker = Uuid::new_v4().simple().to_string()[..10].to_string();
+
+    let response = app.client.post(app.url("/customers"))
+        .json(&json!({ "name": format!("Jon {}", marker), "email": format!("jon.{}@gmail.com", marker) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let customer: serde_json::Value = response.json().await.unwrap();
+
+    let response = app.client.post(app.url("/sellers"))
+        .json(&json!({ "name": format!("Seller {}", marker), "company_name": "Gmail Supplies" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Words may be spread over name and email
+    let response = app.client.get(app.url("/search"))
+        .query(&[("q", format!("jon {} gmail", marker))])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["total"].as_i64().unwrap() >= 1);
+    let result = &body["results"][0];
+    assert_eq!(result["type"], "customer");
+    assert_eq!(result["record"]["id"], customer["id"]);
+    assert_eq!(result["highlights"]["name"], format!("<mark>Jon</mark> <mark>{}</mark>", marker));
+
+    // Both kinds come back for a shared term, paged
+    let response = app.client.get(app.url("/search"))
+        .query(&[("q", marker.as_str()), ("limit", "1")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+
+    let response = app.client.get(app.url("/search"))
+        .query(&[("q", marker.as_str()), ("limit", "1"), ("offset", "1")])
+        .send()
+        .await
+        .unwrap();
+    let next: serde_json::Value = response.json().await.unwrap();
+    assert_ne!(next["results"][0]["type"], body["results"][0]["type"]);
+
+    // Past the last page the total is still counted
+    let response = app.client.get(app.url("/search"))
+        .query(&[("q", marker.as_str()), ("limit", "1"), ("offset", "5")])
+        .send()
+        .await
+        .unwrap();
+    let past: serde_json::Value = response.json().await.unwrap();
+    assert!(past["results"].as_array().unwrap().is_empty());
+    assert_eq!(past["total"], 2);
+}
+
+#[tokio::test]
+async fn test_search_rejects_invalid_queries() {
+    // Rejected before the database is queried
+    let app = TestApp::spawn().await;
+    for query in [vec![("q", "  ")], vec![("q", "jon"), ("limit", "0")], vec![("q", "jon"), ("offset", "-1")]] {
+        let response = app.client.get(app.url("/search"))
+            .query(&query)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+}
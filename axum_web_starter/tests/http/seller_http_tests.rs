@@ -1,25 +1,14 @@
-use reqwest::Client;
 use serde_json::json;
+use uuid::Uuid;
+use super::harness::TestApp;
 
-async fn setup_seller() -> String {
-    let client = Client::new();
-    let response = client.post("http://localhost:3000/sellers")
-        .json(&json!({
-            "name": "Setup User",
-            "company_name": "Setup Company"
-        }))
-        .send()
-        .await
-        .unwrap();
-
-    assert_eq!(response.status(), 200);
-    let body: serde_json::Value = response.json().await.unwrap();
-    body["id"].as_str().unwrap().to_string() // Return the created UUID
+async fn setup_seller(app: &TestApp) -> String {
+    let seller = app.create_seller().await;
+    seller["id"].as_str().unwrap().to_string() // Return the created UUID
 }
 
-async fn teardown_seller(seller_id: &str) {
-    let client = Client::new();
-    let response = client.delete(&format!("http://localhost:3000/sellers/{}", seller_id))
+async fn teardown_seller(app: &TestApp, seller_id: &str) {
+    let response = app.client.delete(app.url(&format!("/sellers/{}", seller_id)))
         .send()
         .await
         .unwrap();
@@ -29,11 +18,11 @@ async fn teardown_seller(seller_id: &str) {
 
 #[tokio::test]
 async fn test_crud_operations() {
-    let seller_id = setup_seller().await;
+    let app = TestApp::spawn().await;
+    let seller_id = setup_seller(&app).await;
 
     // Test Update
-    let client = Client::new();
-    let response = client.put(&format!("http://localhost:3000/sellers/{}", seller_id))
+    let response = app.client.put(app.url(&format!("/sellers/{}", seller_id)))
         .json(&json!({
             "name": "Updated User",
             "company_name": "Updated Company"
@@ -48,7 +37,7 @@ async fn test_crud_operations() {
     assert_eq!(body["company_name"], "Updated Company");
 
     // Test Get
-    let response = client.get(&format!("http://localhost:3000/sellers/{}", seller_id))
+    let response = app.client.get(app.url(&format!("/sellers/{}", seller_id)))
         .send()
         .await
         .unwrap();
@@ -58,10 +47,10 @@ async fn test_crud_operations() {
     assert_eq!(body["id"], seller_id);
 
     // Teardown
-    teardown_seller(&seller_id).await;
+    teardown_seller(&app, &seller_id).await;
 
     // Verify Teardown
-    let response = client.get(&format!("http://localhost:3000/sellers/{}", seller_id))
+    let response = app.client.get(app.url(&format!("/sellers/{}", seller_id)))
         .send()
         .await
         .unwrap();
@@ -71,17 +60,168 @@ async fn test_crud_operations() {
 
 #[tokio::test]
 async fn test_list_sellers() {
-    let seller_id = setup_seller().await;
+    let app = TestApp::spawn().await;
+    let seller_id = setup_seller(&app).await;
+
+    let response = app.client.get(app.url("/sellers"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    // Every test has the server to itself, so the list holds only this seller
+    let sellers = body.as_array().unwrap();
+    assert_eq!(sellers.len(), 1);
+    assert_eq!(sellers[0]["id"], seller_id);
+}
+
+#[tokio::test]
+async fn test_batch_operations() {
+    let app = TestApp::spawn().await;
+    let seller_id = setup_seller(&app).await;
+
+    let response = app.client.post(app.url("/sellers:batch"))
+        .json(&json!({
+            "operations": [
+                { "op": "create", "name": "Batch Seller", "company_name": "Batch Company" },
+                { "op": "update", "id": seller_id, "name": "Batch Updated", "company_name": "Updated Company" },
+                { "op": "delete", "id": Uuid::new_v4() }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = app.client.post(app.url("/sellers:batch"))
+        .json(&json!({
+            "operations": [
+                { "op": "create", "name": "Batch Seller", "company_name": "Batch Company" },
+                { "op": "update", "id": seller_id, "name": "Batch Updated", "company_name": "Updated Company" }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["succeeded"], 2);
+    let created_id = body["results"][0]["id"].as_str().unwrap().to_string();
 
-    let client = Client::new();
-    let response = client.get("http://localhost:3000/sellers")
+    let response = app.client.get(app.url(&format!("/sellers/{}", seller_id)))
         .send()
         .await
         .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["name"], "Batch Updated");
 
+    let response = app.client.post(app.url("/sellers:batch"))
+        .json(&json!({
+            "mode": "best_effort",
+            "operations": [
+                { "op": "delete", "id": created_id },
+                { "op": "delete", "id": created_id },
+                { "op": "delete", "id": seller_id }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
     assert_eq!(response.status(), 200);
     let body: serde_json::Value = response.json().await.unwrap();
-    assert!(body.as_array().unwrap().iter().any(|c| c["id"] == seller_id));
+    assert_eq!(body["results"][0]["status"], 200);
+    assert_eq!(body["results"][1]["status"], 400);
+    assert_eq!(body["results"][2]["status"], 200);
+}
+
+#[tokio::test]
+async fn test_import_and_export() {
+    let app = TestApp::spawn().await;
+    let company_name = format!("Import Company {}", Uuid::new_v4());
+
+    let response = app.client.post(app.url("/sellers/import"))
+        .header("Content-Type", "application/x-ndjson")
+        .body(format!("{}\n{{\"name\": \"Missing company\"}}\n", json!({ "name": "Import Seller", "company_name": company_name })))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 422);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["errors"][0]["line"], 2);
+
+    let response = app.client.post(app.url("/sellers/import"))
+        .header("Content-Type", "text/csv")
+        .body(format!("name,company_name\nImport Seller,{}\n", company_name))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 1);
+
+    let response = app.client.get(app.url("/sellers/export?format=csv"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let export = response.text().await.unwrap();
+    let line = export.lines().find(|line| line.ends_with(&company_name)).unwrap();
+    teardown_seller(&app, line.split(',').next().unwrap()).await;
+}
+
+#[tokio::test]
+async fn test_blank_names_are_rejected() {
+    let app = TestApp::spawn().await;
+
+    let response = app.client.post(app.url("/sellers"))
+        .json(&json!({ "name": "  ", "company_name": "Blank Company" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let seller_id = setup_seller(&app).await;
+    let response = app.client.put(app.url(&format!("/sellers/{}", seller_id)))
+        .json(&json!({ "name": "Named Seller", "company_name": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = app.client.post(app.url("/sellers:batch"))
+        .json(&json!({
+            "mode": "best_effort",
+            "operations": [
+                { "op": "create", "name": "", "company_name": "Batch Company" },
+                { "op": "update", "id": seller_id, "name": " Trimmed Seller ", "company_name": "Batch Company" }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["results"][0]["status"], 400);
+    assert_eq!(body["results"][1]["status"], 200);
+
+    let response = app.client.get(app.url(&format!("/sellers/{}", seller_id)))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["name"], "Trimmed Seller");
+
+    let response = app.client.post(app.url("/sellers/import"))
+        .header("Content-Type", "text/csv")
+        .body("name,company_name\nImport Seller,Import Company\n ,Import Company\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 422);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 0);
+    assert_eq!(body["errors"][0]["line"], 3);
 
-    teardown_seller(&seller_id).await;
+    teardown_seller(&app, &seller_id).await;
 }
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::time::Duration;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+};
+use reqwest::{Client, Identity};
+use serde_json::json;
+use uuid::Uuid;
+
+const PORT: u16 = 3443;
+
+struct TestCa {
+    certificate: Certificate,
+    key: KeyPair,
+}
+
+impl TestCa {
+    fn new() -> Self {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name.push(DnType::CommonName, "Test CA");
+        let certificate = params.self_signed(&key).unwrap();
+        TestCa { certificate, key }
+    }
+
+    fn server(&self) -> (Certificate, KeyPair) {
+        let key = KeyPair::generate().unwrap();
+        let params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        let certificate = params.signed_by(&key, &self.certificate, &self.key).unwrap();
+        (certificate, key)
+    }
+
+    /// Client identity PEM, certificate followed by key
+    fn client(&self, common_name: &str) -> String {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.distinguished_name.push(DnType::CommonName, common_name);
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        let certificate = params.signed_by(&key, &self.certificate, &self.key).unwrap();
+        format!("{}{}", certificate.pem(), key.serialize_pem())
+    }
+}
+
+/// The server binary serving HTTPS with mutual TLS, killed when dropped
+struct TlsServer(Child);
+
+impl TlsServer {
+    fn start(dir: &Path) -> Self {
+        let child = Command::new(env!("CARGO_BIN_EXE_axum_web_starter"))
+            .env("PORT", PORT.to_string())
+            .env("GRPC_PORT", "0")
+            .env("JOB_WORKERS", "0")
+            .env("TLS_CERT_PATH", dir.join("server.pem"))
+            .env("TLS_KEY_PATH", dir.join("server.key"))
+            .env("TLS_CLIENT_CA_PATH", dir.join("ca.pem"))
+            .env("TLS_CLIENT_AUTH_OPTIONAL", "true")
+            .env("TLS_RELOAD_INTERVAL_SECS", "1")
+            .spawn()
+            .unwrap();
+        TlsServer(child)
+    }
+}
+
+impl Drop for TlsServer {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+    }
+}
+
+fn client(ca: &TestCa, identity: Option<&str>) -> Client {
+    let mut builder = Client::builder()
+        .use_rustls_tls()
+        .tls_built_in_root_certs(false)
+        .add_root_certificate(reqwest::Certificate::from_pem(ca.certificate.pem().as_bytes()).unwrap())
+        .tls_info(true);
+    if let Some(identity) = identity {
+        builder = builder.identity(Identity::from_pem(identity.as_bytes()).unwrap());
+    }
+    builder.build().unwrap()
+}
+
+async fn wait_until_ready(client: &Client) {
+    for _ in 0..60 {
+        if client.get(&format!("https://localhost:{}/", PORT)).send().await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    panic!("TLS server did not start");
+}
+
+fn peer_certificate(response: &reqwest::Response) -> Vec<u8> {
+    let tls_info = response.extensions().get::<reqwest::tls::TlsInfo>().unwrap();
+    tls_info.peer_certificate().unwrap().to_vec()
+}
+
+/// Issue a new certificate for localhost and return its DER bytes
+fn write_server_certificate(dir: &Path, ca: &TestCa) -> Vec<u8> {
+    let (certificate, key) = ca.server();
+    std::fs::write(dir.join("server.pem"), certificate.pem()).unwrap();
+    std::fs::write(dir.join("server.key"), key.serialize_pem()).unwrap();
+    certificate.der().to_vec()
+}
+
+#[tokio::test]
+async fn test_https_with_client_certificates() {
+    let dir: PathBuf = std::env::temp_dir().join(format!("axum-tls-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let ca = TestCa::new();
+    std::fs::write(dir.join("ca.pem"), ca.certificate.pem()).unwrap();
+    write_server_certificate(&dir, &ca);
+    let _server = TlsServer::start(&dir);
+
+    let service = client(&ca, Some(&ca.client("billing-service")));
+    let anonymous = client(&ca, None);
+    wait_until_ready(&anonymous).await;
+
+    // HTTP/2 is negotiated over ALPN
+    let response = service.get(&format!("https://localhost:{}/v1/sellers", PORT)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+
+    // Idempotency keys are scoped to the principal taken from the client certificate
+    let key = Uuid::new_v4().to_string();
+    let payload = json!({ "name": "TLS Customer", "email": format!("tls.{}@example.com", Uuid::new_v4()) });
+    let url = format!("https://localhost:{}/v1/customers", PORT);
+    let response = service.post(&url).header("idempotency-key", &key).json(&payload).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let response = service.post(&url).header("idempotency-key", &key).json(&payload).send().await.unwrap();
+    assert_eq!(response.headers()["idempotent-replayed"], "true");
+    let response = anonymous.post(&url).header("idempotency-key", &key).json(&payload).send().await.unwrap();
+    assert!(response.headers().get("idempotent-replayed").is_none());
+    assert_eq!(response.status(), 409);
+
+    // New connections pick up a replaced certificate without a restart
+    let replaced = write_server_certificate(&dir, &ca);
+    let mut reloaded = false;
+    for _ in 0..20 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let response = client(&ca, None).get(&format!("https://localhost:{}/", PORT)).send().await.unwrap();
+        if peer_certificate(&response) == replaced {
+            reloaded = true;
+            break;
+        }
+    }
+    assert!(reloaded, "Server kept the old certificate");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use axum_web_starter::unit_of_work;
+use uuid::Uuid;
+use super::harness::TestApp;
+
+fn cache_key(name: &str) -> String {
+    format!("uow-test:{}:{}", name, Uuid::new_v4())
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_serialization_failure_reruns_the_work() {
+    let app = TestApp::spawn_with_database().await;
+    let state = app.state();
+    let (first_key, second_key) = (cache_key("first"), cache_key("second"));
+    let attempts = Arc::new(AtomicU32::new(0));
+
+    let result = unit_of_work::run(&state, |uow| {
+        let attempts = attempts.clone();
+        let (first_key, second_key) = (first_key.clone(), second_key.clone());
+        Box::pin(async move {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                // The conflict CockroachDB reports, raised by hand
+                uow.cache_write(first_key, &"first attempt");
+                sqlx::query("DO $$ BEGIN RAISE EXCEPTION 'restart transaction' USING ERRCODE = '40001'; END $$")
+                    .execute(uow.connection().await?)
+                    .await?;
+            }
+            uow.cache_write(second_key, &"second attempt");
+            sqlx::query("SELECT 1").execute(uow.connection().await?).await?;
+            Ok::<_, sqlx::Error>("done")
+        })
+    })
+    .await;
+
+    assert_eq!(result.unwrap(), "done");
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    // Only the attempt that committed reaches the cache
+    assert_eq!(state.cache.get::<String>(&first_key).await, None);
+    assert_eq!(state.cache.get::<String>(&second_key).await.as_deref(), Some("second attempt"));
+}
+
+#[tokio::test]
+async fn test_rollback_drops_cache_changes() {
+    let app = TestApp::spawn().await;
+    let state = app.state();
+    let (written, deleted) = (cache_key("written"), cache_key("deleted"));
+    state.cache.put(&deleted, &"kept").await;
+
+    let result = unit_of_work::run(&state, |uow| {
+        let (written, deleted) = (written.clone(), deleted.clone());
+        Box::pin(async move {
+            uow.cache_write(written, &"never written");
+            uow.cache_delete(deleted);
+            Err::<(), _>(sqlx::Error::RowNotFound)
+        })
+    })
+    .await;
+
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(state.cache.get::<String>(&written).await, None);
+    assert_eq!(state.cache.get::<String>(&deleted).await.as_deref(), Some("kept"));
+}
+
+#[tokio::test]
+async fn test_cache_changes_apply_after_commit() {
+    let app = TestApp::spawn().await;
+    let state = app.state();
+    let key = cache_key("committed");
+
+    let result = unit_of_work::run(&state, |uow| {
+        let (state, key) = (state.clone(), key.clone());
+        Box::pin(async move {
+            uow.cache_write(key.clone(), &"committed");
+            // Still queued while the work runs
+            assert_eq!(state.cache.get::<String>(&key).await, None);
+            Ok::<_, sqlx::Error>(())
+        })
+    })
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(state.cache.get::<String>(&key).await.as_deref(), Some("committed"));
+}
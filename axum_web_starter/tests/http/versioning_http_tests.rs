@@ -0,0 +1,63 @@
+use super::harness::TestApp;
+
+#[tokio::test]
+async fn test_versioned_routes() {
+    let app = TestApp::spawn().await;
+    for prefix in ["/v1", "/v2"] {
+        let response = app.client.get(app.url(&format!("{}/sellers", prefix)))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().get("deprecation").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_unprefixed_aliases_are_deprecated() {
+    let app = TestApp::spawn().await;
+    let response = app.client.get(app.url("/sellers"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers()["deprecation"].to_str().unwrap().starts_with('@'));
+    assert_eq!(
+        response.headers()["link"].to_str().unwrap(),
+        "</v1/sellers>; rel=\"successor-version\""
+    );
+}
+
+#[tokio::test]
+async fn test_openapi_per_version() {
+    let app = TestApp::spawn().await;
+    let response = app.client.get(app.url("/v2/openapi.json"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let doc: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(doc["info"]["version"], "v2");
+    assert!(doc["paths"].get("/v2/customers").is_some());
+    assert!(doc["paths"].get("/customers").is_none());
+
+    let response = app.client.get(app.url("/openapi.json"))
+        .send()
+        .await
+        .unwrap();
+    let doc: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(doc["paths"]["/customers"]["get"]["deprecated"], true);
+}
+
+#[tokio::test]
+async fn test_deprecation_date_is_configurable() {
+    let app = TestApp::spawn_configured(|config| {
+        config.legacy_api_deprecated_at = "2026-01-01T00:00:00Z".parse().unwrap();
+    })
+    .await;
+    let response = app.client.get(app.url("/sellers"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["deprecation"], "@1767225600");
+}